@@ -0,0 +1,481 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use egui::{Color32, DragValue, Grid, RichText, ScrollArea, Ui, Window};
+use serde::{Deserialize, Serialize};
+
+use crate::api::Data;
+use crate::current_limit::{CurrentLimitConfig, CurrentWatchdog};
+use crate::i18n::tr;
+use crate::trend::TrendConfig;
+use crate::units;
+
+/// Alarm events of the session, appended as they happen
+const EVENTS_FILE: &str = "alarms.csv";
+const CSV_HEADER: &str = "time,event,alarm,value,unit";
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlarmConfig {
+    /// Cell voltage in mV above which the overvoltage alarm fires
+    pub max_voltage: u16,
+    /// Cell temperature in °C above which the overtemperature alarm fires
+    pub max_temp: f32,
+}
+
+impl Default for AlarmConfig {
+    fn default() -> Self {
+        Self {
+            max_voltage: 4200,
+            max_temp: 60.0,
+        }
+    }
+}
+
+impl AlarmConfig {
+    pub fn settings(&mut self, ui: &mut Ui) {
+        Grid::new("alarm_config").show(ui, |ui| {
+            ui.label(tr("Overvoltage"));
+            ui.add(
+                DragValue::new(&mut self.max_voltage)
+                    .clamp_range(3000..=4500)
+                    .suffix(" mV"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Overtemperature"));
+            ui.add(
+                DragValue::new(&mut self.max_temp)
+                    .clamp_range(20.0..=100.0)
+                    .suffix(" °C"),
+            );
+            ui.end_row();
+        });
+        ui.label(tr(
+            "The undervoltage alarm uses the alarm voltage of the trend settings",
+        ));
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub enum Kind {
+    Undervoltage,
+    Overvoltage,
+    Overtemperature,
+    Insulation,
+    PeakCurrent,
+    /// The I²t budget above the continuous current limit has been used up
+    Overcurrent,
+    /// Raised by the user script with the given text
+    Script(String),
+}
+
+impl Kind {
+    const ALL: [Kind; 4] = [
+        Kind::Undervoltage,
+        Kind::Overvoltage,
+        Kind::Overtemperature,
+        Kind::Insulation,
+    ];
+
+    pub fn name(&self) -> &str {
+        match self {
+            Kind::Undervoltage => tr("Undervoltage"),
+            Kind::Overvoltage => tr("Overvoltage"),
+            Kind::Overtemperature => tr("Overtemperature"),
+            Kind::Insulation => tr("Insulation fault"),
+            Kind::PeakCurrent => tr("Peak current"),
+            Kind::Overcurrent => tr("Overcurrent"),
+            Kind::Script(text) => text,
+        }
+    }
+
+    /// Whether lower values are worse
+    fn low_is_worse(&self) -> bool {
+        matches!(self, Kind::Undervoltage | Kind::Insulation)
+    }
+
+    /// Identifier used in the CSV files, independent of the language
+    fn id(&self) -> String {
+        match self {
+            Kind::Undervoltage => "undervoltage".into(),
+            Kind::Overvoltage => "overvoltage".into(),
+            Kind::Overtemperature => "overtemperature".into(),
+            Kind::Insulation => "insulation".into(),
+            Kind::PeakCurrent => "peak_current".into(),
+            Kind::Overcurrent => "overcurrent".into(),
+            Kind::Script(text) => format!("script:{}", text.replace([',', '\n'], " ")),
+        }
+    }
+
+    /// Unit of the raw values
+    fn unit(&self) -> &'static str {
+        match self {
+            Kind::Undervoltage | Kind::Overvoltage => "mV",
+            Kind::Overtemperature => "°C",
+            Kind::Insulation => "kΩ",
+            Kind::PeakCurrent => "A",
+            Kind::Overcurrent => "%",
+            Kind::Script(_) => "",
+        }
+    }
+
+    pub fn format(&self, value: f32) -> String {
+        match self {
+            Kind::Undervoltage | Kind::Overvoltage => units::voltage(value),
+            Kind::Overtemperature => units::temp(value),
+            Kind::Insulation => format!("{value:.0} kΩ"),
+            Kind::PeakCurrent => format!("{value:.0} A"),
+            Kind::Overcurrent => format!("{value:.0} %"),
+            Kind::Script(_) if value.is_nan() => "-".into(),
+            Kind::Script(_) => format!("{value:.2}"),
+        }
+    }
+}
+
+pub struct Alarm {
+    pub kind: Kind,
+    /// Unix time in ms when the alarm first fired
+    pub first: u128,
+    /// Most extreme value while the condition was present
+    pub worst: f32,
+    /// Whether the condition is still present
+    pub active: bool,
+    pub acknowledged: bool,
+}
+
+#[derive(Clone, Copy)]
+pub enum EventKind {
+    Fired,
+    Acknowledged,
+    Cleared,
+}
+
+impl EventKind {
+    fn name(self) -> &'static str {
+        match self {
+            EventKind::Fired => tr("Fired"),
+            EventKind::Acknowledged => tr("Acknowledged"),
+            EventKind::Cleared => tr("Cleared"),
+        }
+    }
+
+    fn id(self) -> &'static str {
+        match self {
+            EventKind::Fired => "fired",
+            EventKind::Acknowledged => "acknowledged",
+            EventKind::Cleared => "cleared",
+        }
+    }
+}
+
+pub struct AlarmEvent {
+    /// Unix time in ms
+    pub time: u128,
+    pub kind: EventKind,
+    pub alarm: Kind,
+    /// The value that fired the alarm, otherwise the worst value so far
+    pub value: f32,
+}
+
+impl AlarmEvent {
+    fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{}",
+            self.time,
+            self.kind.id(),
+            self.alarm.id(),
+            self.value,
+            self.alarm.unit()
+        )
+    }
+}
+
+/// Alarms stay latched until acknowledged, even if the condition clears in the meantime, so brief
+/// excursions between two glances at the screen are not missed
+#[derive(Default)]
+pub struct AlarmList {
+    pub alarms: Vec<Alarm>,
+    /// Every activation, acknowledgment and clearing since the start
+    pub events: Vec<AlarmEvent>,
+    /// Number of events already written to the session
+    saved: usize,
+    pub show_log: bool,
+    /// Set when an alarm fired, until the user's attention was requested
+    fired: bool,
+}
+
+impl AlarmList {
+    pub fn update(
+        &mut self,
+        config: &AlarmConfig,
+        trend: &TrendConfig,
+        insulation_warning: f32,
+        data: &Data,
+        time: u128,
+    ) {
+        for kind in Kind::ALL {
+            let value = match kind {
+                Kind::Undervoltage => {
+                    let v = data.ucell.overall.min_voltage;
+                    (v < trend.alarm_voltage).then_some(v as f32)
+                }
+                Kind::Overvoltage => {
+                    let v = data.ucell.overall.max_voltage;
+                    (v > config.max_voltage).then_some(v as f32)
+                }
+                Kind::Overtemperature => {
+                    let t = data.tcell.overall.max_temp;
+                    (t > config.max_temp).then_some(t)
+                }
+                Kind::Insulation => data
+                    .status
+                    .as_ref()
+                    .filter(|s| !s.imd_ok || s.insulation_resistance < insulation_warning)
+                    .map(|s| s.insulation_resistance),
+                Kind::PeakCurrent | Kind::Overcurrent | Kind::Script(_) => {
+                    unreachable!("not part of Kind::ALL")
+                }
+            };
+            self.set(kind, value, time);
+        }
+    }
+
+    /// Sets the alarms of the current watchdog, the overcurrent value is the used part of the budget
+    pub fn update_current(
+        &mut self,
+        config: &CurrentLimitConfig,
+        watchdog: &CurrentWatchdog,
+        time: u128,
+    ) {
+        self.set(Kind::PeakCurrent, watchdog.peak, time);
+        self.set(Kind::Overcurrent, watchdog.exceeded(config), time);
+    }
+
+    /// Sets the alarms raised by the user script with their text and value, alarms that are no
+    /// longer raised are cleared
+    pub fn update_script(&mut self, raised: &[(String, f32)], time: u128) {
+        let cleared: Vec<Kind> = self
+            .alarms
+            .iter()
+            .filter(|a| a.active)
+            .filter(|a| match &a.kind {
+                Kind::Script(text) => !raised.iter().any(|(t, _)| t == text),
+                _ => false,
+            })
+            .map(|a| a.kind.clone())
+            .collect();
+        for kind in cleared {
+            self.set(kind, None, time);
+        }
+        for (text, value) in raised {
+            self.set(Kind::Script(text.clone()), Some(*value), time);
+        }
+    }
+
+    fn set(&mut self, kind: Kind, value: Option<f32>, time: u128) {
+        let existing = self.alarms.iter().position(|a| a.kind == kind);
+        match (existing, value) {
+            (Some(i), Some(value)) => {
+                let alarm = &mut self.alarms[i];
+                let fired = !alarm.active;
+                alarm.active = true;
+                alarm.worst = match kind.low_is_worse() {
+                    true => alarm.worst.min(value),
+                    false => alarm.worst.max(value),
+                };
+                // a latched alarm that wasn't acknowledged yet fires again
+                if fired {
+                    self.log(EventKind::Fired, kind, value, time);
+                }
+            }
+            (None, Some(value)) => {
+                self.alarms.push(Alarm {
+                    kind: kind.clone(),
+                    first: time,
+                    worst: value,
+                    active: true,
+                    acknowledged: false,
+                });
+                self.log(EventKind::Fired, kind, value, time);
+            }
+            (Some(i), None) if self.alarms[i].active => {
+                let alarm = &mut self.alarms[i];
+                alarm.active = false;
+                let worst = alarm.worst;
+                if alarm.acknowledged {
+                    self.alarms.remove(i);
+                }
+                self.log(EventKind::Cleared, kind, worst, time);
+            }
+            (_, None) => (),
+        }
+    }
+
+    fn log(&mut self, kind: EventKind, alarm: Kind, value: f32, time: u128) {
+        self.fired |= matches!(kind, EventKind::Fired);
+        self.events.push(AlarmEvent {
+            time,
+            kind,
+            alarm,
+            value,
+        });
+    }
+
+    /// Appends the new events to the session in `directory`
+    pub fn save(&mut self, directory: &Path) -> io::Result<()> {
+        if self.saved == self.events.len() {
+            return Ok(());
+        }
+        fs::create_dir_all(directory)?;
+        let path = directory.join(EVENTS_FILE);
+        let new_file = !path.exists();
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        if new_file {
+            writeln!(file, "{CSV_HEADER}")?;
+        }
+        for event in &self.events[self.saved..] {
+            writeln!(file, "{}", event.to_csv())?;
+        }
+        self.saved = self.events.len();
+        Ok(())
+    }
+
+    /// Forgets the alarms and events without saving them, used when switching between live data
+    /// and replays
+    pub fn reset(&mut self) {
+        let show_log = self.show_log;
+        *self = Self {
+            show_log,
+            ..Default::default()
+        };
+    }
+
+    /// Whether an alarm fired since the last call
+    pub fn take_fired(&mut self) -> bool {
+        std::mem::take(&mut self.fired)
+    }
+
+    pub fn has_unacknowledged(&self) -> bool {
+        self.alarms.iter().any(|a| !a.acknowledged)
+    }
+
+    /// Alarms whose condition is still present stay in the list until it clears
+    pub fn acknowledge_all(&mut self, time: u128) {
+        for i in (0..self.alarms.len()).rev() {
+            if !self.alarms[i].acknowledged {
+                self.acknowledge(i, time);
+            }
+        }
+    }
+
+    fn acknowledge(&mut self, index: usize, time: u128) {
+        let alarm = &mut self.alarms[index];
+        alarm.acknowledged = true;
+        let (kind, worst) = (alarm.kind.clone(), alarm.worst);
+        if !alarm.active {
+            self.alarms.remove(index);
+        }
+        self.log(EventKind::Acknowledged, kind, worst, time);
+    }
+
+    pub fn banner(&mut self, ui: &mut Ui, now: u128) {
+        let ago = |time: u128| {
+            let seconds = now.saturating_sub(time) / 1000;
+            format!("{}:{:02} {}", seconds / 60, seconds % 60, tr("ago"))
+        };
+        let mut acknowledged = None;
+        for (i, alarm) in self.alarms.iter().enumerate() {
+            if alarm.acknowledged {
+                continue;
+            }
+            ui.horizontal(|ui| {
+                let state = match alarm.active {
+                    true => tr("active"),
+                    false => tr("cleared"),
+                };
+                let text = format!(
+                    "⚠ {}: {} {}, {} {} ({state})",
+                    alarm.kind.name(),
+                    tr("worst"),
+                    alarm.kind.format(alarm.worst),
+                    tr("first"),
+                    ago(alarm.first),
+                );
+                ui.label(
+                    RichText::new(text)
+                        .color(Color32::WHITE)
+                        .background_color(Color32::from_rgb(0xc0, 0x30, 0x30))
+                        .strong(),
+                );
+                if ui.button(tr("Acknowledge")).clicked() {
+                    acknowledged = Some(i);
+                }
+            });
+        }
+        if let Some(i) = acknowledged {
+            self.acknowledge(i, now);
+        }
+    }
+
+    /// Returns the result of an export
+    pub fn log_window(
+        &mut self,
+        ctx: &egui::Context,
+        now: u128,
+        directory: &Path,
+    ) -> Option<io::Result<PathBuf>> {
+        let mut result = None;
+        let mut show = self.show_log;
+        Window::new(tr("Alarm log"))
+            .id("alarm_log".into())
+            .open(&mut show)
+            .default_size([500.0, 300.0])
+            .show(ctx, |ui| {
+                if self.events.is_empty() {
+                    ui.label(tr("No alarms so far"));
+                    return;
+                }
+                if ui.button(tr("Export CSV")).clicked() {
+                    result = Some(self.export(directory, now));
+                }
+
+                let ago = |time: u128| {
+                    let seconds = now.saturating_sub(time) / 1000;
+                    format!("{}:{:02} {}", seconds / 60, seconds % 60, tr("ago"))
+                };
+                ScrollArea::vertical().show(ui, |ui| {
+                    Grid::new("alarm_log").striped(true).show(ui, |ui| {
+                        ui.strong(tr("Time"));
+                        ui.strong(tr("Event"));
+                        ui.strong(tr("Alarm"));
+                        ui.strong(tr("Value"));
+                        ui.end_row();
+
+                        for event in self.events.iter().rev() {
+                            ui.label(ago(event.time));
+                            ui.label(event.kind.name());
+                            ui.label(event.alarm.name());
+                            ui.label(event.alarm.format(event.value));
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+        self.show_log = show;
+        result
+    }
+
+    fn export(&self, directory: &Path, now: u128) -> io::Result<PathBuf> {
+        fs::create_dir_all(directory)?;
+        let path = directory.join(format!("alarm_log_{now}.csv"));
+        let mut file = BufWriter::new(File::create(&path)?);
+        writeln!(file, "{CSV_HEADER}")?;
+        for event in &self.events {
+            writeln!(file, "{}", event.to_csv())?;
+        }
+        file.flush()?;
+        Ok(path)
+    }
+}
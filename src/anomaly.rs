@@ -0,0 +1,156 @@
+use egui::{DragValue, Grid, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::history::{slope, History};
+use crate::i18n::tr;
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnomalyConfig {
+    pub enabled: bool,
+    /// Rolling window in s
+    pub window: u64,
+    /// Minimum absolute z-score of a cell to be flagged
+    pub min_z_score: f32,
+    /// Minimum growth of the absolute z-score per minute to be flagged
+    pub min_growth: f32,
+    /// Number of cells with the largest change since the previous sample to outline, 0 disables
+    /// the highlighting
+    pub highlight_changes: usize,
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window: 600,
+            min_z_score: 2.0,
+            min_growth: 0.1,
+            highlight_changes: 0,
+        }
+    }
+}
+
+impl AnomalyConfig {
+    pub fn settings(&mut self, ui: &mut Ui) {
+        ui.checkbox(&mut self.enabled, tr("Detect diverging cells"));
+        Grid::new("anomaly_config").show(ui, |ui| {
+            ui.label(tr("Rolling window"));
+            ui.add(
+                DragValue::new(&mut self.window)
+                    .clamp_range(30..=3600)
+                    .suffix(" s"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Minimum z-score"));
+            ui.add(
+                DragValue::new(&mut self.min_z_score)
+                    .clamp_range(0.5..=10.0)
+                    .speed(0.1),
+            );
+            ui.end_row();
+
+            ui.label(tr("Minimum growth"));
+            ui.add(
+                DragValue::new(&mut self.min_growth)
+                    .clamp_range(0.01..=5.0)
+                    .speed(0.01)
+                    .suffix(" /min"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Highlight changed cells"));
+            ui.add(DragValue::new(&mut self.highlight_changes).clamp_range(0..=20));
+            ui.end_row();
+        });
+    }
+}
+
+pub struct DivergingCell {
+    pub index: usize,
+    pub z_score: f32,
+    /// Growth of the absolute z-score per minute
+    pub growth: f32,
+}
+
+/// Flags cells whose z-score against the pack mean keeps growing over the window
+pub fn diverging_cells(config: &AnomalyConfig, history: &History) -> Vec<DivergingCell> {
+    let samples: Vec<_> = history.window(config.window as u128 * 1000).collect();
+    let Some(first) = samples.first() else {
+        return Vec::new();
+    };
+    let num_cells = first.cell_voltage.len();
+
+    // absolute z-score of every cell for every sample
+    let z_scores: Vec<(f64, Vec<f32>)> = samples
+        .iter()
+        .filter(|s| s.cell_voltage.len() == num_cells)
+        .map(|s| {
+            let n = num_cells as f32;
+            let mean = s.cell_voltage.iter().map(|v| *v as f32).sum::<f32>() / n;
+            let variance = s
+                .cell_voltage
+                .iter()
+                .map(|v| (*v as f32 - mean).powi(2))
+                .sum::<f32>()
+                / n;
+            let std_dev = variance.sqrt().max(1.0);
+            let z = s
+                .cell_voltage
+                .iter()
+                .map(|v| ((*v as f32 - mean) / std_dev).abs())
+                .collect();
+            ((s.time - first.time) as f64 / 60_000.0, z)
+        })
+        .collect();
+    let Some((_, last_z)) = z_scores.last() else {
+        return Vec::new();
+    };
+
+    (0..num_cells)
+        .filter_map(|i| {
+            let growth = slope(z_scores.iter().map(|(t, z)| (*t, z[i] as f64)))? as f32;
+            let z_score = last_z[i];
+            (z_score >= config.min_z_score && growth >= config.min_growth).then_some(
+                DivergingCell {
+                    index: i,
+                    z_score,
+                    growth,
+                },
+            )
+        })
+        .collect()
+}
+
+pub struct ChangedCell {
+    pub index: usize,
+    /// Change since the previous sample in mV
+    pub delta: i32,
+}
+
+/// The cells with the largest absolute change between the last two samples
+pub fn changed_cells(config: &AnomalyConfig, history: &History) -> Vec<ChangedCell> {
+    let mut samples = history.samples().rev();
+    let (Some(last), Some(previous)) = (samples.next(), samples.next()) else {
+        return Vec::new();
+    };
+    if config.highlight_changes == 0 || last.cell_voltage.len() != previous.cell_voltage.len() {
+        return Vec::new();
+    }
+
+    let mut changed: Vec<_> = last
+        .cell_voltage
+        .iter()
+        .zip(&previous.cell_voltage)
+        .enumerate()
+        .map(|(index, (v, p))| ChangedCell {
+            index,
+            delta: *v as i32 - *p as i32,
+        })
+        .filter(|c| c.delta != 0)
+        .collect();
+    changed.sort_by_key(|c| std::cmp::Reverse(c.delta.abs()));
+    changed.truncate(config.highlight_changes);
+    changed
+}
@@ -1,15 +1,24 @@
-use std::cmp;
+use std::io::{BufRead, BufReader, Read};
 use std::str::{FromStr, Split};
 use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+use flate2::read::{GzDecoder, ZlibDecoder};
 use lazy_static::lazy_static;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::app::now;
+use crate::i18n::tr;
 
 lazy_static! {
     static ref MAIN_PATTERN: Regex = Regex::new("Parametersatz = \"([^\"]*)\"").unwrap();
     static ref UCELL_STATS_PATTERN: Regex = Regex::new("PSet0 = \"([^\"]*)\"").unwrap();
     static ref UCELL_CELLS_PATTERN: Regex = Regex::new("PSet = \"([^\"]*)\"").unwrap();
     static ref TCELL_PATTERN: Regex = Regex::new("PSet = \"([^\"]*)\"").unwrap();
+    static ref STATUS_PATTERN: Regex = Regex::new("Status = \"([^\"]*)\"").unwrap();
+    static ref FIRMWARE_PATTERN: Regex = Regex::new("Firmware = \"([^\"]*)\"").unwrap();
+    static ref SETTINGS_PATTERN: Regex = Regex::new("Einstellungen = \"([^\"]*)\"").unwrap();
 }
 
 pub enum Error {
@@ -17,14 +26,295 @@ pub enum Error {
     Fetch(anyhow::Error),
 }
 
-#[derive(Default)]
+/// A pre-fetched request older than this is outdated, e.g. after polling was paused, and polls
+/// further apart are not pipelined
+const MAX_PREFETCH_AGE: Duration = Duration::from_secs(1);
+
+/// A failed probe of the firmware version is retried after this time, e.g. once the BMS has booted
+const PROBE_RETRY: Duration = Duration::from_secs(10);
+
+/// Settings applied to every polled sample
+#[derive(Clone, PartialEq)]
+pub struct PollOptions {
+    pub safe: bool,
+    /// Excluded from the temperature statistics
+    pub dead_sensors: Vec<usize>,
+    /// Issue the next request while the previous one is still being received and parsed
+    pub pipelining: bool,
+    /// Layout of the pages, detected from the firmware version if `None`
+    pub format: Option<Format>,
+}
+
+/// Layout of the pages of the web interface, which changed between firmware versions
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Format {
+    #[default]
+    Current,
+    /// Firmware before 2.0, sends the current before the voltage and only the slave count in front
+    /// of the cell voltages
+    Legacy,
+}
+
+impl Format {
+    pub const ALL: [Format; 2] = [Format::Current, Format::Legacy];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Format::Current => tr("Current firmware"),
+            Format::Legacy => tr("Firmware before 2.0"),
+        }
+    }
+
+    /// Format of a firmware version like `2.3.1` or `v1.8`, versions that can't be read are
+    /// assumed to be current
+    pub fn detect(firmware: &str) -> Format {
+        let version = firmware.trim().trim_start_matches(['v', 'V']);
+        let major = version
+            .split(['.', ' '])
+            .next()
+            .and_then(|m| m.parse::<u32>().ok());
+        match major {
+            Some(major) if major < 2 => Format::Legacy,
+            _ => Format::Current,
+        }
+    }
+
+    /// Values in front of the cell voltages in `PSet`
+    fn cell_prefix(self) -> usize {
+        match self {
+            Format::Current => 2,
+            Format::Legacy => 1,
+        }
+    }
+}
+
+/// Anything the dashboard can receive data from, polled on a background thread
+pub trait DataSource: Send {
+    /// Blocks until the next sample is available
+    fn poll(&mut self, options: &PollOptions) -> Result<Data, Error>;
+
+    /// Whether the source can still deliver samples, otherwise it is opened again
+    fn is_alive(&self) -> bool {
+        true
+    }
+
+    /// Unix time in ms the last polled sample was requested, if it was requested ahead instead of
+    /// when it was returned
+    fn sample_time(&self) -> Option<u128> {
+        None
+    }
+}
+
+/// Address of the BMS web interface and everything applied to the requests sent to it
+#[derive(Clone)]
+pub struct Connection {
+    pub ip: String,
+    /// Sent with every request, e.g. an API key or a cookie required by a reverse proxy
+    pub headers: Vec<(String, String)>,
+    /// HTTP or SOCKS proxy, e.g. `socks5://jumphost:1080`
+    pub proxy: Option<String>,
+}
+
+impl Connection {
+    /// Reads the page line by line until every pattern matched, the data is near the top of the
+    /// pages and the BMS serves them slowly. Returns only the matching lines.
+    fn scan(&self, page: &str, patterns: &[&Regex]) -> anyhow::Result<String> {
+        let resp = self.request("GET", page)?.call()?;
+        let mut reader = BufReader::new(body_reader(resp));
+        let mut found = vec![false; patterns.len()];
+        let mut text = String::new();
+        let mut line = String::new();
+        while found.contains(&false) {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                break;
+            }
+            let mut matched = false;
+            for (pattern, found) in patterns.iter().zip(&mut found) {
+                if !*found && pattern.is_match(&line) {
+                    *found = true;
+                    matched = true;
+                }
+            }
+            if matched {
+                text.push_str(&line);
+            }
+        }
+        Ok(text)
+    }
+
+    /// Like `get`, but also returns the status code of error responses
+    fn get_with_status(&self, page: &str) -> (Option<u16>, anyhow::Result<String>) {
+        let request = match self.request("GET", page) {
+            Ok(r) => r,
+            Err(e) => return (None, Err(e)),
+        };
+        match request.call() {
+            Ok(resp) => (Some(resp.status()), read_body(resp).map_err(Into::into)),
+            Err(ureq::Error::Status(code, resp)) => {
+                let text = resp.status_text().to_string();
+                (Some(code), Err(anyhow::anyhow!("{code} {text}")))
+            }
+            Err(e) => (None, Err(e.into())),
+        }
+    }
+
+    fn post_form(&self, page: &str, form: &[(&str, &str)]) -> anyhow::Result<()> {
+        self.request("POST", page)?.send_form(form)?;
+        Ok(())
+    }
+
+    /// Sends `data` as a `multipart/form-data` file field like the upload form of the web interface
+    fn post_file(
+        &self,
+        page: &str,
+        field: &str,
+        file_name: &str,
+        data: &[u8],
+    ) -> anyhow::Result<()> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let boundary = format!("----s3bmsdashboard{nanos}");
+        let mut body = format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"{field}\"; \
+             filename=\"{file_name}\"\r\nContent-Type: application/octet-stream\r\n\r\n"
+        )
+        .into_bytes();
+        body.extend_from_slice(data);
+        body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+
+        let content_type = format!("multipart/form-data; boundary={boundary}");
+        self.request("POST", page)?
+            .set("Content-Type", &content_type)
+            .send_bytes(&body)?;
+        Ok(())
+    }
+
+    fn request(&self, method: &str, page: &str) -> anyhow::Result<ureq::Request> {
+        let url = format!("{}/{page}", self.ip);
+        let mut agent = ureq::AgentBuilder::new();
+        if let Some(proxy) = &self.proxy {
+            agent = agent.proxy(ureq::Proxy::new(proxy)?);
+        }
+        let mut request = agent
+            .build()
+            .request(method, &url)
+            .set("Accept-Encoding", "gzip, deflate");
+        for (name, value) in &self.headers {
+            request = request.set(name, value);
+        }
+        Ok(request)
+    }
+}
+
+/// Decodes the body according to its `Content-Encoding`, the cell pages are large and compress well
+fn body_reader(resp: ureq::Response) -> Box<dyn Read> {
+    let encoding = resp.header("Content-Encoding").map(str::to_ascii_lowercase);
+    let reader = resp.into_reader();
+    match encoding.as_deref() {
+        Some("gzip" | "x-gzip") => Box::new(GzDecoder::new(reader)),
+        Some("deflate") => Box::new(ZlibDecoder::new(reader)),
+        _ => reader,
+    }
+}
+
+fn read_body(resp: ureq::Response) -> std::io::Result<String> {
+    let mut text = String::new();
+    body_reader(resp).read_to_string(&mut text)?;
+    Ok(text)
+}
+
+/// Scrapes the pages of the BMS web interface
+pub struct HttpSource {
+    pub connection: Connection,
+    /// Request issued ahead for the next poll when pipelining, with its options, format and start
+    /// time
+    pending: Option<(Request, PollOptions, Format, Instant)>,
+    last_poll: Option<Instant>,
+    /// Time the request of the last returned sample was issued
+    sample_time: Option<u128>,
+    /// Format probed from the firmware version
+    detected: Option<Format>,
+    /// Time of the last failed probe, the default format is used until it is retried
+    probe_failed: Option<Instant>,
+}
+
+impl HttpSource {
+    pub fn new(connection: Connection) -> Self {
+        Self {
+            connection,
+            pending: None,
+            last_poll: None,
+            sample_time: None,
+            detected: None,
+            probe_failed: None,
+        }
+    }
+
+    /// The manually selected format, otherwise the one of the firmware version, which is probed
+    /// once
+    fn format(&mut self, options: &PollOptions) -> Format {
+        if let Some(format) = options.format.or(self.detected) {
+            return format;
+        }
+        // every probe blocks the poll, so a BMS without a settings page isn't asked every time
+        if self.probe_failed.is_some_and(|t| t.elapsed() < PROBE_RETRY) {
+            return Format::default();
+        }
+        match probe_format(&self.connection) {
+            Ok(format) => *self.detected.insert(format),
+            Err(_) => {
+                self.probe_failed = Some(Instant::now());
+                Format::default()
+            }
+        }
+    }
+}
+
+impl DataSource for HttpSource {
+    fn poll(&mut self, options: &PollOptions) -> Result<Data, Error> {
+        let polled_recently = self
+            .last_poll
+            .replace(Instant::now())
+            .is_some_and(|t| t.elapsed() < MAX_PREFETCH_AGE);
+        let format = self.format(options);
+        let request = match self.pending.take() {
+            Some((request, o, f, started))
+                if o == *options && f == format && started.elapsed() < MAX_PREFETCH_AGE =>
+            {
+                request
+            }
+            // an outdated request is left to finish on its own
+            _ => fetch(&self.connection, options, format),
+        };
+        self.sample_time = Some(request.time);
+        let result = request.join();
+        // at most one request ahead, so a BMS answering slower than polled isn't flooded. It is
+        // issued after the current one was received, otherwise both would return the same data.
+        if options.pipelining && polled_recently {
+            let next = fetch(&self.connection, options, format);
+            self.pending = Some((next, options.clone(), format, Instant::now()));
+        }
+        result
+    }
+
+    fn sample_time(&self) -> Option<u128> {
+        self.sample_time
+    }
+}
+
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct Data {
     pub main: Main,
     pub ucell: Ucell,
     pub tcell: Tcell,
+    /// Not every firmware provides a status page
+    pub status: Option<Status>,
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, Copy, Serialize, Deserialize)]
 pub struct Main {
     // in mV
     pub voltage: f32,
@@ -37,9 +327,30 @@ pub struct Main {
     pub temp_min: f32,
     pub temp_max: f32,
     pub temp_master: f32,
+    /// Only provided by newer firmware versions
+    pub relays: Option<Relays>,
+    /// Voltage behind the AIRs in V, only provided by newer firmware versions
+    pub dc_link_voltage: Option<f32>,
+    /// Time since the BMS started in ms, the timebase of its internal error log, only provided by
+    /// newer firmware versions
+    pub uptime: Option<u64>,
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, Copy, Serialize, Deserialize)]
+pub struct Relays {
+    pub air_plus: bool,
+    pub air_minus: bool,
+    pub precharge: bool,
+    pub shutdown_circuit: bool,
+}
+
+impl Relays {
+    pub fn ts_active(&self) -> bool {
+        self.air_plus && self.air_minus
+    }
+}
+
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct Ucell {
     pub num_slaves: usize,
     pub num_cells: usize,
@@ -54,7 +365,7 @@ pub struct Ucell {
     pub cell_voltage: Vec<u16>,
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct VoltageStats {
     // in mV
     pub avg_voltage: u16,
@@ -63,7 +374,7 @@ pub struct VoltageStats {
     pub delta_voltage: u16,
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct Tcell {
     pub overall: TempStats,
     pub left: TempStats,
@@ -72,7 +383,7 @@ pub struct Tcell {
     pub temp: Vec<f32>,
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct TempStats {
     pub avg_temp: f32,
     pub min_temp: f32,
@@ -80,39 +391,67 @@ pub struct TempStats {
     pub delta_temp: f32,
 }
 
-pub struct Request {
+#[derive(Default, Clone, Copy, Serialize, Deserialize)]
+pub struct Status {
+    // in kΩ
+    pub insulation_resistance: f32,
+    pub imd_ok: bool,
+}
+
+/// Parameter set flashed onto the BMS
+pub struct BmsInfo {
+    pub firmware: String,
+    pub num_slaves: usize,
+    pub num_cells: usize,
+    pub num_temp_sensors: usize,
+    // in A
+    pub max_discharge_current: f32,
+    pub max_charge_current: f32,
+    // in mV
+    pub min_cell_voltage: u16,
+    pub max_cell_voltage: u16,
+    // in °C
+    pub max_cell_temp: f32,
+}
+
+struct Request {
+    /// Unix time in ms the request was issued
+    time: u128,
     main_task: JoinHandle<anyhow::Result<Main>>,
     ucell_task: JoinHandle<anyhow::Result<Ucell>>,
     tcell_task: JoinHandle<anyhow::Result<Tcell>>,
+    status_task: JoinHandle<anyhow::Result<Status>>,
 }
 
-pub fn fetch(ip: &str, safe: bool) -> Request {
-    let owned_ip = ip.to_string();
-    let main_task = thread::spawn(move || main_data(&owned_ip));
-    let owned_ip = ip.to_string();
-    let ucell_task = thread::spawn(move || ucell(&owned_ip, safe));
-    let owned_ip = ip.to_string();
-    let tcell_task = thread::spawn(move || tcell(&owned_ip, safe));
+fn fetch(connection: &Connection, options: &PollOptions, format: Format) -> Request {
+    let safe = options.safe;
+    let owned = connection.clone();
+    let main_task = thread::spawn(move || main_data(&owned, format));
+    let owned = connection.clone();
+    let ucell_task = thread::spawn(move || ucell(&owned, safe, format));
+    let owned = connection.clone();
+    // excluded from the temperature statistics
+    let dead_sensors = options.dead_sensors.clone();
+    let tcell_task = thread::spawn(move || tcell(&owned, safe, &dead_sensors));
+    let owned = connection.clone();
+    let status_task = thread::spawn(move || status(&owned));
 
     Request {
+        time: now(),
         main_task,
         ucell_task,
         tcell_task,
+        status_task,
     }
 }
 
 impl Request {
-    pub fn is_finished(&self) -> bool {
-        self.main_task.is_finished()
-            && self.ucell_task.is_finished()
-            && self.tcell_task.is_finished()
-    }
-
-    pub fn join(self) -> Result<Data, Error> {
+    fn join(self) -> Result<Data, Error> {
         Ok(Data {
             main: join_task(self.main_task)?,
             ucell: join_task(self.ucell_task)?,
             tcell: join_task(self.tcell_task)?,
+            status: join_task(self.status_task).ok(),
         })
     }
 }
@@ -125,19 +464,29 @@ fn join_task<T>(task: JoinHandle<anyhow::Result<T>>) -> Result<T, Error> {
     }
 }
 
-fn main_data(ip: &str) -> anyhow::Result<Main> {
-    let url = format!("{ip}/main_data.shtml");
-    let resp = ureq::get(&url).call()?;
-    let text = resp.into_string()?;
+fn main_data(connection: &Connection, format: Format) -> anyhow::Result<Main> {
+    parse_main(
+        &connection.scan("main_data.shtml", &[&MAIN_PATTERN])?,
+        format,
+    )
+}
 
-    let stats_captures = MAIN_PATTERN.captures(&text).unwrap();
+fn parse_main(text: &str, format: Format) -> anyhow::Result<Main> {
+    let Some(stats_captures) = MAIN_PATTERN.captures(text) else {
+        anyhow::bail!("Parametersatz not found");
+    };
     let mut stats_iter = stats_captures.get(1).unwrap().as_str().split(',');
 
     skip(&mut stats_iter, 1);
-    let voltage = parse_next::<f32>(&mut stats_iter)? / 1000.0;
+    let first = parse_next::<f32>(&mut stats_iter)?;
 
     skip(&mut stats_iter, 2);
-    let current = parse_next(&mut stats_iter)?;
+    let second = parse_next::<f32>(&mut stats_iter)?;
+
+    let (voltage, current) = match format {
+        Format::Current => (first / 1000.0, second),
+        Format::Legacy => (second / 1000.0, first),
+    };
 
     skip(&mut stats_iter, 2);
     let state_of_charge = parse_next::<f32>(&mut stats_iter)? / 10.0;
@@ -154,6 +503,14 @@ fn main_data(ip: &str) -> anyhow::Result<Main> {
     skip(&mut stats_iter, 2);
     let temp_master = parse_next::<f32>(&mut stats_iter)? / 10.0;
 
+    let relays = relays(&mut stats_iter).ok();
+
+    skip(&mut stats_iter, 2);
+    let dc_link_voltage = parse_next::<f32>(&mut stats_iter).ok().map(|v| v / 1000.0);
+
+    skip(&mut stats_iter, 2);
+    let uptime = parse_next::<u64>(&mut stats_iter).ok();
+
     Ok(Main {
         voltage,
         current,
@@ -162,70 +519,235 @@ fn main_data(ip: &str) -> anyhow::Result<Main> {
         temp_min,
         temp_max,
         temp_master,
+        relays,
+        dc_link_voltage,
+        uptime,
+    })
+}
+
+fn relays(stats_iter: &mut Split<char>) -> anyhow::Result<Relays> {
+    skip(stats_iter, 2);
+    let air_plus = parse_next::<u8>(stats_iter)? != 0;
+
+    skip(stats_iter, 2);
+    let air_minus = parse_next::<u8>(stats_iter)? != 0;
+
+    skip(stats_iter, 2);
+    let precharge = parse_next::<u8>(stats_iter)? != 0;
+
+    skip(stats_iter, 2);
+    let shutdown_circuit = parse_next::<u8>(stats_iter)? != 0;
+
+    Ok(Relays {
+        air_plus,
+        air_minus,
+        precharge,
+        shutdown_circuit,
+    })
+}
+
+/// Result of fetching and parsing one page of the web interface on its own
+pub struct EndpointCheck {
+    pub page: &'static str,
+    /// `None` if no response was received
+    pub status: Option<u16>,
+    pub latency: Duration,
+    /// Summary of the parsed values
+    pub result: anyhow::Result<String>,
+}
+
+/// The pages polled for the live data
+pub const ENDPOINTS: [&str; 3] = ["main_data.shtml", "ucell.shtml", "tcell.shtml"];
+
+pub fn check_endpoint(
+    connection: &Connection,
+    page: &'static str,
+    format: Format,
+) -> EndpointCheck {
+    let start = Instant::now();
+    let (status, text) = connection.get_with_status(page);
+    let latency = start.elapsed();
+    let result = text.and_then(|text| match page {
+        "main_data.shtml" => parse_main(&text, format).map(|m| {
+            format!(
+                "{:.1} V, {:.0} mA, {:.1} %",
+                m.voltage, m.current, m.state_of_charge
+            )
+        }),
+        "ucell.shtml" => parse_ucell(&text, false, format).map(|u| {
+            format!(
+                "{} cells, {}-{} mV",
+                u.cell_voltage.len(),
+                u.overall.min_voltage,
+                u.overall.max_voltage
+            )
+        }),
+        "tcell.shtml" => parse_tcell(&text, false, &[]).map(|t| {
+            format!(
+                "{} sensors, {:.1}-{:.1} °C",
+                t.temp.len(),
+                t.overall.min_temp,
+                t.overall.max_temp
+            )
+        }),
+        _ => Ok(format!("{} bytes", text.len())),
+    });
+    EndpointCheck {
+        page,
+        status,
+        latency,
+        result,
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Command {
+    EnableBalancing,
+    DisableBalancing,
+    ResetErrors,
+}
+
+impl Command {
+    fn param(self) -> &'static str {
+        match self {
+            Command::EnableBalancing => "balancing_on",
+            Command::DisableBalancing => "balancing_off",
+            Command::ResetErrors => "reset_errors",
+        }
+    }
+}
+
+/// Sends a command the same way the web interface does
+pub fn send_command(connection: &Connection, command: Command) -> JoinHandle<anyhow::Result<()>> {
+    let connection = connection.clone();
+    thread::spawn(move || connection.post_form("control.cgi", &[("cmd", command.param())]))
+}
+
+/// Uploads a firmware image, the BMS restarts once it has been flashed
+pub fn upload_firmware(
+    connection: &Connection,
+    file_name: String,
+    image: Vec<u8>,
+) -> JoinHandle<anyhow::Result<()>> {
+    let connection = connection.clone();
+    thread::spawn(move || connection.post_file("update.cgi", "firmware", &file_name, &image))
+}
+
+/// The parameter set doesn't change at runtime, so it is only fetched on demand
+pub fn fetch_info(connection: &Connection) -> JoinHandle<anyhow::Result<BmsInfo>> {
+    let connection = connection.clone();
+    thread::spawn(move || info(&connection))
+}
+
+/// Format of the pages served by the firmware of the BMS
+pub fn probe_format(connection: &Connection) -> anyhow::Result<Format> {
+    info(connection).map(|info| Format::detect(&info.firmware))
+}
+
+fn info(connection: &Connection) -> anyhow::Result<BmsInfo> {
+    let text = connection.scan("settings.shtml", &[&FIRMWARE_PATTERN, &SETTINGS_PATTERN])?;
+
+    let firmware = match FIRMWARE_PATTERN.captures(&text) {
+        Some(c) => c.get(1).unwrap().as_str().to_string(),
+        None => String::new(),
+    };
+    let Some(settings_captures) = SETTINGS_PATTERN.captures(&text) else {
+        anyhow::bail!("Settings not found");
+    };
+    let mut settings_iter = settings_captures.get(1).unwrap().as_str().split(',');
+
+    // Slaves, Zellen, Temperatursensoren, Entladestrom in A, Ladestrom in A,
+    // Zellspannung min/max in mV, Zelltemperatur max in 0.1 °C
+    Ok(BmsInfo {
+        firmware,
+        num_slaves: parse_next(&mut settings_iter)?,
+        num_cells: parse_next(&mut settings_iter)?,
+        num_temp_sensors: parse_next(&mut settings_iter)?,
+        max_discharge_current: parse_next(&mut settings_iter)?,
+        max_charge_current: parse_next(&mut settings_iter)?,
+        min_cell_voltage: parse_next(&mut settings_iter)?,
+        max_cell_voltage: parse_next(&mut settings_iter)?,
+        max_cell_temp: parse_next::<f32>(&mut settings_iter)? / 10.0,
     })
 }
 
-fn ucell(ip: &str, safe: bool) -> anyhow::Result<Ucell> {
-    let url = format!("{ip}/ucell.shtml");
-    let resp = ureq::get(&url).call()?;
-    let text = resp.into_string()?;
+fn ucell(connection: &Connection, safe: bool, format: Format) -> anyhow::Result<Ucell> {
+    let patterns = [&*UCELL_STATS_PATTERN, &UCELL_CELLS_PATTERN];
+    parse_ucell(&connection.scan("ucell.shtml", &patterns)?, safe, format)
+}
 
-    let voltage_captures = UCELL_CELLS_PATTERN.captures(&text).unwrap();
-    let mut voltage: Vec<u16> = voltage_captures
+fn parse_ucell(text: &str, safe: bool, format: Format) -> anyhow::Result<Ucell> {
+    let Some(voltage_captures) = UCELL_CELLS_PATTERN.captures(text) else {
+        anyhow::bail!("PSet not found");
+    };
+    let voltage: Vec<u16> = voltage_captures
         .get(1)
         .unwrap()
         .as_str()
         .split(',')
-        .skip(2)
+        .skip(format.cell_prefix())
         .map(|s| s.parse::<u16>().unwrap_or(0))
         .collect();
-
-    let avg_voltage = (voltage.iter().map(|n| *n as usize).sum::<usize>() / voltage.len()) as u16;
-    if safe {
-        for v in &mut voltage {
-            if *v < 3000 || *v > 4200 {
-                *v = avg_voltage;
-            }
-        }
+    if voltage.is_empty() {
+        anyhow::bail!("No cell voltages");
     }
 
-    let right = voltage_stats(voltage.iter().take(72).copied());
-    let left = voltage_stats(voltage.iter().skip(72).copied());
-
-    let max_voltage = cmp::max(right.max_voltage, left.max_voltage);
-    let min_voltage = cmp::min(right.min_voltage, left.min_voltage);
-    let overall = VoltageStats {
-        avg_voltage,
-        max_voltage,
-        min_voltage,
-        delta_voltage: max_voltage - min_voltage,
+    let Some(stats_captures) = UCELL_STATS_PATTERN.captures(text) else {
+        anyhow::bail!("PSet0 not found");
     };
-
-    let stats_captures = UCELL_STATS_PATTERN.captures(&text).unwrap();
     let mut stats_iter = stats_captures.get(1).unwrap().as_str().split(',');
 
-    Ok(Ucell {
-        num_slaves: parse_next(&mut stats_iter)?,
-        num_cells: parse_next(&mut stats_iter)?,
-        num_cells_per_slave: parse_next(&mut stats_iter)?,
-        num_temp_sensors: parse_next(&mut stats_iter)?,
-        num_safe_resistors: parse_next(&mut stats_iter)?,
+    let mut ucell = Ucell::new(voltage, safe);
+    ucell.num_slaves = parse_next(&mut stats_iter)?;
+    ucell.num_cells = parse_next(&mut stats_iter)?;
+    ucell.num_cells_per_slave = parse_next(&mut stats_iter)?;
+    ucell.num_temp_sensors = parse_next(&mut stats_iter)?;
+    ucell.num_safe_resistors = parse_next(&mut stats_iter)?;
+    Ok(ucell)
+}
 
-        overall,
-        left,
-        right,
+impl Ucell {
+    /// Computes the statistics of the cell voltages, the counts are left empty
+    pub fn new(mut voltage: Vec<u16>, safe: bool) -> Self {
+        let avg_voltage =
+            (voltage.iter().map(|n| *n as usize).sum::<usize>() / voltage.len().max(1)) as u16;
+        if safe {
+            for v in &mut voltage {
+                if *v < 3000 || *v > 4200 {
+                    *v = avg_voltage;
+                }
+            }
+        }
 
-        cell_voltage: voltage,
-    })
+        let right = voltage_stats(voltage.iter().take(72).copied());
+        let left = voltage_stats(voltage.iter().skip(72).copied());
+
+        // the left side is empty on packs with up to 72 cells
+        let overall = VoltageStats {
+            avg_voltage,
+            ..voltage_stats(voltage.iter().copied())
+        };
+
+        Ucell {
+            overall,
+            left,
+            right,
+            cell_voltage: voltage,
+            ..Default::default()
+        }
+    }
 }
 
-fn tcell(ip: &str, safe: bool) -> anyhow::Result<Tcell> {
-    let url = format!("{ip}/tcell.shtml");
-    let resp = ureq::get(&url).call()?;
-    let text = resp.into_string()?;
+fn tcell(connection: &Connection, safe: bool, dead_sensors: &[usize]) -> anyhow::Result<Tcell> {
+    let text = connection.scan("tcell.shtml", &[&TCELL_PATTERN])?;
+    parse_tcell(&text, safe, dead_sensors)
+}
 
-    let temp_captures = TCELL_PATTERN.captures(&text).unwrap();
-    let mut temp: Vec<f32> = temp_captures
+fn parse_tcell(text: &str, safe: bool, dead_sensors: &[usize]) -> anyhow::Result<Tcell> {
+    let Some(temp_captures) = TCELL_PATTERN.captures(text) else {
+        anyhow::bail!("PSet not found");
+    };
+    let temp: Vec<f32> = temp_captures
         .get(1)
         .unwrap()
         .as_str()
@@ -234,33 +756,57 @@ fn tcell(ip: &str, safe: bool) -> anyhow::Result<Tcell> {
         .map(|s| s.parse::<u16>().unwrap_or(0) as f32 / 10.0)
         .collect();
 
-    let avg_temp = temp.iter().copied().sum::<f32>() / temp.len() as f32;
-
-    let right = temp_stats(temp.iter().take(8).copied());
-    let left = temp_stats(temp.iter().skip(8).copied());
-
-    let max_temp = right.max_temp.max(left.max_temp);
-    let min_temp = right.min_temp.min(left.min_temp);
-    let overall = TempStats {
-        avg_temp,
-        max_temp,
-        min_temp,
-        delta_temp: max_temp - min_temp,
-    };
+    Ok(Tcell::new(temp, safe, dead_sensors))
+}
 
-    if safe {
-        for t in temp.iter_mut() {
-            if *t < 15.0 || *t > 45.0 {
-                *t = avg_temp;
+impl Tcell {
+    /// `dead_sensors` are excluded from the statistics
+    pub fn new(mut temp: Vec<f32>, safe: bool, dead_sensors: &[usize]) -> Self {
+        let alive = |range: std::ops::Range<usize>| {
+            temp.iter()
+                .enumerate()
+                .skip(range.start)
+                .take(range.len())
+                .filter(|(i, _)| !dead_sensors.contains(i))
+                .map(|(_, t)| *t)
+        };
+        let right = temp_stats(alive(0..8));
+        let left = temp_stats(alive(8..temp.len()));
+        let overall = temp_stats(alive(0..temp.len()));
+        let avg_temp = overall.avg_temp;
+
+        if safe {
+            for (i, t) in temp.iter_mut().enumerate() {
+                if !dead_sensors.contains(&i) && (*t < 15.0 || *t > 45.0) {
+                    *t = avg_temp;
+                }
             }
         }
+
+        Tcell {
+            temp,
+            overall,
+            left,
+            right,
+        }
     }
+}
 
-    Ok(Tcell {
-        temp,
-        overall,
-        left,
-        right,
+fn status(connection: &Connection) -> anyhow::Result<Status> {
+    let text = connection.scan("status.shtml", &[&STATUS_PATTERN])?;
+
+    let Some(status_captures) = STATUS_PATTERN.captures(&text) else {
+        anyhow::bail!("Status not found");
+    };
+    let mut status_iter = status_captures.get(1).unwrap().as_str().split(',');
+
+    // Isolationswiderstand in kOhm, IMD OK
+    let insulation_resistance = parse_next(&mut status_iter)?;
+    let imd_ok = parse_next::<u8>(&mut status_iter)? != 0;
+
+    Ok(Status {
+        insulation_resistance,
+        imd_ok,
     })
 }
 
@@ -279,6 +825,9 @@ fn voltage_stats(voltage: impl Iterator<Item = u16>) -> VoltageStats {
         sum += v as u32;
         len += 1;
     }
+    if len == 0 {
+        return VoltageStats::default();
+    }
     let delta = max - min;
     let avg = (sum / len) as u16;
 
@@ -305,6 +854,9 @@ fn temp_stats(voltage: impl Iterator<Item = f32>) -> TempStats {
         sum += v;
         len += 1.0;
     }
+    if len == 0.0 {
+        return TempStats::default();
+    }
     let delta = max - min;
     let avg = sum / len;
 
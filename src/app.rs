@@ -1,14 +1,84 @@
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use egui::style::Margin;
 use egui::{
-    menu, Align, CentralPanel, Color32, DragValue, FontFamily, FontId, Frame, Grid, Layout, Rect,
-    RichText, Rounding, ScrollArea, SidePanel, TopBottomPanel, Ui, Vec2,
+    menu, Align, Align2, Button, CentralPanel, Color32, ColorImage, ComboBox, CursorIcon,
+    DragValue, Event, FontFamily, FontId, Frame, Grid, Id, Layout, Pos2, Rect, RichText, Rounding,
+    ScrollArea, Sense, SidePanel, Slider, Stroke, TextStyle, TopBottomPanel, Ui, UserAttentionType,
+    Vec2, ViewportCommand, Window,
 };
 
 use serde::{Deserialize, Serialize};
 
-use crate::api::{self, fetch, Data, Request, Tcell, Ucell};
+use crate::alarms::{AlarmConfig, AlarmList};
+use crate::anomaly::{changed_cells, diverging_cells, AnomalyConfig, ChangedCell, DivergingCell};
+use crate::api::{self, Data, DataSource, HttpSource, PollOptions, Tcell};
+use crate::balancing::{recommend, BalancingConfig, BalancingWindow};
+use crate::can::CanSource;
+use crate::cell_detail::CellDetail;
+use crate::cell_search::CellSearch;
+use crate::charging::{charging_view, ChargingConfig};
+use crate::comparison::comparison_view;
+use crate::control::ControlPanel;
+use crate::cooling::{cooling_health, CoolingConfig, StackCooling};
+use crate::current_limit::{CurrentLimitConfig, CurrentWatchdog};
+use crate::derived::{DerivedConfig, DerivedWindow};
+use crate::diagnostics::Diagnostics;
+use crate::driver_messages::DriverMessages;
+use crate::end_of_charge::{EndOfCharge, EndOfChargeConfig};
+use crate::firmware::FirmwareDialog;
+use crate::fleet::{Fleet, FleetConfig};
+use crate::fuel_gauge::FuelGaugeConfig;
+use crate::gps::{Fix, Gps, GpsConfig};
+use crate::heartbeat::{Heartbeat, HeartbeatConfig};
+use crate::heatmap::{neighbor_average, HeatmapConfig};
+use crate::history::{History, HistoryConfig};
+use crate::http::{parse_address, AddressCheck, HttpConfig};
+use crate::i18n::{tr, Language};
+use crate::indicators::relay_indicators;
+use crate::info::InfoPage;
+use crate::integrity::{self, IntegrityConfig, Quarantine};
+use crate::inventory::CellInventory;
+use crate::keymap::{Action, Keymap};
+use crate::laps::{LapConfig, LapTracker};
+use crate::launch::{LaunchArgs, LaunchOptions};
+use crate::markers::{Marker, Markers};
+use crate::mirror::{MirrorConfig, MirrorServer, MirrorSource};
+use crate::numbering::CellNumbering;
+use crate::outliers::OutlierList;
+use crate::palette::CommandPalette;
+use crate::precharge::{PrechargeConfig, PrechargeMonitor};
+use crate::prediction::{Prediction, PredictionConfig};
+use crate::preferences::{Preferences, Section};
+use crate::procedure::{ProcedureConfig, TestProcedure};
+use crate::push::{PushConfig, Pusher};
+use crate::recording::{self, LogConfig, Recorder};
+use crate::regen::{self, CurrentPeaks, RegenConfig};
+use crate::replay::{Replay, ReplayPicker};
+use crate::resistance::resistance_view;
+use crate::rolling_stats::{RollingStats, StatsWindow};
+use crate::sag::{SagConfig, SagEstimator};
+use crate::scripting::{Script, ScriptConfig};
+use crate::serial::SerialSource;
+use crate::session::SessionManager;
+use crate::slaves::slave_view;
+use crate::smoothing::{FrameBuffer, Smoother, SmoothingConfig};
+use crate::soc::{OcvConfig, OcvEstimator};
+use crate::soh::{SohConfig, SohTracker};
+use crate::stack_bands::stack_bands_view;
+use crate::svg::Svg;
+use crate::temp_map::TempMapping;
+use crate::theme::Appearance;
+use crate::timelapse::{Timelapse, TimelapseConfig};
+use crate::trend::{TrendConfig, WeakestCell};
+use crate::units::{self, Units};
+use crate::window::WindowOptions;
+
+/// Space in px around every stack
+const STACK_GAP: f32 = 3.0;
 
 const STACK_POS: [(f32, f32, Side); 8] = [
     (2.0, 1.0, Side::Right),
@@ -25,21 +95,366 @@ const STACK_POS: [(f32, f32, Side); 8] = [
 #[serde(default)]
 pub struct DashboardApp {
     pub safe: bool,
+    pub source: Source,
     pub ip: String,
+    /// socketcan interface used by the CAN source
+    pub can_interface: String,
+    /// Debug UART of the BMS master used by the serial source
+    pub serial_port: String,
+    pub baud_rate: u32,
+    /// Dashboard the mirror source receives its data from
+    pub mirror_address: String,
+    pub http: HttpConfig,
+    pub integrity: IntegrityConfig,
     pub poll_rate: usize,
-    pub voltage_heatmap_delta: f32,
-    pub temp_heatmap_delta: f32,
-    pub relative_heatmap: bool,
+    pub heatmap: HeatmapConfig,
+    /// Heatmap settings of versions before the profiles, moved into the default profiles on start
+    #[serde(skip_serializing)]
+    voltage_heatmap_delta: Option<f32>,
+    #[serde(skip_serializing)]
+    temp_heatmap_delta: Option<f32>,
+    #[serde(skip_serializing)]
+    relative_heatmap: Option<bool>,
+    pub layout: PanelLayout,
+    pub appearance: Appearance,
+    pub font_sizes: FontSizes,
+    pub language: Language,
+    pub units: Units,
+    pub keymap: Keymap,
+    pub driver_mode: bool,
+    pub view: View,
+    pub ocv: OcvConfig,
+    pub prediction_config: PredictionConfig,
+    pub trend: TrendConfig,
+    pub sag_config: SagConfig,
+    pub anomaly: AnomalyConfig,
+    pub temp_mapping: TempMapping,
+    pub numbering: CellNumbering,
+    pub inventory: CellInventory,
+    pub charging: ChargingConfig,
+    pub end_of_charge_config: EndOfChargeConfig,
+    /// Insulation resistance warning threshold in kΩ
+    pub insulation_warning: f32,
+    pub alarm_config: AlarmConfig,
+    pub current_limit: CurrentLimitConfig,
+    pub regen: RegenConfig,
+    pub fuel_gauge: FuelGaugeConfig,
+    pub derived: DerivedConfig,
+    pub script_config: ScriptConfig,
+    pub fleet_config: FleetConfig,
+    pub logging: LogConfig,
+    pub soh_config: SohConfig,
+    pub procedure_config: ProcedureConfig,
+    pub balancing_config: BalancingConfig,
+    pub cooling_config: CoolingConfig,
+    pub precharge_config: PrechargeConfig,
+    pub gps_config: GpsConfig,
+    pub lap_config: LapConfig,
+    pub driver_messages: DriverMessages,
+    /// Window in s of the internal resistance scatter plots
+    pub resistance_window: u64,
+    /// Window in s of the left/right comparison plots
+    pub comparison_window: u64,
+    /// Window in s of the stack band plots
+    pub stack_band_window: u64,
+    pub history_config: HistoryConfig,
+    pub smoothing: SmoothingConfig,
+    pub push_config: PushConfig,
+    pub heartbeat_config: HeartbeatConfig,
+    pub mirror_config: MirrorConfig,
+    pub timelapse_config: TimelapseConfig,
+    pub window: WindowOptions,
+    pub launch: LaunchOptions,
+    #[serde(skip)]
+    pub paused: bool,
+    /// Asking whether safe mode should really be disabled while receiving live data
+    #[serde(skip)]
+    confirm_unsafe: bool,
+    /// Read-only screen for visitors, e.g. in the public pit area, that only displays the data
+    #[serde(skip)]
+    spectator: bool,
+    /// Time of the last poll, recording and heartbeat
+    #[serde(skip)]
+    last_tick: Instant,
+    #[serde(skip)]
+    pub status: Option<String>,
+    #[serde(skip)]
+    palette: CommandPalette,
+    #[serde(skip)]
+    cell_search: CellSearch,
+    #[serde(skip)]
+    rolling_stats: RollingStats,
+    #[serde(skip)]
+    pub ocv_estimator: OcvEstimator,
+    #[serde(skip)]
+    pub sag: SagEstimator,
+    #[serde(skip)]
+    pub history: History,
+    #[serde(skip)]
+    pub prediction: Option<Prediction>,
+    #[serde(skip)]
+    pub weakest_cell: Option<WeakestCell>,
+    #[serde(skip)]
+    pub cooling: Vec<Option<StackCooling>>,
+    #[serde(skip)]
+    pub diverging: Vec<DivergingCell>,
+    #[serde(skip)]
+    pub changed: Vec<ChangedCell>,
+    #[serde(skip)]
+    pub alarms: AlarmList,
+    #[serde(skip)]
+    pub current_watchdog: CurrentWatchdog,
+    #[serde(skip)]
+    derived_window: DerivedWindow,
+    #[serde(skip)]
+    script: Script,
+    #[serde(skip)]
+    fleet: Fleet,
+    #[serde(skip)]
+    smoother: Smoother,
+    #[serde(skip)]
+    frames: FrameBuffer,
+    #[serde(skip)]
+    pub outliers: OutlierList,
+    #[serde(skip)]
+    quarantine: Quarantine,
+    #[serde(skip)]
+    cell_detail: CellDetail,
+    #[serde(skip)]
+    pusher: Pusher,
+    #[serde(skip)]
+    heartbeat: Heartbeat,
+    #[serde(skip)]
+    mirror_server: MirrorServer,
+    #[serde(skip)]
+    address_check: AddressCheck,
+    #[serde(skip)]
+    diagnostics: Diagnostics,
+    #[serde(skip)]
+    timelapse: Timelapse,
+    #[serde(skip)]
+    charging_suggested: bool,
+    #[serde(skip)]
+    recorder: Option<Recorder>,
+    #[serde(skip)]
+    finishing: Vec<JoinHandle<std::io::Result<()>>>,
+    #[serde(skip)]
+    pub soh: SohTracker,
+    #[serde(skip)]
+    procedure: TestProcedure,
+    #[serde(skip)]
+    balancing: BalancingWindow,
+    #[serde(skip)]
+    pub precharge: PrechargeMonitor,
+    #[serde(skip)]
+    current_peaks: CurrentPeaks,
+    #[serde(skip)]
+    end_of_charge: EndOfCharge,
+    #[serde(skip)]
+    info: InfoPage,
+    #[serde(skip)]
+    gps: Option<Gps>,
+    #[serde(skip)]
+    pub laps: LapTracker,
+    #[serde(skip)]
+    pub sessions: SessionManager,
+    #[serde(skip)]
+    pub markers: Markers,
+    #[serde(skip)]
+    replay: Option<Replay>,
+    #[serde(skip)]
+    replay_picker: ReplayPicker,
+    #[serde(skip)]
+    control: ControlPanel,
+    #[serde(skip)]
+    firmware: FirmwareDialog,
+    #[serde(skip)]
+    pub show_settings: bool,
+    #[serde(skip)]
+    preferences: Preferences,
     #[serde(skip)]
     pub last_poll: u128,
     #[serde(skip)]
-    request: Option<Request>,
+    request: Option<JoinHandle<PolledSample>>,
+    /// The source is moved to the polling thread while a request is running
+    #[serde(skip)]
+    data_source: Option<(String, Box<dyn DataSource>)>,
     #[serde(skip)]
     pub data: Option<Data>,
     #[serde(skip)]
     pub error: Option<api::Error>,
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct PanelLayout {
+    pub show_stats: bool,
+    pub show_temps: bool,
+    pub show_stacks: bool,
+    pub stats_width: f32,
+    /// Fraction of every stack used by its band of temperature sensors
+    pub temp_height: f32,
+    /// Samples the statistics are aggregated over
+    pub stats_window: StatsWindow,
+    /// Swap left and right, e.g. when standing in front of the car
+    pub mirror_horizontal: bool,
+    pub mirror_vertical: bool,
+}
+
+impl PanelLayout {
+    /// Position of a cell in a `columns` x `rows` grid, mirrored to match the viewer's orientation
+    fn grid_pos(&self, x: f32, y: f32, columns: f32, rows: f32) -> Vec2 {
+        let x = match self.mirror_horizontal {
+            true => columns - 1.0 - x,
+            false => x,
+        };
+        let y = match self.mirror_vertical {
+            true => rows - 1.0 - y,
+            false => y,
+        };
+        Vec2::new(x, y)
+    }
+}
+
+impl Default for PanelLayout {
+    fn default() -> Self {
+        Self {
+            show_stats: true,
+            show_temps: true,
+            show_stacks: true,
+            stats_width: 300.0,
+            temp_height: 0.2,
+            stats_window: StatsWindow::default(),
+            mirror_horizontal: false,
+            mirror_vertical: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct FontSizes {
+    /// Derive the cell font sizes from the cell size instead of using the fixed ones
+    pub auto: bool,
+    pub cell_value: f32,
+    pub cell_index: f32,
+    pub side_panel: f32,
+}
+
+impl Default for FontSizes {
+    fn default() -> Self {
+        Self {
+            auto: true,
+            cell_value: 24.0,
+            cell_index: 12.0,
+            side_panel: 17.0,
+        }
+    }
+}
+
+impl FontSizes {
+    /// Font sizes of the value and the index label of a cell
+    fn cell(&self, cell_size: Vec2) -> (f32, f32) {
+        if self.auto {
+            let size = (cell_size.x + cell_size.y) / 8.0;
+            (size, size / 2.0)
+        } else {
+            (self.cell_value, self.cell_index)
+        }
+    }
+
+    fn settings(&mut self, ui: &mut Ui) {
+        Grid::new("font_sizes").show(ui, |ui| {
+            ui.label(tr("Automatic cell sizes"));
+            ui.checkbox(&mut self.auto, "");
+            ui.end_row();
+
+            ui.label(tr("Cell values"));
+            ui.add_enabled(
+                !self.auto,
+                DragValue::new(&mut self.cell_value).clamp_range(6.0..=96.0),
+            );
+            ui.end_row();
+
+            ui.label(tr("Cell indices"));
+            ui.add_enabled(
+                !self.auto,
+                DragValue::new(&mut self.cell_index).clamp_range(6.0..=48.0),
+            );
+            ui.end_row();
+
+            ui.label(tr("Side panel"));
+            ui.add(DragValue::new(&mut self.side_panel).clamp_range(6.0..=48.0));
+            ui.end_row();
+        });
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum View {
+    Pack,
+    Slaves,
+    Charging,
+    Info,
+    Resistance,
+    Comparison,
+    StackBands,
+}
+
+impl View {
+    pub const ALL: [View; 7] = [
+        View::Pack,
+        View::Slaves,
+        View::Charging,
+        View::Info,
+        View::Resistance,
+        View::Comparison,
+        View::StackBands,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            View::Pack => tr("Pack"),
+            View::Slaves => tr("Slaves"),
+            View::Charging => tr("Charging"),
+            View::Info => tr("BMS info"),
+            View::Resistance => tr("Internal resistance"),
+            View::Comparison => tr("Left/right"),
+            View::StackBands => tr("Stack bands"),
+        }
+    }
+}
+
+/// The data source with its address and the received data
+/// The source, the sample and the Unix time in ms it was taken
+type PolledSample = (
+    (String, Box<dyn DataSource>),
+    Result<Data, api::Error>,
+    u128,
+);
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Source {
+    Http,
+    Can,
+    Serial,
+    /// Another dashboard that is connected to the car
+    Mirror,
+}
+
+impl Source {
+    pub const ALL: [Source; 4] = [Source::Http, Source::Can, Source::Serial, Source::Mirror];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Source::Http => "HTTP",
+            Source::Can => "CAN",
+            Source::Serial => "UART",
+            Source::Mirror => tr("Mirror"),
+        }
+    }
+}
+
 #[derive(Clone, Copy)]
 enum Side {
     Left,
@@ -50,13 +465,120 @@ impl Default for DashboardApp {
     fn default() -> Self {
         Self {
             safe: true,
+            source: Source::Http,
             ip: "http://192.168.0.200".into(),
+            can_interface: "can0".into(),
+            serial_port: "/dev/ttyUSB0".into(),
+            mirror_address: "http://192.168.0.10:8090".into(),
+            baud_rate: 115200,
+            http: HttpConfig::default(),
+            integrity: IntegrityConfig::default(),
             poll_rate: 1000,
-            voltage_heatmap_delta: 100.0,
-            temp_heatmap_delta: 5.0,
-            relative_heatmap: false,
+            heatmap: HeatmapConfig::default(),
+            voltage_heatmap_delta: None,
+            temp_heatmap_delta: None,
+            relative_heatmap: None,
+            layout: PanelLayout::default(),
+            appearance: Appearance::default(),
+            font_sizes: FontSizes::default(),
+            language: Language::default(),
+            units: Units::default(),
+            keymap: Keymap::default(),
+            driver_mode: false,
+            view: View::Pack,
+            ocv: OcvConfig::default(),
+            prediction_config: PredictionConfig::default(),
+            trend: TrendConfig::default(),
+            sag_config: SagConfig::default(),
+            anomaly: AnomalyConfig::default(),
+            temp_mapping: TempMapping::default(),
+            numbering: CellNumbering::default(),
+            inventory: CellInventory::default(),
+            charging: ChargingConfig::default(),
+            end_of_charge_config: EndOfChargeConfig::default(),
+            insulation_warning: 300.0,
+            alarm_config: AlarmConfig::default(),
+            current_limit: CurrentLimitConfig::default(),
+            regen: RegenConfig::default(),
+            fuel_gauge: FuelGaugeConfig::default(),
+            derived: DerivedConfig::default(),
+            script_config: ScriptConfig::default(),
+            fleet_config: FleetConfig::default(),
+            logging: LogConfig::default(),
+            soh_config: SohConfig::default(),
+            procedure_config: ProcedureConfig::default(),
+            balancing_config: BalancingConfig::default(),
+            cooling_config: CoolingConfig::default(),
+            precharge_config: PrechargeConfig::default(),
+            gps_config: GpsConfig::default(),
+            lap_config: LapConfig::default(),
+            driver_messages: DriverMessages::default(),
+            resistance_window: 120,
+            comparison_window: 600,
+            stack_band_window: 600,
+            history_config: HistoryConfig::default(),
+            smoothing: SmoothingConfig::default(),
+            push_config: PushConfig::default(),
+            heartbeat_config: HeartbeatConfig::default(),
+            mirror_config: MirrorConfig::default(),
+            timelapse_config: TimelapseConfig::default(),
+            window: WindowOptions::default(),
+            launch: LaunchOptions::default(),
+            timelapse: Timelapse::default(),
+            pusher: Pusher::default(),
+            heartbeat: Heartbeat::default(),
+            mirror_server: MirrorServer::default(),
+            address_check: AddressCheck::default(),
+            diagnostics: Diagnostics::default(),
+            smoother: Smoother::default(),
+            frames: FrameBuffer::default(),
+            outliers: OutlierList::default(),
+            quarantine: Quarantine::default(),
+            cell_detail: CellDetail::default(),
+            paused: false,
+            confirm_unsafe: false,
+            spectator: false,
+            last_tick: Instant::now(),
+            status: None,
+            palette: CommandPalette::default(),
+            cell_search: CellSearch::default(),
+            rolling_stats: RollingStats::default(),
+            ocv_estimator: OcvEstimator::default(),
+            sag: SagEstimator::default(),
+            history: History::default(),
+            prediction: None,
+            weakest_cell: None,
+            cooling: Vec::new(),
+            diverging: Vec::new(),
+            changed: Vec::new(),
+            alarms: AlarmList::default(),
+            current_watchdog: CurrentWatchdog::default(),
+            derived_window: DerivedWindow::default(),
+            script: Script::default(),
+            fleet: Fleet::default(),
+            charging_suggested: false,
+            recorder: None,
+            finishing: Vec::new(),
+            soh: SohTracker::default(),
+            procedure: TestProcedure::default(),
+            balancing: BalancingWindow::default(),
+            precharge: PrechargeMonitor::default(),
+            current_peaks: CurrentPeaks::default(),
+            end_of_charge: EndOfCharge::default(),
+            info: InfoPage::default(),
+            gps: None,
+            laps: LapTracker::default(),
+            sessions: SessionManager::default(),
+            markers: Markers::default(),
+            replay: None,
+            replay_picker: ReplayPicker::default(),
+            control: ControlPanel::default(),
+            firmware: FirmwareDialog::default(),
+            show_settings: false,
+            preferences: Preferences::default(),
             last_poll: 0,
             request: None,
+            data_source: None,
             data: None,
             error: None,
         }
@@ -64,72 +586,127 @@ impl Default for DashboardApp {
 }
 
 impl DashboardApp {
-    pub fn new(context: &eframe::CreationContext) -> Self {
+    pub fn new(context: &eframe::CreationContext, args: LaunchArgs) -> Self {
         let mut style = (*context.egui_ctx.style()).clone();
         for (_, f) in style.text_styles.iter_mut() {
             f.size = (f.size * 1.2).round();
         }
         context.egui_ctx.set_style(style);
 
-        context
+        let mut app: Self = context
             .storage
-            .and_then(|s| eframe::get_value::<Self>(s, eframe::APP_KEY))
-            .unwrap_or_default()
+            .and_then(|s| eframe::get_value(s, eframe::APP_KEY))
+            .unwrap_or_default();
+        app.soh = SohTracker::load(app.logging.directory());
+        app.heatmap.migrate(
+            app.voltage_heatmap_delta.take(),
+            app.temp_heatmap_delta.take(),
+            app.relative_heatmap.take(),
+        );
+
+        app.spectator = args.spectator;
+        if app.spectator {
+            app.logging.enabled = false;
+            app.show_settings = false;
+            return app;
+        }
+        let minimized = args.minimized || app.launch.start_minimized;
+        let record = args.record || app.launch.start_recording;
+        if minimized {
+            context
+                .egui_ctx
+                .send_viewport_cmd(ViewportCommand::Minimized(true));
+        }
+        if record {
+            app.logging.enabled = true;
+        }
+        if !minimized && !record {
+            app.sessions.open(app.logging.directory());
+        }
+        app
     }
 }
 
 impl eframe::App for DashboardApp {
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
-        eframe::set_value(storage, eframe::APP_KEY, self);
+        // a spectator screen starts with the same settings every time
+        if !self.spectator {
+            eframe::set_value(storage, eframe::APP_KEY, self);
+        }
     }
 
-    fn update(&mut self, ctx: &egui::Context, _: &mut eframe::Frame) {
-        if ctx.input(|i| i.key_down(egui::Key::V) && i.key_pressed(egui::Key::W)) {
-            self.safe = !self.safe;
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // compress and convert the last recording before the process ends and kills the threads
+        self.finish_recording();
+        for recording in self.finishing.drain(..) {
+            if let Ok(Err(e)) = recording.join() {
+                eprintln!("{}: {e}", tr("Error finishing recording"));
+            }
+        }
+    }
+
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        self.appearance.apply(ctx, frame.info().system_theme);
+        self.window.apply(ctx);
+        self.language.set_current();
+        self.units.set_current();
+
+        for action in self.keymap.pressed(ctx) {
+            self.run_action(ctx, action);
+        }
+        self.save_screenshots(ctx);
+        if self.timelapse.due(&self.timelapse_config, now()) {
+            ctx.send_viewport_cmd(ViewportCommand::Screenshot);
         }
 
-        self.poll_data();
+        if self.replay.is_some() {
+            self.replay_data();
+        }
+        self.tick(ctx);
         ctx.request_repaint_after(Duration::from_millis(100));
 
         TopBottomPanel::top("top_panel").show(ctx, |ui| {
             menu::bar(ui, |ui| {
-                if self.safe {
-                    ui.label("s3racing");
+                let (text, color, hint) = match self.safe {
+                    true => (
+                        tr("Safe mode"),
+                        Color32::GREEN,
+                        tr("Implausible values are replaced by the average"),
+                    ),
+                    false => (
+                        tr("Raw values"),
+                        Color32::from_rgb(0xff, 0x8c, 0x00),
+                        tr("All values are shown as received"),
+                    ),
+                };
+                let safe = RichText::new(text).color(color).strong();
+                if self.spectator {
+                    ui.label(safe).on_hover_text(hint);
+                    ui.menu_button(tr("View"), |ui| {
+                        for view in View::ALL {
+                            ui.radio_value(&mut self.view, view, view.name());
+                        }
+                    });
+                } else {
+                    if ui.button(safe).on_hover_text(hint).clicked() {
+                        self.toggle_safe();
+                    }
+                    self.toolbar(ui);
                 }
-                ui.label("IP");
-                ui.horizontal(|ui| {
-                    ui.set_width(160.0);
-                    ui.text_edit_singleline(&mut self.ip);
-                });
-
-                ui.label("Poll rate");
-                ui.add(
-                    DragValue::new(&mut self.poll_rate)
-                        .clamp_range(100..=10000)
-                        .speed(10),
-                );
-
-                ui.label("Volatge heatmap delta");
-                ui.add(
-                    DragValue::new(&mut self.voltage_heatmap_delta)
-                        .clamp_range(5.0..=1000.0)
-                        .speed(1.0),
-                );
-
-                ui.label("Temperature heatmap delta");
-                ui.add(
-                    DragValue::new(&mut self.temp_heatmap_delta)
-                        .clamp_range(0.5..=25.0)
-                        .speed(0.1),
-                );
-
-                ui.label("Relative heatmap");
-                ui.checkbox(&mut self.relative_heatmap, "");
 
                 ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    if let Some(status) = &self.status {
+                        ui.label(status);
+                    }
+                    if self.paused {
+                        ui.label(RichText::new(tr("Paused")).strong());
+                    }
+                    if self.logging.enabled {
+                        ui.label(RichText::new(tr("● REC")).color(Color32::RED));
+                    }
                     if self.request.is_some() {
                         ui.spinner();
-                        if ui.button("cancel").clicked() {
+                        if ui.button(tr("cancel")).clicked() {
                             self.request = None;
                         }
                     }
@@ -137,94 +714,616 @@ impl eframe::App for DashboardApp {
             });
         });
 
-        CentralPanel::default().show(ctx, |ui| {
-            let panel_fill = if ui.style().visuals.dark_mode {
-                Color32::from_gray(0x20)
-            } else {
-                Color32::from_gray(0xf0)
-            };
-            SidePanel::left("stats")
-                .resizable(false)
-                .frame(Frame {
-                    inner_margin: Margin::same(6.0),
-                    rounding: Rounding::same(5.0),
-                    fill: panel_fill,
-                    ..Default::default()
-                })
-                .show_inside(ui, |ui| {
-                    if let Some(data) = &self.data {
-                        ScrollArea::vertical().show(ui, |ui| {
-                            Grid::new("stats_container").show(ui, |ui| side_panel(ui, data));
-                        });
+        let mut show_settings = self.show_settings;
+        Window::new(tr("Settings"))
+            .id(Id::new("settings"))
+            .open(&mut show_settings)
+            .default_size([420.0, 600.0])
+            .show(ctx, |ui| {
+                let sections = self.preferences.header(ui);
+                if sections.is_empty() {
+                    ui.label(tr("No matching settings"));
+                }
+                ScrollArea::vertical().show(ui, |ui| {
+                    for (i, section) in sections.into_iter().enumerate() {
+                        if i > 0 {
+                            ui.separator();
+                        }
+                        ui.heading(section.name());
+                        self.settings_section(ui, section);
                     }
                 });
+            });
+        self.show_settings = show_settings;
+
+        self.keymap.help_overlay(ctx);
+        self.soh.window(ctx, &self.soh_config, &self.inventory);
+        self.derived_window
+            .window(ctx, &self.derived, &self.history);
+        self.script.window(ctx);
+        let open = self
+            .fleet
+            .window(ctx, &self.fleet_config, &self.charging, now());
+        if let Some(i) = open {
+            self.open_pack(i);
+        }
+        self.precharge.window(ctx, &self.precharge_config);
+        self.end_of_charge.window(ctx, now());
+        let directory = self.sessions.directory(self.logging.directory());
+        if let Some(Err(e)) = self.markers.window(ctx, &directory) {
+            self.status = Some(format!("{}: {e}", tr("Error saving marker")));
+        }
+        let error = self.procedure.window(
+            ctx,
+            &self.procedure_config,
+            &self.ocv,
+            &mut self.markers,
+            &directory,
+            now(),
+        );
+        if error.is_some() {
+            self.status = error;
+        }
+        let recommendation = match (self.balancing.show, &self.data) {
+            (true, Some(data)) => Some(recommend(
+                &self.balancing_config,
+                &self.ocv,
+                self.soh.cycles.last(),
+                data,
+            )),
+            _ => None,
+        };
+        let status =
+            self.balancing
+                .window(ctx, recommendation.as_ref(), &self.numbering, &directory);
+        if status.is_some() {
+            self.status = status;
+        }
+        match self.alarms.log_window(ctx, self.alarm_time(), &directory) {
+            Some(Ok(path)) => {
+                self.status = Some(format!("{} {}", tr("Exported to"), path.display()));
+            }
+            Some(Err(e)) => self.status = Some(format!("{}: {e}", tr("Error exporting alarm log"))),
+            None => (),
+        }
+        if self.replay.is_none() && !self.spectator {
+            if let Err(e) = self.alarms.save(&directory) {
+                self.status = Some(format!("{}: {e}", tr("Error saving alarm log")));
+            }
+        }
+        if let Some(path) = self.replay_picker.window(ctx) {
+            self.open_replay(&path);
+        }
+        if let Some(status) = self.sessions.window(ctx, self.logging.directory(), now()) {
+            self.status = Some(status);
+        }
+        let fix = self.gps.as_ref().and_then(Gps::fix);
+        self.laps.window(ctx, &mut self.lap_config, fix, now());
+        self.outliers.window(ctx, &self.inventory, now());
+        let cells = self.data.as_ref().map_or(0, |d| d.ucell.cell_voltage.len());
+        self.inventory.window(ctx, cells);
+        self.quarantine.window(ctx, now());
+        let status = self.cell_detail.window(
+            ctx,
+            &self.history,
+            &self.temp_mapping,
+            &self.inventory,
+            &directory,
+            self.replay.as_ref().map(|r| r.path.as_path()),
+        );
+        if status.is_some() {
+            self.status = status;
+        }
+        // a mirror client never talks to the car, that is left to the dashboard it mirrors
+        if self.source == Source::Mirror {
+            self.diagnostics.show = false;
+            self.control.show = false;
+            self.firmware.show = false;
+        }
+        if self.spectator {
+            self.cell_detail.cell = None;
+        }
+        let connection = self.http.connection(&self.ip);
+        self.diagnostics.window(ctx, &connection, self.http.format);
+        if let Some(status) = self
+            .control
+            .window(ctx, &connection, self.logging.directory())
+        {
+            self.status = Some(status);
+        }
+        let ts_active = self
+            .data
+            .as_ref()
+            .and_then(|d| d.main.relays)
+            .is_some_and(|r| r.ts_active());
+        if let Some(status) =
+            self.firmware
+                .window(ctx, &connection, ts_active, self.logging.directory())
+        {
+            self.status = Some(status);
+        }
+        self.safe_mode_dialog(ctx);
+        let profiles = self.heatmap.actions();
+        if let Some(action) = self.palette.show(ctx, &self.keymap, profiles) {
+            self.run_action(ctx, action);
+        }
+        let cells = self.data.as_ref().map_or(0, |d| d.ucell.cell_voltage.len());
+        if self.cell_search.show(ctx, &self.numbering, cells) {
+            self.view = View::Pack;
+            self.layout.show_stacks = true;
+        }
+
+        if let Some(relays) = self.data.as_ref().and_then(|d| d.main.relays) {
+            TopBottomPanel::top("relays").show(ctx, |ui| {
+                relay_indicators(ui, &relays);
+            });
+        }
+
+        if self.alarms.has_unacknowledged() {
+            let time = self.alarm_time();
+            TopBottomPanel::top("alarms").show(ctx, |ui| self.alarms.banner(ui, time));
+        }
+
+        if let Some(replay) = &mut self.replay {
+            let (close, status) = TopBottomPanel::bottom("replay")
+                .show(ctx, |ui| {
+                    replay.timeline(ui, self.trend.alarm_voltage, self.logging.utc_offset)
+                })
+                .inner;
+            if status.is_some() {
+                self.status = status;
+            }
+            if close {
+                self.stop_replay();
+            }
+        }
+
+        if let (true, Some(prediction)) = (self.prediction_config.enabled, &self.prediction) {
+            TopBottomPanel::bottom("prediction").show(ctx, |ui| {
+                prediction.show(ui, self.font_sizes.side_panel * 1.5);
+            });
+        }
+
+        CentralPanel::default().show(ctx, |ui| {
+            let panel_fill = self.appearance.palette(ui.style().visuals.dark_mode).panel;
+            if self.layout.show_stats {
+                let panel = SidePanel::left("stats")
+                    .resizable(true)
+                    .default_width(self.layout.stats_width)
+                    .frame(Frame {
+                        inner_margin: Margin::same(6.0),
+                        rounding: Rounding::same(5.0),
+                        fill: panel_fill,
+                        ..Default::default()
+                    })
+                    .show_inside(ui, |ui| {
+                        let size = self.font_sizes.side_panel;
+                        let text_styles = &mut ui.style_mut().text_styles;
+                        text_styles.insert(TextStyle::Body, FontId::proportional(size));
+                        text_styles.insert(TextStyle::Heading, FontId::proportional(size * 1.3));
+
+                        let window = &mut self.layout.stats_window;
+                        ComboBox::from_id_source("stats_window")
+                            .selected_text(window.name())
+                            .show_ui(ui, |ui| {
+                                for w in StatsWindow::ALL {
+                                    ui.selectable_value(window, w, w.name());
+                                }
+                            });
+                        if let Some(data) = &self.data {
+                            self.rolling_stats.update(
+                                self.layout.stats_window,
+                                &self.history,
+                                data,
+                                &self.temp_mapping.dead,
+                            );
+                            let data = self.rolling_stats.data().unwrap_or(data);
+                            ScrollArea::vertical().show(ui, |ui| {
+                                Grid::new("stats_container")
+                                    .show(ui, |ui| side_panel(ui, data, self));
+                            });
+                        }
+                    });
+                self.layout.stats_width = panel.response.rect.width();
+            }
 
             match &self.error {
-                Some(api::Error::Fetch(_)) => {
+                Some(api::Error::Fetch(e)) => {
                     ui.vertical_centered(|ui| {
-                        ui.label(RichText::new("Error loading data").color(Color32::RED));
+                        ui.label(RichText::new(tr("Error loading data")).color(Color32::RED))
+                            .on_hover_text(e.to_string());
+                        let diagnose = self.source == Source::Http && !self.spectator;
+                        if diagnose && ui.button(tr("Diagnose")).clicked() {
+                            self.diagnostics.show = true;
+                        }
                     });
                 }
                 Some(api::Error::Unexpected) => {
                     ui.vertical_centered(|ui| {
-                        ui.label(RichText::new("Unexpected error").color(Color32::RED));
+                        ui.label(RichText::new(tr("Unexpected error")).color(Color32::RED));
                     });
                 }
                 None => (),
             }
 
+            if let (true, Some(weakest)) = (self.trend.enabled, &self.weakest_cell) {
+                if weakest.is_warning(&self.trend) {
+                    let seconds = weakest.time_to_alarm.unwrap_or_default();
+                    ui.vertical_centered(|ui| {
+                        ui.label(
+                            RichText::new(format!(
+                                "{} {} {} {:.0} s",
+                                tr("Cell"),
+                                weakest.index + 1,
+                                tr("projected to reach the alarm voltage in"),
+                                seconds,
+                            ))
+                            .color(Color32::RED)
+                            .strong(),
+                        );
+                    });
+                }
+            }
+
+            // the parameter set can be read even if polling the live data fails
+            if self.view == View::Info && !self.driver_mode {
+                if self.source == Source::Mirror {
+                    ui.label(tr("The BMS info is not available to mirror clients"));
+                    return;
+                }
+                let connection = self.http.connection(&self.ip);
+                self.info.view(ui, &connection, self.data.as_ref());
+                return;
+            }
+
             if let Some(data) = &self.data {
-                let pos = ui.cursor().min;
-                let size = ui.available_size();
-                let temp_size = size * Vec2::new(1.0, 0.2);
-                ui.allocate_ui_at_rect(Rect::from_min_size(pos, temp_size), |ui| {
-                    draw_temps(ui, data, self);
-                });
+                if self.charging.is_charging(data) {
+                    if !self.charging_suggested && self.view != View::Charging {
+                        ui.vertical_centered(|ui| {
+                            ui.horizontal(|ui| {
+                                ui.label(tr("The pack is charging."));
+                                if ui.button(tr("Show charging view")).clicked() {
+                                    self.view = View::Charging;
+                                    self.charging_suggested = true;
+                                }
+                                if ui.button(tr("Dismiss")).clicked() {
+                                    self.charging_suggested = true;
+                                }
+                            });
+                        });
+                    }
+                } else {
+                    self.charging_suggested = false;
+                }
 
-                let stacks_pos = pos + Vec2::new(pos.x, pos.y + temp_size.y);
-                let stacks_size = Vec2::new(size.x, size.y - temp_size.y);
-                ui.allocate_ui_at_rect(Rect::from_min_size(stacks_pos, stacks_size), |ui| {
-                    draw_stacks(ui, data, self);
-                });
+                let data = match self.smoothing.applies(self.view, self.driver_mode) {
+                    true => self.smoother.data.as_ref().unwrap_or(data),
+                    false => data,
+                };
+                if self.driver_mode {
+                    let fix = self.gps.as_ref().and_then(Gps::fix);
+                    if self.fuel_gauge.enabled {
+                        let compensated = self.sag.compensate(&self.sag_config, data);
+                        let height = ui.available_height() / 6.0;
+                        self.fuel_gauge
+                            .show(ui, compensated.ucell.overall.min_voltage, height);
+                    }
+                    driver_view(ui, data, fix, &self.regen);
+                    return;
+                }
+                match self.view {
+                    View::Pack => (),
+                    View::Slaves => {
+                        slave_view(ui, data, &self.history);
+                        return;
+                    }
+                    View::Charging => {
+                        charging_view(ui, &self.charging, data, &self.history, &self.markers);
+                        return;
+                    }
+                    View::Info => (),
+                    View::Resistance => {
+                        resistance_view(ui, &self.history, &mut self.resistance_window);
+                        return;
+                    }
+                    View::Comparison => {
+                        comparison_view(
+                            ui,
+                            data,
+                            &self.history,
+                            &self.markers,
+                            &mut self.comparison_window,
+                        );
+                        return;
+                    }
+                    View::StackBands => {
+                        stack_bands_view(
+                            ui,
+                            &self.history,
+                            &self.markers,
+                            STACK_POS.len(),
+                            &mut self.stack_band_window,
+                        );
+                        return;
+                    }
+                }
+
+                let compensated = self
+                    .heatmap
+                    .compensated
+                    .then(|| self.sag.compensate(&self.sag_config, data));
+                let clicked = draw_pack(ui, compensated.as_ref().unwrap_or(data), self);
+                if clicked.is_some() {
+                    self.cell_detail.cell = clicked;
+                }
             }
         });
     }
 }
 
-fn side_panel(ui: &mut Ui, data: &Data) {
+fn side_panel(ui: &mut Ui, data: &Data, app: &DashboardApp) {
     let ucell = &data.ucell;
 
-    field(ui, "Current", data.main.current.to_string(), "mA");
+    let current = data.main.current;
+    ui.label(tr("Current"));
+    ui.label(app.regen.text(current.to_string(), current));
+    ui.label("mA");
+    ui.end_row();
     field(ui, "Voltage", format!("{:.3}", data.main.voltage), "V");
+    ui.label(tr("Power"));
+    ui.label(
+        app.regen
+            .text(format!("{:.1}", regen::power(data)), current),
+    );
+    ui.label("kW");
+    ui.end_row();
+    app.current_peaks.show(ui, &app.regen);
+    field(
+        ui,
+        "State of charge",
+        format!("{:.1}", data.main.state_of_charge),
+        "%",
+    );
+    if let Some(status) = &data.status {
+        let resistance = format!("{:.0}", status.insulation_resistance);
+        ui.label(tr("Insulation resistance"));
+        if !status.imd_ok || status.insulation_resistance < app.insulation_warning {
+            ui.label(RichText::new(resistance).color(Color32::RED).strong())
+                .on_hover_text(if status.imd_ok {
+                    tr("Below the warning threshold")
+                } else {
+                    tr("IMD fault")
+                });
+        } else {
+            ui.label(resistance);
+        }
+        ui.label("kΩ");
+        ui.end_row();
+    }
+    if app.ocv.enabled {
+        let estimator = &app.ocv_estimator;
+        let value = match estimator.estimate {
+            Some(soc) => format!("{soc:.1}"),
+            None => "-".into(),
+        };
+        ui.label(tr("OCV state of charge"));
+        if estimator.rested {
+            ui.label(value);
+        } else {
+            ui.label(RichText::new(value).weak())
+                .on_hover_text(tr("Pack not at rest, showing the last estimate"));
+        }
+        ui.label("%");
+        ui.end_row();
+    }
     ui.end_row();
 
     heading(ui, "Both accumulators");
-    field(ui, "Min cell voltage", ucell.overall.min_voltage, "mV");
-    field(ui, "Avg cell voltage", ucell.overall.avg_voltage, "mV");
-    field(ui, "Max cell voltage", ucell.overall.max_voltage, "mV");
-    field(ui, "Delta cell voltage", ucell.overall.delta_voltage, "mV");
+    field(
+        ui,
+        "Min cell voltage",
+        units::voltage_value(ucell.overall.min_voltage),
+        units::voltage_unit(),
+    );
+    field(
+        ui,
+        "Avg cell voltage",
+        units::voltage_value(ucell.overall.avg_voltage),
+        units::voltage_unit(),
+    );
+    field(
+        ui,
+        "Max cell voltage",
+        units::voltage_value(ucell.overall.max_voltage),
+        units::voltage_unit(),
+    );
+    field(
+        ui,
+        "Delta cell voltage",
+        units::voltage_value(ucell.overall.delta_voltage),
+        units::voltage_unit(),
+    );
+    if let (true, Some(weakest)) = (app.trend.enabled, &app.weakest_cell) {
+        field(
+            ui,
+            "Weakest cell",
+            format!(
+                "#{}: {}",
+                weakest.index + 1,
+                units::voltage_value(weakest.voltage)
+            ),
+            units::voltage_unit(),
+        );
+        field(
+            ui,
+            "Weakest cell trend",
+            units::voltage_trend(weakest.slope * 60.0),
+            "",
+        );
+    }
+    for cell in &app.diverging {
+        field(
+            ui,
+            "Diverging cell",
+            format!(
+                "#{}: z {:.1} (+{:.2}/min)",
+                cell.index + 1,
+                cell.z_score,
+                cell.growth
+            ),
+            "",
+        );
+    }
     ui.end_row();
 
+    if app.current_limit.enabled {
+        let watchdog = &app.current_watchdog;
+        heading(ui, "Current limits");
+        let used = watchdog.budget_used(&app.current_limit);
+        ui.label(tr("Overload budget"));
+        let color = match used {
+            u if u >= 100.0 => Color32::RED,
+            u if u >= 50.0 => Color32::YELLOW,
+            _ => ui.visuals().text_color(),
+        };
+        ui.colored_label(color, format!("{used:.0}"));
+        ui.label("%");
+        ui.end_row();
+        field(
+            ui,
+            "Time above limit",
+            format!("{:.0}", watchdog.time_above as f64 / 1000.0),
+            "s",
+        );
+        field(
+            ui,
+            "Max current",
+            format!("{:.0}", watchdog.max_current),
+            "A",
+        );
+        ui.end_row();
+    }
+
+    if app.cooling.iter().any(Option::is_some) {
+        heading(ui, "Cooling");
+        for (i, stack) in app.cooling.iter().enumerate() {
+            let Some(stack) = stack else {
+                continue;
+            };
+            ui.label(format!("{} {}", tr("Stack"), i + 1));
+            let text = format!(
+                "{} ({:+.0} %)",
+                stack.health.name(),
+                (stack.ratio - 1.0) * 100.0
+            );
+            let sensitivity = units::temp_delta_scale(stack.sensitivity);
+            ui.colored_label(stack.health.color(), text)
+                .on_hover_text(format!("{sensitivity:.2} {}/min/kA²", units::temp_unit()));
+            ui.label("");
+            ui.end_row();
+        }
+        ui.end_row();
+    }
+
     heading(ui, "Right accumulator");
-    field(ui, "Min cell voltage", ucell.right.min_voltage, "mV");
-    field(ui, "Avg cell voltage", ucell.right.avg_voltage, "mV");
-    field(ui, "Max cell voltage", ucell.right.max_voltage, "mV");
-    field(ui, "Delta cell voltage", ucell.right.delta_voltage, "mV");
+    field(
+        ui,
+        "Min cell voltage",
+        units::voltage_value(ucell.right.min_voltage),
+        units::voltage_unit(),
+    );
+    field(
+        ui,
+        "Avg cell voltage",
+        units::voltage_value(ucell.right.avg_voltage),
+        units::voltage_unit(),
+    );
+    field(
+        ui,
+        "Max cell voltage",
+        units::voltage_value(ucell.right.max_voltage),
+        units::voltage_unit(),
+    );
+    field(
+        ui,
+        "Delta cell voltage",
+        units::voltage_value(ucell.right.delta_voltage),
+        units::voltage_unit(),
+    );
     ui.end_row();
 
     heading(ui, "Left accumulator");
-    field(ui, "Min cell voltage", ucell.left.min_voltage, "mV");
-    field(ui, "Avg cell voltage", ucell.left.avg_voltage, "mV");
-    field(ui, "Max cell voltage", ucell.left.max_voltage, "mV");
-    field(ui, "Delta cell voltage", ucell.left.delta_voltage, "mV");
+    field(
+        ui,
+        "Min cell voltage",
+        units::voltage_value(ucell.left.min_voltage),
+        units::voltage_unit(),
+    );
+    field(
+        ui,
+        "Avg cell voltage",
+        units::voltage_value(ucell.left.avg_voltage),
+        units::voltage_unit(),
+    );
+    field(
+        ui,
+        "Max cell voltage",
+        units::voltage_value(ucell.left.max_voltage),
+        units::voltage_unit(),
+    );
+    field(
+        ui,
+        "Delta cell voltage",
+        units::voltage_value(ucell.left.delta_voltage),
+        units::voltage_unit(),
+    );
     ui.end_row();
 
-    field(ui, "Min temperature", data.main.temp_min, "°C");
-    field(ui, "Avg temperature", data.main.temp_avg, "°C");
-    field(ui, "Max temperature", data.main.temp_max, "°C");
-    field(ui, "Master temperature", data.main.temp_master, "°C");
+    // sensor statistics without dead sensors, the values reported by the BMS are shown on hover
+    let temps = &data.tcell.overall;
+    temp_field(ui, "Min temperature", temps.min_temp, data.main.temp_min);
+    temp_field(ui, "Avg temperature", temps.avg_temp, data.main.temp_avg);
+    temp_field(ui, "Max temperature", temps.max_temp, data.main.temp_max);
+    field(
+        ui,
+        "Delta temperature",
+        units::temp_delta(temps.delta_temp),
+        "",
+    );
+    field(
+        ui,
+        "Master temperature",
+        units::temp_value(data.main.temp_master),
+        units::temp_unit(),
+    );
     ui.end_row();
 
+    let derived = app.history.last().map(|s| &s.derived);
+    if let (false, Some(derived)) = (app.derived.channels.is_empty(), derived) {
+        heading(ui, "Derived channels");
+        for (channel, value) in app.derived.channels.iter().zip(derived) {
+            ui.label(&channel.name);
+            match value {
+                Some(v) => ui.label(format!("{v:.2}")),
+                None => ui.label("-"),
+            };
+            ui.label(&channel.unit);
+            ui.end_row();
+        }
+        ui.end_row();
+    }
+
+    if !app.script.output.values.is_empty() {
+        heading(ui, "Script");
+        for (name, value, unit) in &app.script.output.values {
+            ui.label(name);
+            ui.label(format!("{value:.2}"));
+            ui.label(unit);
+            ui.end_row();
+        }
+        ui.end_row();
+    }
+
     field(ui, "#Slaves", ucell.num_slaves, "");
     field(ui, "#Cells", ucell.num_cells, "");
     field(ui, "#Cells / #Slaves", ucell.num_cells_per_slave, "");
@@ -232,37 +1331,243 @@ fn side_panel(ui: &mut Ui, data: &Data) {
     field(ui, "#Safe resistors", ucell.num_safe_resistors, "");
 }
 
-fn heading(ui: &mut Ui, name: &str) {
-    ui.heading(name);
+fn driver_view(ui: &mut Ui, data: &Data, fix: Option<Fix>, regen: &RegenConfig) {
+    let mut values = vec![
+        (
+            tr("Min cell voltage"),
+            units::voltage_value(data.ucell.overall.min_voltage),
+            units::voltage_unit(),
+            None,
+        ),
+        (
+            tr("Max temperature"),
+            units::temp_value(data.main.temp_max),
+            units::temp_unit(),
+            None,
+        ),
+        (
+            tr("Voltage"),
+            format!("{:.1}", data.main.voltage),
+            "V",
+            None,
+        ),
+        (
+            tr("Current"),
+            format!("{:.1}", data.main.current / 1000.0),
+            "A",
+            regen.color(data.main.current),
+        ),
+    ];
+    if let Some(fix) = fix {
+        values.push((tr("Speed"), format!("{:.0}", fix.speed), "km/h", None));
+    }
+
+    let rows = values.len().div_ceil(2);
+    let pos = ui.cursor().min;
+    let size = ui.available_size() / Vec2::new(2.0, rows as f32);
+    let font_size = size.y / 3.0;
+    for (i, (name, value, unit, color)) in values.into_iter().enumerate() {
+        let value_pos = pos + Vec2::new((i % 2) as f32 * size.x, (i / 2) as f32 * size.y);
+        ui.allocate_ui_at_rect(Rect::from_min_size(value_pos, size), |ui| {
+            ui.vertical_centered(|ui| {
+                ui.label(RichText::new(name).size(font_size / 3.0));
+                let mut text = RichText::new(format!("{value} {unit}"))
+                    .font(FontId::new(font_size, FontFamily::Monospace))
+                    .strong();
+                if let Some(color) = color {
+                    text = text.color(color);
+                }
+                ui.label(text);
+            });
+        });
+    }
+}
+
+fn heading(ui: &mut Ui, name: &'static str) {
+    ui.heading(tr(name));
     ui.end_row();
 }
 
-fn field(ui: &mut Ui, name: &str, value: impl ToString, unit: &str) {
-    ui.label(name);
+fn field(ui: &mut Ui, name: &'static str, value: impl ToString, unit: impl ToString) {
+    ui.label(tr(name));
     ui.label(value.to_string());
-    ui.label(unit);
+    ui.label(unit.to_string());
     ui.end_row();
 }
 
-fn draw_temps(ui: &mut Ui, data: &Data, app: &DashboardApp) {
+fn temp_field(ui: &mut Ui, name: &'static str, value: f32, bms_value: f32) {
+    ui.label(tr(name));
+    ui.label(units::temp_value(value))
+        .on_hover_text(format!("BMS: {}", units::temp(bms_value)));
+    ui.label(units::temp_unit());
+    ui.end_row();
+}
+
+/// Every stack with its temperature sensors as a header band above the cells, returns the index
+/// of a clicked cell
+fn draw_pack(ui: &mut Ui, data: &Data, app: &DashboardApp) -> Option<usize> {
     let pos = ui.cursor().min;
     let size = ui.available_size();
     let stack_size = size / Vec2::new(4.0, 2.0);
+    let temp_fraction = match (app.layout.show_temps, app.layout.show_stacks) {
+        (false, _) => 0.0,
+        (true, false) => 1.0,
+        (true, true) => app.layout.temp_height,
+    };
+    let mut clicked = None;
 
     for (i, (x, y, side)) in STACK_POS.iter().enumerate() {
-        let stack_pos = pos + Vec2::new(x * stack_size.x, y * stack_size.y);
-        let stack_rect = Rect::from_min_size(stack_pos, stack_size);
-        let offset = i * 2;
-        ui.allocate_ui_at_rect(stack_rect, |ui| {
-            draw_temp(ui, &data.tcell, offset, app, *side);
-        });
+        let stack_pos = pos + app.layout.grid_pos(*x, *y, 4.0, 2.0) * stack_size;
+        // the gap separates the stacks, so every band visibly belongs to the cells below it
+        let stack_rect = Rect::from_min_size(stack_pos, stack_size).shrink(STACK_GAP);
+        let (temp_rect, cells_rect) = stack_rect
+            .split_top_bottom_at_y(stack_rect.top() + stack_rect.height() * temp_fraction);
+
+        if app.layout.show_temps {
+            ui.allocate_ui_at_rect(temp_rect, |ui| {
+                draw_temp(ui, &data.tcell, i * 2, app, *side);
+            });
+        }
+        if app.layout.show_stacks {
+            let response =
+                ui.allocate_ui_at_rect(cells_rect, |ui| draw_stack(ui, data, i * 18, app, *side));
+            clicked = clicked.or(response.inner);
+        }
+        if app.layout.show_temps || app.layout.show_stacks {
+            let stroke = ui.visuals().widgets.noninteractive.bg_stroke;
+            ui.painter().rect_stroke(stack_rect, Rounding::ZERO, stroke);
+        }
+    }
+    clicked
+}
+
+/// The layout of [`draw_pack`] as a vector diagram on a fixed page in light colors for print,
+/// without the interactive markers
+fn pack_svg(data: &Data, app: &DashboardApp) -> String {
+    const WIDTH: f32 = 1600.0;
+    const HEIGHT: f32 = 1000.0;
+    const CAPTION: f32 = 40.0;
+    let text_color = Color32::BLACK;
+    let stroke = (1.0, Color32::from_gray(0x80));
+    let mut svg = Svg::new(WIDTH, HEIGHT);
+
+    let (ucell, tcell) = (&data.ucell, &data.tcell);
+    let caption = format!(
+        "{:.1} V  {:.1} A  {:.1} %  {} {}  {} {}  {} {}",
+        data.main.voltage,
+        data.main.current / 1000.0,
+        data.main.state_of_charge,
+        tr("Min"),
+        units::voltage(ucell.overall.min_voltage),
+        tr("Max"),
+        units::voltage(ucell.overall.max_voltage),
+        tr("Max temperature"),
+        units::temp(tcell.overall.max_temp)
+    );
+    let caption_rect = Rect::from_min_size(Pos2::ZERO, Vec2::new(WIDTH, CAPTION));
+    svg.text(
+        caption_rect.shrink(STACK_GAP),
+        Align2::LEFT_CENTER,
+        20.0,
+        text_color,
+        &caption,
+    );
+
+    let stack_size = Vec2::new(WIDTH / 4.0, (HEIGHT - CAPTION) / 2.0);
+    let temp_fraction = match (app.layout.show_temps, app.layout.show_stacks) {
+        (false, _) => 0.0,
+        (true, false) => 1.0,
+        (true, true) => app.layout.temp_height,
+    };
+    let cell = |svg: &mut Svg, rect: Rect, fill: Color32, value: String, label: String| {
+        svg.rect(rect, Some(fill), Some(stroke));
+        svg.text(
+            rect,
+            Align2::CENTER_CENTER,
+            rect.height() * 0.4,
+            text_color,
+            &value,
+        );
+        svg.text(
+            rect.shrink(4.0),
+            Align2::RIGHT_BOTTOM,
+            rect.height() * 0.25,
+            text_color,
+            &label,
+        );
+    };
+
+    for (i, (x, y, side)) in STACK_POS.iter().enumerate() {
+        let stack_pos =
+            Pos2::new(0.0, CAPTION) + app.layout.grid_pos(*x, *y, 4.0, 2.0) * stack_size;
+        let stack_rect = Rect::from_min_size(stack_pos, stack_size).shrink(STACK_GAP);
+        let (temp_rect, cells_rect) = stack_rect
+            .split_top_bottom_at_y(stack_rect.top() + stack_rect.height() * temp_fraction);
+
+        if app.layout.show_temps {
+            let avg = match (app.heatmap.relative, side) {
+                (true, Side::Left) => tcell.left.avg_temp,
+                (true, Side::Right) => tcell.right.avg_temp,
+                (false, _) => tcell.overall.avg_temp,
+            };
+            let size = temp_rect.size() / Vec2::new(2.0, 1.0);
+            for j in 0..2 {
+                let index = i * 2 + j;
+                let rect = Rect::from_min_size(
+                    temp_rect.min + app.layout.grid_pos(j as f32, 0.0, 2.0, 1.0) * size,
+                    size,
+                );
+                let (fill, value) = match tcell.temp.get(index) {
+                    Some(t) if !app.temp_mapping.is_dead(index) => (
+                        heatmap_color(false, avg, *t, app.heatmap.temp_delta()),
+                        units::temp_value(*t),
+                    ),
+                    _ => (Color32::from_gray(0xc8), "-".into()),
+                };
+                cell(&mut svg, rect, fill, value, (index + 1).to_string());
+            }
+        }
+        if app.layout.show_stacks {
+            let avg = match (app.heatmap.relative, side) {
+                (true, Side::Left) => ucell.left.avg_voltage,
+                (true, Side::Right) => ucell.right.avg_voltage,
+                (false, _) => ucell.overall.avg_voltage,
+            } as f32;
+            let size = cells_rect.size() / Vec2::new(2.0, 9.0);
+            // the left column counts up from the bottom and the right one down from the top
+            for row in 0..9 {
+                for (column, index) in [(0, i * 18 + 8 - row), (1, i * 18 + row + 9)] {
+                    let rect = Rect::from_min_size(
+                        cells_rect.min
+                            + app.layout.grid_pos(column as f32, row as f32, 2.0, 9.0) * size,
+                        size,
+                    );
+                    let reference = match app.heatmap.neighbors {
+                        true => neighbor_average(&ucell.cell_voltage, index).unwrap_or(avg),
+                        false => avg,
+                    };
+                    let (fill, value) = match ucell.cell_voltage.get(index) {
+                        Some(v) => (
+                            heatmap_color(false, reference, *v as f32, app.heatmap.voltage_delta()),
+                            units::voltage_value(*v),
+                        ),
+                        None => (Color32::from_gray(0xc8), "-".into()),
+                    };
+                    cell(&mut svg, rect, fill, value, app.numbering.label(index));
+                }
+            }
+        }
+        if app.layout.show_temps || app.layout.show_stacks {
+            svg.rect(stack_rect, None, Some((2.0, text_color)));
+        }
     }
+    svg.finish()
 }
 
 fn draw_temp(ui: &mut Ui, tcell: &Tcell, offset: usize, app: &DashboardApp, side: Side) {
     let pos = ui.cursor().min;
     let cell_size = ui.available_size() / Vec2::new(2.0, 1.0);
-    let avg = if app.relative_heatmap {
+    let avg = if app.heatmap.relative {
         match side {
             Side::Left => tcell.left.avg_temp,
             Side::Right => tcell.right.avg_temp,
@@ -274,55 +1579,43 @@ fn draw_temp(ui: &mut Ui, tcell: &Tcell, offset: usize, app: &DashboardApp, side
     for i in 0..2 {
         let cell_index = offset + i;
         let cell_temp = tcell.temp.get(cell_index).copied().unwrap_or(f32::MAX);
-        let bg_color = heatmap_color(ui, avg, cell_temp, app.temp_heatmap_delta);
-
-        let cell_pos = pos + Vec2::new(i as f32 * cell_size.x, 0.0);
-        let mut rect = Rect::from_min_size(cell_pos, cell_size);
-        ui.painter().rect_filled(rect, Rounding::ZERO, bg_color);
-
-        let font_size = (cell_size.x + cell_size.y) / 8.0;
-
-        ui.allocate_ui_at_rect(rect, |ui| {
-            ui.centered_and_justified(|ui| {
-                ui.label(
-                    RichText::new(cell_temp.to_string())
-                        .font(FontId::new(font_size, FontFamily::Monospace)),
-                );
-            });
-        });
-
-        rect.min.y += cell_size.y / 2.0;
-        rect.max.x -= 10.0;
-        ui.allocate_ui_at_rect(rect, |ui| {
-            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
-                ui.label(
-                    RichText::new((cell_index + 1).to_string())
-                        .font(FontId::new(font_size / 2.0, FontFamily::Monospace)),
-                )
-            });
-        });
-    }
-}
-
-fn draw_stacks(ui: &mut Ui, data: &Data, app: &DashboardApp) {
-    let pos = ui.cursor().min;
-    let size = ui.available_size();
-    let stack_size = size / Vec2::new(4.0, 2.0);
+        let bg_color = if app.temp_mapping.is_dead(cell_index) {
+            ui.visuals().widgets.inactive.bg_fill
+        } else {
+            heatmap_color(
+                ui.visuals().dark_mode,
+                avg,
+                cell_temp,
+                app.heatmap.temp_delta(),
+            )
+        };
 
-    for (i, (x, y, side)) in STACK_POS.iter().enumerate() {
-        let stack_pos = pos + Vec2::new(x * stack_size.x, y * stack_size.y);
-        let stack_rect = Rect::from_min_size(stack_pos, stack_size);
-        let offset = i * 18;
-        ui.allocate_ui_at_rect(stack_rect, |ui| {
-            draw_stack(ui, &data.ucell, offset, app, *side)
-        });
+        let cell_pos = pos + app.layout.grid_pos(i as f32, 0.0, 2.0, 1.0) * cell_size;
+        let rect = Rect::from_min_size(cell_pos, cell_size);
+        let label = cell_index + 1;
+        let value = match tcell.temp.get(cell_index) {
+            Some(t) if !app.temp_mapping.is_dead(cell_index) => {
+                let offset = units::temp_delta_scale((t - tcell.overall.avg_temp) as f64);
+                format!("{} {offset:+.1}", units::temp_value(*t))
+            }
+            _ => units::temp_value(cell_temp),
+        };
+        draw_cell(ui, rect, bg_color, value, label, &app.font_sizes);
     }
 }
 
-fn draw_stack(ui: &mut Ui, ucell: &Ucell, offset: usize, app: &DashboardApp, side: Side) {
+fn draw_stack(
+    ui: &mut Ui,
+    data: &Data,
+    offset: usize,
+    app: &DashboardApp,
+    side: Side,
+) -> Option<usize> {
+    let mut clicked = None;
+    let ucell = &data.ucell;
     let pos = ui.cursor().min;
     let cell_size = ui.available_size() / Vec2::new(2.0, 9.0);
-    let avg = if app.relative_heatmap {
+    let avg = if app.heatmap.relative {
         match side {
             Side::Left => ucell.left.avg_voltage,
             Side::Right => ucell.right.avg_voltage,
@@ -330,6 +1623,10 @@ fn draw_stack(ui: &mut Ui, ucell: &Ucell, offset: usize, app: &DashboardApp, sid
     } else {
         ucell.overall.avg_voltage
     };
+    let reference = |cell_index: usize| match app.heatmap.neighbors {
+        true => neighbor_average(&ucell.cell_voltage, cell_index).unwrap_or(avg as f32),
+        false => avg as f32,
+    };
 
     for i in 0..9 {
         let cell_index = offset + (8 - i);
@@ -339,37 +1636,30 @@ fn draw_stack(ui: &mut Ui, ucell: &Ucell, offset: usize, app: &DashboardApp, sid
             .copied()
             .unwrap_or(u16::MAX);
         let bg_color = heatmap_color(
-            ui,
-            avg as f32,
+            ui.visuals().dark_mode,
+            reference(cell_index),
             cell_voltage as f32,
-            app.voltage_heatmap_delta,
+            app.heatmap.voltage_delta(),
         );
 
-        let cell_pos = pos + Vec2::new(0.0, i as f32 * cell_size.y);
-        let mut rect = Rect::from_min_size(cell_pos, cell_size);
-        ui.painter().rect_filled(rect, Rounding::ZERO, bg_color);
-
-        let font_size = (cell_size.x + cell_size.y) / 8.0;
-
-        ui.allocate_ui_at_rect(rect, |ui| {
-            ui.centered_and_justified(|ui| {
-                ui.label(
-                    RichText::new(cell_voltage.to_string())
-                        .font(FontId::new(font_size, FontFamily::Monospace)),
-                );
-            });
-        });
-
-        rect.min.y += cell_size.y / 2.0;
-        rect.max.x -= 10.0;
-        ui.allocate_ui_at_rect(rect, |ui| {
-            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
-                ui.label(
-                    RichText::new((cell_index + 1).to_string())
-                        .font(FontId::new(font_size / 2.0, FontFamily::Monospace)),
-                )
-            });
-        });
+        let cell_pos = pos + app.layout.grid_pos(0.0, i as f32, 2.0, 9.0) * cell_size;
+        let rect = Rect::from_min_size(cell_pos, cell_size);
+        draw_cell(
+            ui,
+            rect,
+            bg_color,
+            units::voltage_value(cell_voltage),
+            app.numbering.label(cell_index),
+            &app.font_sizes,
+        );
+        draw_voltage_markers(ui, rect, cell_index, data, app);
+        if ui
+            .interact(rect, ui.id().with(cell_index), Sense::click())
+            .on_hover_cursor(CursorIcon::PointingHand)
+            .clicked()
+        {
+            clicked = Some(cell_index);
+        }
     }
 
     for i in 0..9 {
@@ -380,69 +1670,900 @@ fn draw_stack(ui: &mut Ui, ucell: &Ucell, offset: usize, app: &DashboardApp, sid
             .copied()
             .unwrap_or(u16::MAX);
         let bg_color = heatmap_color(
-            ui,
-            avg as f32,
+            ui.visuals().dark_mode,
+            reference(cell_index),
             cell_voltage as f32,
-            app.voltage_heatmap_delta,
+            app.heatmap.voltage_delta(),
+        );
+
+        let cell_pos = pos + app.layout.grid_pos(1.0, i as f32, 2.0, 9.0) * cell_size;
+        let rect = Rect::from_min_size(cell_pos, cell_size);
+        draw_cell(
+            ui,
+            rect,
+            bg_color,
+            units::voltage_value(cell_voltage),
+            app.numbering.label(cell_index),
+            &app.font_sizes,
+        );
+        draw_voltage_markers(ui, rect, cell_index, data, app);
+        if ui
+            .interact(rect, ui.id().with(cell_index), Sense::click())
+            .on_hover_cursor(CursorIcon::PointingHand)
+            .clicked()
+        {
+            clicked = Some(cell_index);
+        }
+    }
+    clicked
+}
+
+/// Outlines voltage cells that are flagged by one of the analyses
+fn draw_voltage_markers(
+    ui: &mut Ui,
+    rect: Rect,
+    cell_index: usize,
+    data: &Data,
+    app: &DashboardApp,
+) {
+    if app.temp_mapping.enabled {
+        let temp = app
+            .temp_mapping
+            .sensor(cell_index)
+            .filter(|s| !app.temp_mapping.is_dead(*s))
+            .and_then(|s| data.tcell.temp.get(s));
+        if let Some(temp) = temp {
+            let avg = data.tcell.overall.avg_temp;
+            let color = heatmap_color(ui.visuals().dark_mode, avg, *temp, app.heatmap.temp_delta());
+            ui.painter()
+                .rect_stroke(rect.shrink(3.0), Rounding::ZERO, Stroke::new(6.0, color));
+        }
+    }
+
+    if app.diverging.iter().any(|c| c.index == cell_index) {
+        let stroke = Stroke::new(3.0, Color32::from_rgb(0xff, 0x8c, 0x00));
+        ui.painter()
+            .rect_stroke(rect.shrink(1.5), Rounding::ZERO, stroke);
+    }
+
+    if let Some(changed) = app.changed.iter().find(|c| c.index == cell_index) {
+        let color = Color32::from_rgb(0x00, 0xb0, 0xe0);
+        ui.painter()
+            .rect_stroke(rect.shrink(1.5), Rounding::ZERO, Stroke::new(2.0, color));
+        let (_, index_size) = app.font_sizes.cell(rect.size());
+        ui.painter().text(
+            rect.left_top() + Vec2::new(4.0, 2.0),
+            Align2::LEFT_TOP,
+            format!("{:+}", changed.delta),
+            FontId::new(index_size, FontFamily::Monospace),
+            color,
         );
+    }
 
-        let cell_pos = pos + Vec2::new(cell_size.x, i as f32 * cell_size.y);
-        let mut rect = Rect::from_min_size(cell_pos, cell_size);
-        ui.painter().rect_filled(rect, Rounding::ZERO, bg_color);
+    let time = ui.input(|i| i.time);
+    app.cell_search
+        .highlight(ui.painter(), rect, cell_index, time);
+}
 
-        let font_size = (cell_size.x + cell_size.y) / 8.0;
+fn draw_cell(
+    ui: &mut Ui,
+    mut rect: Rect,
+    bg_color: Color32,
+    value: impl ToString,
+    label: impl ToString,
+    font_sizes: &FontSizes,
+) {
+    ui.painter().rect_filled(rect, Rounding::ZERO, bg_color);
 
-        ui.allocate_ui_at_rect(rect, |ui| {
-            ui.centered_and_justified(|ui| {
-                ui.label(
-                    RichText::new(cell_voltage.to_string())
-                        .font(FontId::new(font_size, FontFamily::Monospace)),
+    let (value_size, index_size) = font_sizes.cell(rect.size());
+
+    ui.allocate_ui_at_rect(rect, |ui| {
+        ui.centered_and_justified(|ui| {
+            ui.label(
+                RichText::new(value.to_string())
+                    .font(FontId::new(value_size, FontFamily::Monospace)),
+            );
+        });
+    });
+
+    rect.min.y += rect.height() / 2.0;
+    rect.max.x -= 10.0;
+    ui.allocate_ui_at_rect(rect, |ui| {
+        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+            ui.label(
+                RichText::new(label.to_string())
+                    .font(FontId::new(index_size, FontFamily::Monospace)),
+            )
+        });
+    });
+}
+
+impl DashboardApp {
+    /// Data source, session, windows and settings, hidden on spectator screens
+    fn settings_section(&mut self, ui: &mut Ui, section: Section) {
+        match section {
+            Section::Polling => {
+                ui.horizontal(|ui| {
+                    ui.label(tr("Poll rate"));
+                    ui.add(
+                        DragValue::new(&mut self.poll_rate)
+                            .clamp_range(100..=10000)
+                            .speed(10)
+                            .suffix(" ms"),
+                    );
+                });
+            }
+            Section::BmsRequests => self.http.settings(ui),
+            Section::IntegrityChecks => self.integrity.settings(ui),
+            Section::LiveStreaming => self.push_config.settings(ui, &self.pusher),
+            Section::Mirror => self.mirror_config.settings(ui, &self.mirror_server),
+            Section::Gps => self.gps_config.settings(ui),
+            Section::Fleet => self.fleet_config.settings(ui),
+            Section::Launch => self.launch.settings(ui),
+            Section::Language => self.language.settings(ui),
+            Section::Appearance => self.appearance.settings(ui),
+            Section::Window => self.window.settings(ui),
+            Section::Layout => {
+                let layout = &mut self.layout;
+                ui.checkbox(&mut layout.show_stats, tr("Stats panel"));
+                ui.checkbox(&mut layout.show_temps, tr("Temperatures"));
+                ui.checkbox(&mut layout.show_stacks, tr("Stacks"));
+                ui.checkbox(&mut layout.mirror_horizontal, tr("Mirror horizontally"));
+                ui.checkbox(&mut layout.mirror_vertical, tr("Mirror vertically"));
+                ui.add_enabled(
+                    layout.show_temps && layout.show_stacks,
+                    Slider::new(&mut layout.temp_height, 0.05..=0.5).text(tr("Temperature height")),
                 );
+                if ui.button(tr("Reset layout")).clicked() {
+                    *layout = PanelLayout::default();
+                }
+            }
+            Section::Units => self.units.settings(ui),
+            Section::FontSizes => self.font_sizes.settings(ui),
+            Section::Heatmap => self.heatmap.settings(ui),
+            Section::CellNumbering => {
+                let cells = self.data.as_ref().map_or(0, |d| d.ucell.cell_voltage.len());
+                self.numbering.settings(ui, cells);
+            }
+            Section::TemperatureSensors => self.temp_mapping.settings(ui),
+            Section::CurrentDirection => self.regen.settings(ui),
+            Section::Smoothing => self.smoothing.settings(ui),
+            Section::FuelGauge => self.fuel_gauge.settings(ui),
+            Section::KeyboardShortcuts => self.keymap.settings(ui),
+            Section::Alarms => self.alarm_config.settings(ui),
+            Section::CurrentLimits => self.current_limit.settings(ui),
+            Section::Insulation => {
+                ui.horizontal(|ui| {
+                    ui.label(tr("Warning threshold"));
+                    ui.add(
+                        DragValue::new(&mut self.insulation_warning)
+                            .clamp_range(0.0..=10000.0)
+                            .suffix(" kΩ"),
+                    );
+                });
+            }
+            Section::Anomalies => self.anomaly.settings(ui),
+            Section::Script => self.script_config.settings(ui, &self.script),
+            Section::EndOfCharge => self.end_of_charge_config.settings(ui),
+            Section::Precharge => self.precharge_config.settings(ui),
+            Section::Recording => {
+                self.logging.settings(ui);
+                self.timelapse_config.settings(ui);
+            }
+            Section::History => self.history_config.settings(ui, &self.history),
+            Section::Laps => self.lap_config.settings(ui),
+            Section::DriverMessages => self.driver_messages.settings(ui, &self.keymap),
+            Section::TestProcedure => self.procedure_config.settings(ui),
+            Section::Heartbeat => self.heartbeat_config.settings(ui, &self.heartbeat),
+            Section::VoltageSag => self.sag_config.settings(ui),
+            Section::StateOfCharge => self.ocv.settings(ui),
+            Section::DerivedChannels => self.derived.settings(ui),
+            Section::Prediction => self.prediction_config.settings(ui),
+            Section::Trend => self.trend.settings(ui),
+            Section::Charging => self.charging.settings(ui),
+            Section::Cooling => self.cooling_config.settings(ui),
+            Section::StateOfHealth => self.soh_config.settings(ui),
+            Section::Balancing => self.balancing_config.settings(ui),
+        }
+    }
+
+    fn toolbar(&mut self, ui: &mut Ui) {
+        ComboBox::from_id_source("source")
+            .selected_text(self.source.name())
+            .show_ui(ui, |ui| {
+                for source in Source::ALL {
+                    ui.selectable_value(&mut self.source, source, source.name());
+                }
             });
+        match self.source {
+            Source::Http => {
+                ui.label("IP");
+                ui.horizontal(|ui| {
+                    ui.set_width(160.0);
+                    ui.text_edit_singleline(&mut self.ip);
+                });
+                self.address_check.show(ui, &self.ip);
+            }
+            Source::Can => {
+                ui.label(tr("Interface"));
+                ui.horizontal(|ui| {
+                    ui.set_width(80.0);
+                    ui.text_edit_singleline(&mut self.can_interface);
+                });
+            }
+            Source::Serial => {
+                ui.label(tr("Port"));
+                ui.horizontal(|ui| {
+                    ui.set_width(120.0);
+                    ui.text_edit_singleline(&mut self.serial_port);
+                });
+                ComboBox::from_id_source("baud_rate")
+                    .selected_text(self.baud_rate.to_string())
+                    .show_ui(ui, |ui| {
+                        for rate in [9600, 19200, 38400, 57600, 115200, 230400, 460800] {
+                            ui.selectable_value(&mut self.baud_rate, rate, rate.to_string());
+                        }
+                    });
+            }
+            Source::Mirror => {
+                ui.label(tr("Dashboard"));
+                ui.horizontal(|ui| {
+                    ui.set_width(180.0);
+                    ui.text_edit_singleline(&mut self.mirror_address);
+                });
+            }
+        }
+
+        let session = match &self.sessions.current {
+            Some(s) => s.name.as_str(),
+            None => tr("No session"),
+        };
+        if ui.button(session).clicked() {
+            self.sessions.open(self.logging.directory());
+        }
+
+        if !self.outliers.outliers.is_empty() {
+            let text = format!("{} {}", self.outliers.outliers.len(), tr("outliers"));
+            let button = Button::new(RichText::new(text).color(Color32::WHITE))
+                .fill(Color32::from_rgb(0xc0, 0x30, 0x30));
+            if ui.add(button).clicked() {
+                self.outliers.show = true;
+            }
+        }
+        if self.quarantine.count > 0 {
+            let text = format!("{} {}", self.quarantine.count, tr("quarantined"));
+            let button = Button::new(RichText::new(text).color(Color32::WHITE))
+                .fill(Color32::from_rgb(0xc0, 0x80, 0x20));
+            if ui.add(button).clicked() {
+                self.quarantine.show = true;
+            }
+        }
+
+        ui.menu_button(tr("Heatmap"), |ui| self.heatmap.menu(ui));
+
+        ui.menu_button(tr("View"), |ui| {
+            for view in View::ALL {
+                ui.radio_value(&mut self.view, view, view.name());
+            }
+            ui.separator();
+            ui.checkbox(&mut self.driver_mode, tr("Driver mode"));
+            ui.checkbox(&mut self.soh.show, tr("State of health"));
+            ui.checkbox(&mut self.procedure.show, tr("Test procedure"));
+            ui.checkbox(&mut self.balancing.show, tr("Balancing recommendation"));
+            ui.checkbox(&mut self.precharge.show, tr("Precharge"));
+            ui.checkbox(&mut self.control.show, tr("Control"));
+            ui.checkbox(&mut self.firmware.show, tr("Firmware update"));
+            ui.checkbox(&mut self.laps.show, tr("Laps"));
+            ui.checkbox(&mut self.outliers.show, tr("Outliers"));
+            ui.checkbox(&mut self.inventory.show, tr("Cell inventory"));
+            if ui.button(tr("Export pack as SVG")).clicked() {
+                self.export_svg();
+                ui.close_menu();
+            }
+            ui.checkbox(&mut self.quarantine.show, tr("Quarantined samples"));
+            ui.checkbox(&mut self.alarms.show_log, tr("Alarm log"));
+            ui.checkbox(&mut self.derived_window.show, tr("Derived channels"));
+            ui.checkbox(&mut self.script.show, tr("Script"));
+            ui.checkbox(&mut self.fleet.show, tr("Fleet"));
+            ui.checkbox(&mut self.diagnostics.show, tr("Network diagnostics"));
         });
 
-        rect.min.y += cell_size.y / 2.0;
-        rect.max.x -= 10.0;
-        ui.allocate_ui_at_rect(rect, |ui| {
-            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
-                ui.label(
-                    RichText::new((cell_index + 1).to_string())
-                        .font(FontId::new(font_size / 2.0, FontFamily::Monospace)),
-                )
+        if ui.button(tr("Settings")).clicked() {
+            self.show_settings = !self.show_settings;
+        }
+    }
+
+    /// Disabling safe mode while receiving live data has to be confirmed first
+    fn toggle_safe(&mut self) {
+        let live = self.replay.is_none() && self.data.is_some() && !self.paused;
+        if self.safe && live {
+            self.confirm_unsafe = true;
+        } else {
+            self.safe = !self.safe;
+        }
+    }
+
+    fn safe_mode_dialog(&mut self, ctx: &egui::Context) {
+        if !self.confirm_unsafe {
+            return;
+        }
+        Window::new(tr("Disable safe mode"))
+            .id("confirm_unsafe".into())
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(tr(
+                    "Implausible cell voltages and temperatures will be shown as received.",
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button(tr("Disable safe mode")).clicked() {
+                        self.safe = false;
+                        self.confirm_unsafe = false;
+                    }
+                    if ui.button(tr("cancel")).clicked() {
+                        self.confirm_unsafe = false;
+                    }
+                });
             });
+    }
+
+    fn run_action(&mut self, ctx: &egui::Context, action: Action) {
+        if self.spectator && !action.display_only() {
+            return;
+        }
+        match action {
+            Action::Help => self.keymap.show_help = !self.keymap.show_help,
+            Action::CommandPalette => self.palette.toggle(),
+            Action::Settings => self.show_settings = !self.show_settings,
+            Action::ToggleSafe => self.toggle_safe(),
+            Action::Pause => match &mut self.replay {
+                Some(replay) => replay.playing = !replay.playing,
+                None => self.paused = !self.paused,
+            },
+            Action::DriverMode => self.driver_mode = !self.driver_mode,
+            Action::View(view) => self.view = view,
+            Action::ToggleStats => self.layout.show_stats = !self.layout.show_stats,
+            Action::ToggleTemps => self.layout.show_temps = !self.layout.show_temps,
+            Action::ToggleStacks => self.layout.show_stacks = !self.layout.show_stacks,
+            Action::ToggleNumbering => self.numbering.physical = !self.numbering.physical,
+            Action::MirrorLayout => {
+                self.layout.mirror_horizontal = !self.layout.mirror_horizontal;
+            }
+            Action::Screenshot => ctx.send_viewport_cmd(ViewportCommand::Screenshot),
+            Action::ExportSvg => self.export_svg(),
+            Action::ToggleRecording => self.logging.enabled = !self.logging.enabled,
+            Action::StateOfHealth => self.soh.show = !self.soh.show,
+            Action::TestProcedure => self.procedure.show = !self.procedure.show,
+            Action::Balancing => self.balancing.show = !self.balancing.show,
+            Action::FindCell => self.cell_search.toggle(),
+            Action::CellInventory => self.inventory.show = !self.inventory.show,
+            Action::Precharge => self.precharge.show = !self.precharge.show,
+            Action::Control => self.control.show = !self.control.show,
+            Action::FirmwareUpdate => self.firmware.show = !self.firmware.show,
+            Action::Laps => self.laps.show = !self.laps.show,
+            Action::Outliers => self.outliers.show = !self.outliers.show,
+            Action::AcknowledgeAlarms => self.alarms.acknowledge_all(now()),
+            Action::AlarmLog => self.alarms.show_log = !self.alarms.show_log,
+            Action::DerivedChannels => self.derived_window.show = !self.derived_window.show,
+            Action::ScriptPanel => self.script.show = !self.script.show,
+            Action::Fleet => self.fleet.show = !self.fleet.show,
+            Action::Diagnostics => self.diagnostics.show = !self.diagnostics.show,
+            Action::Quit => self.window.quit(ctx),
+            Action::MarkLap => self.laps.mark(now()),
+            Action::Session => self.sessions.open(self.logging.directory()),
+            Action::AddMarker => self.markers.add(now()),
+            Action::DriverMessage(slot) => self.log_driver_message(slot),
+            Action::Replay => self.replay_picker.open(self.logging.directory()),
+            Action::VoltageProfile(i) => self.heatmap.active_voltage = i as usize,
+            Action::TempProfile(i) => self.heatmap.active_temp = i as usize,
+        }
+    }
+
+    /// Logs a predefined driver message as a marker of the current session
+    fn log_driver_message(&mut self, slot: u8) {
+        let Some(text) = self.driver_messages.text(slot) else {
+            return;
+        };
+        let marker = Marker {
+            time: now(),
+            text: format!("{}: {text}", tr("Driver")),
+        };
+        let directory = self.sessions.directory(self.logging.directory());
+        self.status = Some(match self.markers.insert(&directory, marker) {
+            Ok(()) => format!("{}: {text}", tr("Driver message logged")),
+            Err(e) => format!("{}: {e}", tr("Error saving marker")),
         });
     }
-}
 
-impl DashboardApp {
+    /// Saves the pack layout with the displayed values as an SVG next to the screenshots
+    fn export_svg(&mut self) {
+        let Some(data) = &self.data else {
+            self.status = Some(tr("No data").to_string());
+            return;
+        };
+        let data = match self.smoothing.applies(self.view, self.driver_mode) {
+            true => self.smoother.data.as_ref().unwrap_or(data),
+            false => data,
+        };
+        let compensated = self
+            .heatmap
+            .compensated
+            .then(|| self.sag.compensate(&self.sag_config, data));
+        let svg = pack_svg(compensated.as_ref().unwrap_or(data), self);
+
+        let directory = if self.sessions.current.is_some() {
+            self.sessions.directory(self.logging.directory())
+        } else {
+            PathBuf::new()
+        };
+        let path = directory.join(format!("pack-{}.svg", now()));
+        let result = std::fs::create_dir_all(&directory).and_then(|()| std::fs::write(&path, svg));
+        self.status = Some(match result {
+            Ok(()) => format!("{} {}", tr("Saved"), path.display()),
+            Err(e) => format!("{}: {e}", tr("Error exporting SVG")),
+        });
+    }
+
+    fn save_screenshots(&mut self, ctx: &egui::Context) {
+        let images: Vec<Arc<ColorImage>> = ctx.input(|i| {
+            i.raw
+                .events
+                .iter()
+                .filter_map(|e| match e {
+                    Event::Screenshot { image, .. } => Some(image.clone()),
+                    _ => None,
+                })
+                .collect()
+        });
+
+        for image in images {
+            let timelapse = std::mem::take(&mut self.timelapse.pending);
+            let directory = if timelapse {
+                let directory = self.sessions.directory(self.logging.directory());
+                directory.join("timelapse")
+            } else if self.sessions.current.is_some() {
+                self.sessions.directory(self.logging.directory())
+            } else {
+                PathBuf::new()
+            };
+            let path = directory.join(format!("screenshot-{}.png", now()));
+            let [width, height] = image.size;
+            let res = std::fs::create_dir_all(&directory)
+                .map_err(Into::into)
+                .and_then(|()| {
+                    image::save_buffer(
+                        &path,
+                        image.as_raw(),
+                        width as u32,
+                        height as u32,
+                        image::ColorType::Rgba8,
+                    )
+                });
+            match res {
+                // the timelapse would flood the status bar
+                Ok(()) if timelapse => (),
+                Ok(()) => self.status = Some(format!("{} {}", tr("Saved"), path.display())),
+                Err(e) => self.status = Some(format!("{}: {e}", tr("Error saving screenshot"))),
+            }
+        }
+    }
+
+    /// `now` is the time the sample was taken, a pipelined request was issued a poll earlier
+    fn process_data(&mut self, data: &Data, now: u128) {
+        self.analyze(data, now);
+        self.precharge.update(&self.precharge_config, data, now);
+        let finished =
+            self.end_of_charge
+                .update(&self.end_of_charge_config, &self.charging, data, now);
+        // spectators don't write to the session
+        if finished && !self.spectator {
+            let marker = Marker {
+                time: now,
+                text: tr("Charging complete").to_string(),
+            };
+            let directory = self.sessions.directory(self.logging.directory());
+            self.status = Some(match self.markers.insert(&directory, marker) {
+                Ok(()) => tr("Charging complete").to_string(),
+                Err(e) => format!("{}: {e}", tr("Error saving marker")),
+            });
+        }
+        if let Err(e) = self
+            .procedure
+            .update(&self.procedure_config, &self.ocv, data, now)
+        {
+            self.status = Some(format!("{}: {e}", tr("Error saving test")));
+        }
+        self.update_gps();
+        let fix = self.gps.as_ref().and_then(Gps::fix);
+        self.laps.update(&self.lap_config, data, fix, now);
+        // the dashboard that is spectated already pushes the samples
+        if !self.spectator {
+            self.pusher.push(&self.push_config, data, now);
+        }
+
+        if let Err(e) = self.record(data, now) {
+            self.logging.enabled = false;
+            self.status = Some(format!("{}: {e}", tr("Error recording data")));
+        }
+    }
+
+    /// Updates everything derived from the history, shared by live data and replays
+    fn analyze(&mut self, data: &Data, now: u128) {
+        let derived = self.derived.evaluate(data);
+        self.history.push(&self.history_config, data, derived, now);
+        self.current_peaks.update(data);
+        self.sag.update(&self.sag_config, &self.history, now);
+        self.ocv_estimator.update(&self.ocv, data, now);
+        self.prediction = Prediction::compute(
+            &self.prediction_config,
+            &self.history,
+            data.main.state_of_charge,
+        );
+        self.weakest_cell = WeakestCell::compute(&self.trend, &self.history);
+        self.cooling = cooling_health(
+            &self.cooling_config,
+            &self.history,
+            &self.temp_mapping,
+            STACK_POS.len(),
+        );
+        self.diverging = if self.anomaly.enabled {
+            diverging_cells(&self.anomaly, &self.history)
+        } else {
+            Vec::new()
+        };
+        self.changed = changed_cells(&self.anomaly, &self.history);
+        self.alarms.update(
+            &self.alarm_config,
+            &self.trend,
+            self.insulation_warning,
+            data,
+            now,
+        );
+        self.current_watchdog.update(&self.current_limit, data, now);
+        self.alarms
+            .update_current(&self.current_limit, &self.current_watchdog, now);
+        self.script.run(&self.script_config, data, now);
+        self.alarms.update_script(&self.script.output.alarms, now);
+        self.outliers.update(
+            data,
+            &self.trend,
+            &self.diverging,
+            self.weakest_cell.as_ref(),
+            now,
+        );
+        self.smoother
+            .push(&self.smoothing, data, &self.temp_mapping.dead);
+    }
+
+    fn replay_data(&mut self) {
+        let Some(replay) = &mut self.replay else {
+            return;
+        };
+        let seeked = replay.take_seeked();
+        if !replay.tick(now()) && !seeked {
+            return;
+        }
+
+        // refill the history with everything the analyses look back on
+        let position = replay.position;
+        if seeked {
+            let window = [
+                self.prediction_config.window,
+                self.trend.window,
+                self.anomaly.window,
+                self.charging.window,
+            ];
+            let window = window.into_iter().max().unwrap_or_default() as u128 * 1000;
+            let start = replay.time(position).saturating_sub(window);
+            let first = (0..position)
+                .find(|i| replay.time(*i) >= start)
+                .unwrap_or(position);
+            self.history = History::default();
+            self.smoother.clear();
+            self.ocv_estimator = OcvEstimator::default();
+            for i in first..position {
+                let data = replay.data(i, self.safe, &self.temp_mapping.dead);
+                let derived = self.derived.evaluate(&data);
+                self.history
+                    .push(&self.history_config, &data, derived, replay.time(i));
+            }
+        }
+
+        let replay = self.replay.as_ref().unwrap();
+        let time = replay.time(position);
+        let data = replay.data(position, self.safe, &self.temp_mapping.dead);
+        self.analyze(&data, time);
+        self.data = Some(data);
+        self.error = None;
+    }
+
+    fn open_replay(&mut self, path: &Path) {
+        match Replay::load(path) {
+            Ok(replay) => {
+                self.replay = Some(replay);
+                self.request = None;
+                self.frames.clear();
+                self.alarms.reset();
+                self.current_watchdog = CurrentWatchdog::default();
+                self.current_peaks = CurrentPeaks::default();
+                self.ocv_estimator = OcvEstimator::default();
+            }
+            Err(e) => self.status = Some(format!("{}: {e}", tr("Error loading recording"))),
+        }
+    }
+
+    fn stop_replay(&mut self) {
+        self.replay = None;
+        self.alarms.reset();
+        self.current_watchdog = CurrentWatchdog::default();
+        self.current_peaks = CurrentPeaks::default();
+        self.ocv_estimator = OcvEstimator::default();
+        self.history = History::default();
+        self.smoother.clear();
+        self.frames.clear();
+        self.data = None;
+    }
+
+    /// Shows a pack of the fleet in the dashboard, its history starts from scratch
+    fn open_pack(&mut self, i: usize) {
+        let ip = self.fleet_config.packs[i].ip.clone();
+        if self.source == Source::Http && self.ip == ip {
+            return;
+        }
+        if self.replay.is_some() {
+            self.stop_replay();
+        }
+        self.source = Source::Http;
+        self.ip = ip;
+        self.alarms.reset();
+        self.current_watchdog = CurrentWatchdog::default();
+        self.current_peaks = CurrentPeaks::default();
+        self.history = History::default();
+        self.smoother.clear();
+        self.frames.clear();
+        self.quarantine.clear();
+        self.data = None;
+    }
+
+    fn update_gps(&mut self) {
+        if !self.gps_config.enabled {
+            self.gps = None;
+            return;
+        }
+        if let Some(e) = self.gps.as_ref().and_then(Gps::take_error) {
+            self.status = Some(format!("{}: {e}", tr("GPS error")));
+        }
+        // a failed receiver is kept until the retry is due, opening it again every sample would
+        // flood the status bar
+        if self
+            .gps
+            .as_ref()
+            .is_some_and(|g| g.config == self.gps_config && !g.retry_due())
+        {
+            return;
+        }
+        self.gps = Some(Gps::open(&self.gps_config));
+    }
+
+    fn record(&mut self, data: &Data, now: u128) -> std::io::Result<()> {
+        if !self.logging.enabled {
+            self.finish_recording();
+            return Ok(());
+        }
+
+        // start a new recording when the session or the derived channels change or the file is full
+        let session_directory = self.sessions.directory(self.logging.directory());
+        let columns = self.derived.columns();
+        let finished = self.recorder.as_ref().is_some_and(|r| {
+            r.path.parent() != Some(&session_directory)
+                || r.derived != columns
+                || r.is_full(&self.logging, now)
+        });
+        if finished {
+            self.finish_recording();
+        }
+        let recorder = match &mut self.recorder {
+            Some(r) => r,
+            None => {
+                let recorder = Recorder::start(&session_directory, data, columns, now)?;
+                self.status = Some(format!(
+                    "{} {}",
+                    tr("Recording to"),
+                    recorder.path.display()
+                ));
+                self.recorder.insert(recorder)
+            }
+        };
+        let fix = self.gps.as_ref().and_then(Gps::fix);
+        let derived = self.derived.evaluate(data);
+        recorder.write(data, fix, &derived, now)?;
+
+        let directory = self.logging.directory();
+        self.soh
+            .update(&self.soh_config, &self.ocv, directory, data, now)
+    }
+
+    /// Closes the current recording and converts, exports and compresses it in the background
+    fn finish_recording(&mut self) {
+        let Some(recorder) = self.recorder.take() else {
+            return;
+        };
+        if self.logging.compress || self.logging.parquet || self.logging.motec {
+            let path = recorder.path.clone();
+            drop(recorder);
+            self.finishing.push(recording::finish(path, &self.logging));
+        }
+    }
+
+    fn check_finished_recordings(&mut self) {
+        let (finished, running) = std::mem::take(&mut self.finishing)
+            .into_iter()
+            .partition(|c| c.is_finished());
+        self.finishing = running;
+        for recording in finished {
+            match recording.join() {
+                Ok(Ok(())) => (),
+                Ok(Err(e)) => {
+                    self.status = Some(format!("{}: {e}", tr("Error finishing recording")))
+                }
+                Err(_) => self.status = Some(tr("Unexpected error").to_string()),
+            }
+        }
+    }
+
+    /// Polls, records and sends the heartbeat. Called with every frame and by the background
+    /// thread while eframe doesn't call `update`, e.g. while the window is minimized.
+    pub fn tick(&mut self, ctx: &egui::Context) {
+        self.last_tick = Instant::now();
+        self.check_finished_recordings();
+        if self.replay.is_none() {
+            self.poll_data();
+            if let Some(data) = self
+                .frames
+                .take(&self.smoothing, &self.temp_mapping.dead, now())
+            {
+                self.data = Some(data);
+            }
+            // brings a minimized or hidden window to the user's attention
+            if self.alarms.take_fired() {
+                ctx.send_viewport_cmd(ViewportCommand::RequestUserAttention(
+                    UserAttentionType::Critical,
+                ));
+                ctx.request_repaint();
+            }
+        }
+        self.fleet
+            .update(&self.fleet_config, &self.http, self.safe, now());
+        // spectator screens aren't loggers
+        if !self.spectator {
+            self.heartbeat.update(&self.heartbeat_config, now());
+        }
+    }
+
+    /// The alarms of a replay were raised at the times of the recording
+    fn alarm_time(&self) -> u128 {
+        match &self.replay {
+            Some(replay) => replay.time(replay.position),
+            None => now(),
+        }
+    }
+
+    /// Time since `tick` was called last
+    pub fn since_tick(&self) -> Duration {
+        self.last_tick.elapsed()
+    }
+
     fn poll_data(&mut self) {
+        if self.paused || self.firmware.is_busy() {
+            return;
+        }
+
         match &self.request {
             Some(r) => {
                 if r.is_finished() {
-                    let result = self.request.take().unwrap().join();
-                    match result {
-                        Ok(d) => {
-                            self.data = Some(d);
-                            self.error = None;
+                    match self.request.take().unwrap().join() {
+                        Ok((source, result, time)) => {
+                            // a failed stream is reopened with the next poll
+                            if source.1.is_alive() {
+                                self.data_source = Some(source);
+                            }
+                            self.receive(result, time);
                         }
-                        Err(e) => self.error = Some(e),
+                        Err(_) => self.error = Some(api::Error::Unexpected),
                     }
                 }
             }
             None => {
                 let now = now();
 
-                if self.last_poll + (self.poll_rate as u128) < now {
-                    self.request = Some(fetch(&self.ip, self.safe));
+                // poll as fast as possible while capturing the precharge or logging a test step
+                let capturing = self.precharge.is_capturing() || self.procedure.is_logging();
+                let poll_rate = match capturing {
+                    true => 0,
+                    false => self.poll_rate as u128,
+                };
+                if self.last_poll + poll_rate < now {
                     self.last_poll = now;
+                    let Some((address, mut source)) = self.open_source() else {
+                        return;
+                    };
+                    let options = PollOptions {
+                        safe: self.safe,
+                        dead_sensors: self.temp_mapping.dead.clone(),
+                        pipelining: self.http.pipelining,
+                        format: self.http.format,
+                    };
+                    self.request = Some(thread::spawn(move || {
+                        let result = source.poll(&options);
+                        let time = source.sample_time().unwrap_or_else(self::now);
+                        ((address, source), result, time)
+                    }));
+                }
+            }
+        }
+    }
+
+    /// Reuses the current data source unless its address has changed
+    fn open_source(&mut self) -> Option<(String, Box<dyn DataSource>)> {
+        let address = match self.source {
+            Source::Http => format!("{}{:?}{}", self.ip, self.http.headers, self.http.proxy),
+            Source::Can => self.can_interface.clone(),
+            Source::Serial => format!("{}@{}", self.serial_port, self.baud_rate),
+            Source::Mirror => self.mirror_address.clone(),
+        };
+        if let Some((a, source)) = self.data_source.take() {
+            if a == address {
+                return Some((a, source));
+            }
+        }
+
+        let source: std::io::Result<Box<dyn DataSource>> = match self.source {
+            Source::Http => match parse_address(&self.ip) {
+                Ok(_) => Ok(Box::new(HttpSource::new(self.http.connection(&self.ip)))),
+                Err(e) => Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, e)),
+            },
+            Source::Can => CanSource::open(&self.can_interface).map(|s| Box::new(s) as _),
+            Source::Serial => {
+                SerialSource::open(&self.serial_port, self.baud_rate).map(|s| Box::new(s) as _)
+            }
+            Source::Mirror => Ok(Box::new(MirrorSource::new(&self.mirror_address))),
+        };
+        match source {
+            Ok(source) => Some((address, source)),
+            Err(e) => {
+                self.error = Some(api::Error::Fetch(e.into()));
+                None
+            }
+        }
+    }
+
+    fn receive(&mut self, result: Result<Data, api::Error>, time: u128) {
+        match result {
+            Ok(d) => {
+                // an inconsistent sample is kept out of the display, the history and the recording
+                let issues = integrity::check(&self.integrity, &d);
+                if !issues.is_empty() {
+                    self.quarantine.add(time, issues);
+                    return;
+                }
+                if !self.spectator {
+                    self.mirror_server.publish(&self.mirror_config, &d, time);
+                }
+                self.process_data(&d, time);
+                match self.smoothing.frame_interval {
+                    0 => self.data = Some(d),
+                    _ => self.frames.push(d),
                 }
+                self.error = None;
             }
+            Err(e) => self.error = Some(e),
         }
     }
 }
 
-fn heatmap_color(ui: &Ui, avg: f32, cell: f32, delta: f32) -> Color32 {
-    if ui.style().visuals.dark_mode {
+fn heatmap_color(dark_mode: bool, avg: f32, cell: f32, delta: f32) -> Color32 {
+    if dark_mode {
         const BG: u8 = 0x20;
         const RANGE: f32 = (255 - BG) as f32;
         let diff = ((cell - avg) / (delta / 2.0)).clamp(-1.0, 1.0);
@@ -467,7 +2588,7 @@ fn heatmap_color(ui: &Ui, avg: f32, cell: f32, delta: f32) -> Color32 {
     }
 }
 
-fn now() -> u128 {
+pub fn now() -> u128 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap()
@@ -0,0 +1,57 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use eframe::App;
+
+use crate::app::DashboardApp;
+
+/// The background thread takes over if `update` wasn't called for this long
+const STALL: Duration = Duration::from_millis(500);
+const CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Runs the dashboard and keeps it polling, recording and sending the heartbeat while eframe
+/// doesn't call `update`. Depending on the platform a minimized or hidden window isn't redrawn,
+/// which would leave an unattended logger without data.
+pub struct Dashboard {
+    app: Arc<Mutex<DashboardApp>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Dashboard {
+    pub fn new(app: DashboardApp, ctx: egui::Context) -> Self {
+        let app = Arc::new(Mutex::new(app));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let (thread_app, thread_stop) = (app.clone(), stop.clone());
+        thread::spawn(move || {
+            while !thread_stop.load(Ordering::Relaxed) {
+                thread::sleep(CHECK_INTERVAL);
+                // a hung `update` keeps the lock, so the heartbeat stops as intended
+                let mut app = thread_app.lock().unwrap();
+                if app.since_tick() > STALL {
+                    app.tick(&ctx);
+                }
+            }
+        });
+
+        Self { app, stop }
+    }
+}
+
+impl App for Dashboard {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        self.app.lock().unwrap().save(storage);
+    }
+
+    fn on_exit(&mut self, gl: Option<&eframe::glow::Context>) {
+        // no new recording may be started after the last one was finished
+        self.stop.store(true, Ordering::Relaxed);
+        self.app.lock().unwrap().on_exit(gl);
+    }
+
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        self.app.lock().unwrap().update(ctx, frame);
+    }
+}
@@ -0,0 +1,230 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use egui::{DragValue, Grid, ScrollArea, Ui, Window};
+use serde::{Deserialize, Serialize};
+
+use crate::api::Data;
+use crate::app::now;
+use crate::i18n::tr;
+use crate::numbering::CellNumbering;
+use crate::soc::OcvConfig;
+use crate::soh::Cycle;
+use crate::units;
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct BalancingConfig {
+    /// Highest allowed difference between the lowest cell and any other cell in mV
+    pub target_delta: u16,
+    /// Capacity in Ah used for cells without an estimate from the state of health tracking
+    pub cell_capacity: f32,
+    /// Current in mA drawn by the bleed resistor
+    pub bleed_current: f32,
+}
+
+impl Default for BalancingConfig {
+    fn default() -> Self {
+        Self {
+            target_delta: 10,
+            cell_capacity: 13.0,
+            bleed_current: 100.0,
+        }
+    }
+}
+
+impl BalancingConfig {
+    pub fn settings(&mut self, ui: &mut Ui) {
+        Grid::new("balancing_config").show(ui, |ui| {
+            ui.label(tr("Target delta"));
+            ui.add(
+                DragValue::new(&mut self.target_delta)
+                    .clamp_range(1..=200)
+                    .suffix(" mV"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Cell capacity"));
+            ui.add(
+                DragValue::new(&mut self.cell_capacity)
+                    .clamp_range(0.1..=500.0)
+                    .speed(0.1)
+                    .suffix(" Ah"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Bleed current"));
+            ui.add(
+                DragValue::new(&mut self.bleed_current)
+                    .clamp_range(1.0..=5000.0)
+                    .suffix(" mA"),
+            );
+            ui.end_row();
+        });
+    }
+}
+
+/// A cell that has to be bled down to the target
+pub struct Bleed {
+    pub cell: usize,
+    pub voltage: u16,
+    /// Charge to remove in mAh
+    pub charge: f32,
+    /// Time in min at the bleed current
+    pub minutes: f32,
+}
+
+pub struct Recommendation {
+    pub bleeds: Vec<Bleed>,
+    /// The OCV curve only applies to a pack at rest
+    pub at_rest: bool,
+}
+
+/// Cells above the lowest cell plus the target delta with the charge to remove, estimated from the
+/// difference in state of charge on the OCV curve
+pub fn recommend(
+    config: &BalancingConfig,
+    ocv: &OcvConfig,
+    last_cycle: Option<&Cycle>,
+    data: &Data,
+) -> Recommendation {
+    Recommendation {
+        bleeds: bleeds(config, ocv, last_cycle, data),
+        at_rest: data.main.current.abs() <= ocv.rest_current,
+    }
+}
+
+fn bleeds(
+    config: &BalancingConfig,
+    ocv: &OcvConfig,
+    last_cycle: Option<&Cycle>,
+    data: &Data,
+) -> Vec<Bleed> {
+    let cells = &data.ucell.cell_voltage;
+    let Some(min) = cells.iter().copied().min() else {
+        return Vec::new();
+    };
+    let target = min.saturating_add(config.target_delta);
+    let Some(target_soc) = ocv.soc(target) else {
+        return Vec::new();
+    };
+
+    cells
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| **v > target)
+        .filter_map(|(cell, voltage)| {
+            let capacity = last_cycle
+                .filter(|c| c.capacity.len() == cells.len())
+                .map(|c| c.capacity[cell])
+                .filter(|c| *c > 0.0)
+                .unwrap_or(config.cell_capacity);
+            let soc = ocv.soc(*voltage)?;
+            let charge = (soc - target_soc) / 100.0 * capacity * 1000.0;
+            Some(Bleed {
+                cell,
+                voltage: *voltage,
+                charge,
+                minutes: charge / config.bleed_current * 60.0,
+            })
+        })
+        .collect()
+}
+
+#[derive(Default)]
+pub struct BalancingWindow {
+    pub show: bool,
+}
+
+impl BalancingWindow {
+    /// Returns a status message once the checklist has been exported
+    pub fn window(
+        &mut self,
+        ctx: &egui::Context,
+        recommendation: Option<&Recommendation>,
+        numbering: &CellNumbering,
+        directory: &Path,
+    ) -> Option<String> {
+        let mut result = None;
+        let mut show = self.show;
+        Window::new(tr("Balancing recommendation"))
+            .id("balancing".into())
+            .open(&mut show)
+            .default_size([450.0, 400.0])
+            .show(ctx, |ui| {
+                let Some(recommendation) = recommendation else {
+                    ui.label(tr("No data"));
+                    return;
+                };
+                if !recommendation.at_rest {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        tr("The pack is not at rest, the recommendation is inaccurate"),
+                    );
+                }
+                let bleeds = &recommendation.bleeds;
+                if bleeds.is_empty() {
+                    ui.label(tr("All cells are within the target delta"));
+                    return;
+                }
+
+                let total: f32 = bleeds.iter().map(|b| b.charge).sum();
+                let longest = bleeds.iter().map(|b| b.minutes).fold(0.0, f32::max);
+                ui.label(format!(
+                    "{} {}, {total:.0} mAh, {} {longest:.0} min",
+                    bleeds.len(),
+                    tr("cells to bleed"),
+                    tr("longest")
+                ));
+                if ui.button(tr("Export checklist")).clicked() {
+                    result = Some(export(bleeds, numbering, directory));
+                }
+
+                ScrollArea::vertical().show(ui, |ui| {
+                    Grid::new("balancing").striped(true).show(ui, |ui| {
+                        ui.strong(tr("Cell"));
+                        ui.strong(tr("Voltage"));
+                        ui.strong(tr("Bleed"));
+                        ui.strong(tr("Time"));
+                        ui.end_row();
+
+                        for bleed in bleeds {
+                            ui.label(numbering.label(bleed.cell));
+                            ui.label(units::voltage(bleed.voltage));
+                            ui.label(format!("{:.0} mAh", bleed.charge));
+                            ui.label(format!("{:.0} min", bleed.minutes));
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+        self.show = show;
+
+        match result? {
+            Ok(path) => Some(format!("{} {}", tr("Exported to"), path.display())),
+            Err(e) => Some(format!("{}: {e}", tr("Error exporting checklist"))),
+        }
+    }
+}
+
+/// Writes one checkbox line per cell to `balancing_<time>.txt`, for the mechanic doing the bleed
+fn export(bleeds: &[Bleed], numbering: &CellNumbering, directory: &Path) -> io::Result<PathBuf> {
+    fs::create_dir_all(directory)?;
+    let path = directory.join(format!("balancing_{}.txt", now()));
+    let mut file = BufWriter::new(File::create(&path)?);
+    for bleed in bleeds {
+        writeln!(
+            file,
+            "[ ] {} {}: {} {:.0} mAh ({:.0} min), {}",
+            tr("Cell"),
+            numbering.label(bleed.cell),
+            tr("bleed"),
+            bleed.charge,
+            bleed.minutes,
+            units::voltage(bleed.voltage)
+        )?;
+    }
+    file.flush()?;
+    Ok(path)
+}
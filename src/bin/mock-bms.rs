@@ -0,0 +1,277 @@
+//! Serves the pages of the BMS web interface with simulated data, so the dashboard can be
+//! developed and demoed without the car.
+//!
+//! `cargo run --bin mock-bms -- [--port 8080] [--scenario normal|cell-failure|sensor-dropout]`
+
+use std::env;
+use std::f32::consts::PI;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process;
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+const NUM_SLAVES: usize = 8;
+const CELLS_PER_SLAVE: usize = 18;
+const NUM_CELLS: usize = NUM_SLAVES * CELLS_PER_SLAVE;
+const NUM_TEMP_SENSORS: usize = 16;
+/// Capacity in As, small so a demo shows a visible state of charge change
+const CAPACITY: f32 = 3600.0 * 5.0;
+/// Internal resistance of a cell in Ω
+const CELL_RESISTANCE: f32 = 0.002;
+/// Cell that fails in the cell failure scenario
+const FAILING_CELL: usize = 41;
+/// Sensor that drops out in the sensor dropout scenario
+const DROPPING_SENSOR: usize = 5;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Scenario {
+    Normal,
+    /// One cell starts losing voltage after 30 s
+    CellFailure,
+    /// One temperature sensor intermittently reads 0
+    SensorDropout,
+}
+
+impl Scenario {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "normal" => Some(Scenario::Normal),
+            "cell-failure" => Some(Scenario::CellFailure),
+            "sensor-dropout" => Some(Scenario::SensorDropout),
+            _ => None,
+        }
+    }
+}
+
+struct Simulation {
+    scenario: Scenario,
+    start: Instant,
+}
+
+/// Values at one point in time
+struct State {
+    /// in mA, positive while discharging
+    current: f32,
+    // in %
+    state_of_charge: f32,
+    // in mV
+    cell_voltage: Vec<u16>,
+    // in °C
+    temp: Vec<f32>,
+}
+
+impl Simulation {
+    fn state(&self) -> State {
+        let t = self.start.elapsed().as_secs_f32();
+
+        // laps of 90 s with acceleration peaks and recuperation
+        let phase = (t % 90.0) / 90.0;
+        let current = 60_000.0 * (2.0 * PI * phase * 6.0).sin().max(-0.3) + 5_000.0;
+        // the average current is known, so the charge can be integrated analytically
+        let state_of_charge = (100.0 - 5.0 * t * 100.0 / CAPACITY).max(5.0);
+
+        let open_circuit = 3300.0 + 900.0 * state_of_charge / 100.0;
+        let sag = current / 1000.0 * CELL_RESISTANCE * 1000.0;
+        let cell_voltage = (0..NUM_CELLS)
+            .map(|i| {
+                // small static spread and noise, so the heatmap isn't flat
+                let spread = ((i * 7919) % 13) as f32 - 6.0;
+                let noise = ((t * 3.0 + i as f32).sin() * 2.0).round();
+                let mut v = open_circuit - sag + spread + noise;
+                if self.scenario == Scenario::CellFailure && i == FAILING_CELL && t > 30.0 {
+                    v -= ((t - 30.0) * 2.0).min(800.0);
+                }
+                v.max(0.0) as u16
+            })
+            .collect();
+
+        let temp = (0..NUM_TEMP_SENSORS)
+            .map(|i| {
+                // the left container is cooled worse
+                let offset = if i >= 8 { 3.0 } else { 0.0 };
+                let dropout = self.scenario == Scenario::SensorDropout
+                    && i == DROPPING_SENSOR
+                    && (t as u64 / 5).is_multiple_of(3);
+                match dropout {
+                    true => 0.0,
+                    false => 25.0 + offset + (t / 60.0).min(15.0) + (i % 3) as f32 * 0.5,
+                }
+            })
+            .collect();
+
+        State {
+            current,
+            state_of_charge,
+            cell_voltage,
+            temp,
+        }
+    }
+
+    fn page(&self, path: &str) -> Option<String> {
+        let state = self.state();
+        let body = match path {
+            "/main_data.shtml" => main_data(&state),
+            "/ucell.shtml" => ucell(&state),
+            "/tcell.shtml" => tcell(&state),
+            "/status.shtml" => "var Status = \"1500,1\";".to_string(),
+            "/settings.shtml" => format!(
+                "var Firmware = \"mock-bms {}\";\nvar Einstellungen = \"{NUM_SLAVES},{NUM_CELLS},{NUM_TEMP_SENSORS},200,30,3000,4200,600\";",
+                env!("CARGO_PKG_VERSION")
+            ),
+            _ => return None,
+        };
+        Some(format!("<html><script>\n{body}\n</script></html>"))
+    }
+}
+
+/// Every value is preceded by two unused fields, like on the real BMS
+fn main_data(state: &State) -> String {
+    let voltage: u32 = state.cell_voltage.iter().map(|v| *v as u32).sum();
+    let temp = &state.temp;
+    let alive = temp.iter().filter(|t| **t > 0.0);
+    let count = alive.clone().count().max(1) as f32;
+    let avg = alive.clone().sum::<f32>() / count;
+    let min = alive.clone().copied().fold(f32::MAX, f32::min);
+    let max = alive.copied().fold(f32::MIN, f32::max);
+    let values = [
+        voltage as i64,
+        state.current as i64,
+        (state.state_of_charge * 10.0) as i64,
+        (avg * 10.0) as i64,
+        (min * 10.0) as i64,
+        (max * 10.0) as i64,
+        350,
+        // AIR+, AIR-, precharge, shutdown circuit
+        1,
+        1,
+        0,
+        1,
+        voltage as i64 - 500,
+    ];
+    let fields: Vec<String> = values.iter().map(|v| format!("0,0,{v}")).collect();
+    // the voltage is only preceded by one field
+    format!("var Parametersatz = \"{}\";", &fields.join(",")[2..])
+}
+
+fn ucell(state: &State) -> String {
+    let cells: Vec<String> = state.cell_voltage.iter().map(u16::to_string).collect();
+    format!(
+        "var PSet0 = \"{NUM_SLAVES},{NUM_CELLS},{CELLS_PER_SLAVE},{NUM_TEMP_SENSORS},0\";\nvar PSet = \"0,0,{}\";",
+        cells.join(",")
+    )
+}
+
+fn tcell(state: &State) -> String {
+    let temps: Vec<String> = state
+        .temp
+        .iter()
+        .map(|t| ((t * 10.0) as u16).to_string())
+        .collect();
+    format!("var PSet = \"0,{}\";", temps.join(","))
+}
+
+fn handle(mut stream: TcpStream, simulation: &Simulation) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut content_length = 0;
+    let mut gzip = false;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            } else if name.eq_ignore_ascii_case("accept-encoding") {
+                gzip = value.contains("gzip");
+            }
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let path = parts.next().unwrap_or_default();
+    let (status, body) = match (method, path) {
+        ("POST", "/control.cgi") => {
+            let mut form = vec![0; content_length];
+            reader.read_exact(&mut form)?;
+            println!("command: {}", String::from_utf8_lossy(&form));
+            ("200 OK", "OK".to_string())
+        }
+        ("POST", "/update.cgi") => {
+            let mut image = vec![0; content_length];
+            reader.read_exact(&mut image)?;
+            println!("firmware upload: {} bytes", image.len());
+            ("200 OK", "OK".to_string())
+        }
+        ("GET", path) => match simulation.page(path) {
+            Some(page) => ("200 OK", page),
+            None => ("404 Not Found", "Not found".to_string()),
+        },
+        _ => ("405 Method Not Allowed", String::new()),
+    };
+    let (encoding, body) = match gzip {
+        true => {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(body.as_bytes())?;
+            ("Content-Encoding: gzip\r\n", encoder.finish()?)
+        }
+        false => ("", body.into_bytes()),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: text/html\r\n{encoding}Content-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    )?;
+    stream.write_all(&body)
+}
+
+fn main() {
+    let mut port = 8080;
+    let mut scenario = Scenario::Normal;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next();
+        match (arg.as_str(), value) {
+            ("--port", Some(p)) if p.parse::<u16>().is_ok() => port = p.parse().unwrap(),
+            ("--scenario", Some(s)) if Scenario::parse(&s).is_some() => {
+                scenario = Scenario::parse(&s).unwrap()
+            }
+            _ => {
+                eprintln!(
+                    "usage: mock-bms [--port 8080] [--scenario normal|cell-failure|sensor-dropout]"
+                );
+                process::exit(1);
+            }
+        }
+    }
+
+    let listener = match TcpListener::bind(("0.0.0.0", port)) {
+        Ok(l) => l,
+        Err(e) => {
+            eprintln!("failed to listen on port {port}: {e}");
+            process::exit(1);
+        }
+    };
+    println!("mock BMS listening on http://127.0.0.1:{port}");
+
+    let simulation = Arc::new(Simulation {
+        scenario,
+        start: Instant::now(),
+    });
+    for stream in listener.incoming().flatten() {
+        let simulation = simulation.clone();
+        thread::spawn(move || {
+            if let Err(e) = handle(stream, &simulation) {
+                eprintln!("{e}");
+            }
+        });
+    }
+}
@@ -0,0 +1,192 @@
+use std::io;
+use std::time::Instant;
+
+use crate::api::{self, Data, DataSource, PollOptions, Relays};
+use crate::stream::{Stream, Values};
+
+// message ids of the BMS broadcast as defined in the BMS DBC
+const MAIN_ID: u32 = 0x600;
+const TEMP_ID: u32 = 0x601;
+const CONFIG_ID: u32 = 0x602;
+/// Multiplexed, the first byte is the index of the first of three cells
+const CELL_VOLTAGE_ID: u32 = 0x610;
+/// Multiplexed, the first byte is the index of the first of three sensors
+const CELL_TEMP_ID: u32 = 0x620;
+
+/// Reads the BMS broadcast from a socketcan interface in the background
+pub struct CanSource {
+    stream: Stream,
+}
+
+impl CanSource {
+    pub fn open(interface: &str) -> io::Result<Self> {
+        let socket = open_socket(interface)?;
+        let stream = Stream::spawn(move |values| {
+            if let Some((id, data)) = read_frame(&socket)? {
+                let mut values = values.lock().unwrap();
+                decode(&mut values, id, &data);
+                values.received = Some(Instant::now());
+            }
+            Ok(())
+        });
+        Ok(Self { stream })
+    }
+}
+
+impl DataSource for CanSource {
+    fn poll(&mut self, options: &PollOptions) -> Result<Data, api::Error> {
+        self.stream.data(options.safe, &options.dead_sensors)
+    }
+
+    fn is_alive(&self) -> bool {
+        self.stream.is_alive()
+    }
+}
+
+fn decode(values: &mut Values, id: u32, data: &[u8]) {
+    let u16_at = |i: usize| u16::from_le_bytes([data[i], data[i + 1]]);
+    let i16_at = |i: usize| i16::from_le_bytes([data[i], data[i + 1]]);
+    match (id, data.len()) {
+        // voltage in 0.1 V, current in 0.1 A, state of charge in 0.1 %, relay bits
+        (MAIN_ID, 8) => {
+            values.main.voltage = u16_at(0) as f32 / 10.0;
+            values.main.current = i16_at(2) as f32 * 100.0;
+            values.main.state_of_charge = u16_at(4) as f32 / 10.0;
+            let relays = data[6];
+            values.main.relays = Some(Relays {
+                air_plus: relays & 0x01 != 0,
+                air_minus: relays & 0x02 != 0,
+                precharge: relays & 0x04 != 0,
+                shutdown_circuit: relays & 0x08 != 0,
+            });
+        }
+        // average, minimum, maximum and master temperature in 0.1 °C
+        (TEMP_ID, 8) => {
+            values.main.temp_avg = i16_at(0) as f32 / 10.0;
+            values.main.temp_min = i16_at(2) as f32 / 10.0;
+            values.main.temp_max = i16_at(4) as f32 / 10.0;
+            values.main.temp_master = i16_at(6) as f32 / 10.0;
+        }
+        (CONFIG_ID, 6) => {
+            values.num_slaves = data[0] as usize;
+            values.num_cells_per_slave = data[1] as usize;
+            values.num_cells = u16_at(2) as usize;
+            values.num_temp_sensors = u16_at(4) as usize;
+        }
+        (CELL_VOLTAGE_ID, 7) => {
+            let first = data[0] as usize * 3;
+            for i in 0..3 {
+                values.cell_voltage.insert(first + i, u16_at(1 + i * 2));
+            }
+        }
+        (CELL_TEMP_ID, 7) => {
+            let first = data[0] as usize * 3;
+            for i in 0..3 {
+                values
+                    .temp
+                    .insert(first + i, i16_at(1 + i * 2) as f32 / 10.0);
+            }
+        }
+        _ => (),
+    }
+}
+
+#[cfg(target_os = "linux")]
+use socket::{open_socket, read_frame};
+
+#[cfg(not(target_os = "linux"))]
+fn open_socket(_interface: &str) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "socketcan is only available on Linux",
+    ))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_frame(_socket: &()) -> io::Result<Option<(u32, Vec<u8>)>> {
+    Ok(None)
+}
+
+#[cfg(target_os = "linux")]
+mod socket {
+    use std::ffi::CString;
+    use std::io;
+    use std::mem;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+    pub fn open_socket(interface: &str) -> io::Result<OwnedFd> {
+        let name =
+            CString::new(interface).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        unsafe {
+            let index = libc::if_nametoindex(name.as_ptr());
+            if index == 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            let fd = libc::socket(libc::PF_CAN, libc::SOCK_RAW, libc::CAN_RAW);
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let socket = OwnedFd::from_raw_fd(fd);
+
+            // wake up regularly to notice when the source is dropped
+            let timeout = libc::timeval {
+                tv_sec: 0,
+                tv_usec: 200_000,
+            };
+            let res = libc::setsockopt(
+                fd,
+                libc::SOL_SOCKET,
+                libc::SO_RCVTIMEO,
+                &timeout as *const _ as *const libc::c_void,
+                mem::size_of::<libc::timeval>() as libc::socklen_t,
+            );
+            if res < 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            let mut addr: libc::sockaddr_can = mem::zeroed();
+            addr.can_family = libc::AF_CAN as libc::sa_family_t;
+            addr.can_ifindex = index as libc::c_int;
+            let res = libc::bind(
+                fd,
+                &addr as *const _ as *const libc::sockaddr,
+                mem::size_of::<libc::sockaddr_can>() as libc::socklen_t,
+            );
+            if res < 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            Ok(socket)
+        }
+    }
+
+    /// Returns `None` if no frame was received before the timeout
+    pub fn read_frame(socket: &OwnedFd) -> io::Result<Option<(u32, Vec<u8>)>> {
+        unsafe {
+            let mut frame: libc::can_frame = mem::zeroed();
+            let size = mem::size_of::<libc::can_frame>();
+            let read = libc::read(
+                socket.as_raw_fd(),
+                &mut frame as *mut _ as *mut libc::c_void,
+                size,
+            );
+            if read < 0 {
+                let error = io::Error::last_os_error();
+                return match error.kind() {
+                    io::ErrorKind::WouldBlock
+                    | io::ErrorKind::TimedOut
+                    | io::ErrorKind::Interrupted => Ok(None),
+                    _ => Err(error),
+                };
+            }
+            if read as usize != size {
+                return Ok(None);
+            }
+
+            let id = frame.can_id & libc::CAN_EFF_MASK;
+            let len = (frame.can_dlc as usize).min(frame.data.len());
+            Ok(Some((id, frame.data[..len].to_vec())))
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use egui::Window;
+use egui_plot::{Legend, Line, Plot, PlotPoints};
+
+use crate::history::History;
+use crate::i18n::tr;
+use crate::inventory::CellInventory;
+use crate::recording;
+use crate::temp_map::TempMapping;
+use crate::units;
+
+/// Popup with the history of a single cell, opened by clicking it in the pack view
+#[derive(Default)]
+pub struct CellDetail {
+    pub cell: Option<usize>,
+}
+
+impl CellDetail {
+    /// Returns a status message after exporting. The export reads the recordings of the session
+    /// in `directory`, or the replayed recording.
+    pub fn window(
+        &mut self,
+        ctx: &egui::Context,
+        history: &History,
+        temp_mapping: &TempMapping,
+        inventory: &CellInventory,
+        directory: &Path,
+        replay: Option<&Path>,
+    ) -> Option<String> {
+        let index = self.cell?;
+        let sensor = temp_mapping.sensor(index);
+
+        let start = history.first().map_or(0, |s| s.time);
+        let minutes = |time: u128| time.saturating_sub(start) as f64 / 60_000.0;
+        let voltage: PlotPoints = history
+            .samples()
+            .filter_map(|s| {
+                Some([
+                    minutes(s.time),
+                    units::voltage_scale(*s.cell_voltage.get(index)? as f64),
+                ])
+            })
+            .collect();
+        let temp: Option<PlotPoints> = sensor.map(|sensor| {
+            history
+                .samples()
+                .filter_map(|s| {
+                    Some([
+                        minutes(s.time),
+                        units::temp_scale(*s.temp.get(sensor)? as f64),
+                    ])
+                })
+                .collect()
+        });
+
+        let mut result = None;
+        let mut open = true;
+        Window::new(format!("{} {}", tr("Cell"), inventory.label(index)))
+            .id("cell_detail".into())
+            .open(&mut open)
+            .default_size([450.0, 400.0])
+            .show(ctx, |ui| {
+                if let Some(cell) = inventory.cells.get(index).filter(|c| !c.serial.is_empty()) {
+                    ui.label(format!(
+                        "{}: {}, {}: {}, {}: {}",
+                        tr("Serial number"),
+                        cell.serial,
+                        tr("Module"),
+                        cell.module,
+                        tr("Position"),
+                        cell.position
+                    ));
+                }
+                if let Some(sensor) = sensor {
+                    ui.label(format!("{}: {}", tr("Temperature sensor"), sensor + 1));
+                }
+                if ui.button(tr("Export CSV")).clicked() {
+                    let recordings = match replay {
+                        Some(path) => vec![path.to_path_buf()],
+                        None => recording::recordings(directory),
+                    };
+                    result = Some(
+                        export(&recordings, index, sensor, directory).and_then(|path| match path {
+                            Some(path) => Ok((path, false)),
+                            None => export_history(history, index, sensor, directory),
+                        }),
+                    );
+                }
+
+                let height = match temp {
+                    Some(_) => ui.available_height() / 2.0 - 4.0,
+                    None => ui.available_height(),
+                };
+                Plot::new("cell_detail_voltage")
+                    .legend(Legend::default())
+                    .height(height.max(80.0))
+                    .x_axis_label("min")
+                    .y_axis_label(units::voltage_unit())
+                    .show(ui, |plot| {
+                        plot.line(Line::new(voltage).name(tr("Voltage")));
+                    });
+                if let Some(temp) = temp {
+                    Plot::new("cell_detail_temp")
+                        .legend(Legend::default())
+                        .height(height.max(80.0))
+                        .x_axis_label("min")
+                        .y_axis_label(units::temp_unit())
+                        .show(ui, |plot| {
+                            plot.line(Line::new(temp).name(tr("Temperature")));
+                        });
+                }
+            });
+        if !open {
+            self.cell = None;
+        }
+
+        match result? {
+            Ok((path, false)) => Some(format!("{} {}", tr("Exported to"), path.display())),
+            Ok((path, true)) => Some(format!(
+                "{} {}",
+                tr("No recording of the cell, exported the downsampled in-memory history to"),
+                path.display()
+            )),
+            Err(e) => Some(format!("{}: {e}", tr("Error exporting cell"))),
+        }
+    }
+}
+
+/// Writes the full trace of one cell in the recordings to `cell_<n>_<time>.csv`, nothing is
+/// written if the recordings don't contain the cell
+fn export(
+    recordings: &[PathBuf],
+    index: usize,
+    sensor: Option<usize>,
+    directory: &Path,
+) -> io::Result<Option<PathBuf>> {
+    let voltage_column = format!("cell_{}", index + 1);
+    let temp_column = sensor.map(|s| format!("temp_{}", s + 1));
+    let mut rows = Vec::new();
+    for path in recordings {
+        let text = recording::read(path)?;
+        let mut lines = text.lines();
+        let header: Vec<&str> = lines.next().unwrap_or_default().split(',').collect();
+        let column = |name: &str| header.iter().position(|c| *c == name);
+        // the number of cells can differ between the recordings of a session
+        let Some(voltage) = column(&voltage_column) else {
+            continue;
+        };
+        let temp = temp_column.as_deref().and_then(column);
+        for line in lines {
+            let values: Vec<&str> = line.split(',').collect();
+            let value = |i: usize| values.get(i).copied().unwrap_or_default().to_string();
+            rows.push((
+                value(0),
+                value(voltage),
+                temp.map(value).unwrap_or_default(),
+            ));
+        }
+    }
+
+    if rows.is_empty() {
+        return Ok(None);
+    }
+
+    fs::create_dir_all(directory)?;
+    let time = rows.last().map_or("0", |(time, _, _)| time.as_str());
+    let path = directory.join(format!("cell_{}_{time}.csv", index + 1));
+    let mut file = BufWriter::new(File::create(&path)?);
+    writeln!(file, "time,voltage,temp")?;
+    for (time, voltage, temp) in &rows {
+        writeln!(file, "{time},{voltage},{temp}")?;
+    }
+    file.flush()?;
+    Ok(Some(path))
+}
+
+/// Writes the in-memory history of one cell to `cell_<n>_<time>_history.csv`, the history is
+/// downsampled beyond the recent samples and limited in size. Returns the path and `true`.
+fn export_history(
+    history: &History,
+    index: usize,
+    sensor: Option<usize>,
+    directory: &Path,
+) -> io::Result<(PathBuf, bool)> {
+    fs::create_dir_all(directory)?;
+    let time = history.last().map_or(0, |s| s.time);
+    let path = directory.join(format!("cell_{}_{time}_history.csv", index + 1));
+    let mut file = BufWriter::new(File::create(&path)?);
+
+    writeln!(file, "time,voltage,temp")?;
+    for sample in history.samples() {
+        let Some(voltage) = sample.cell_voltage.get(index) else {
+            continue;
+        };
+        write!(file, "{},{voltage},", sample.time)?;
+        if let Some(temp) = sensor.and_then(|s| sample.temp.get(s)) {
+            write!(file, "{temp}")?;
+        }
+        writeln!(file)?;
+    }
+    file.flush()?;
+    Ok((path, true))
+}
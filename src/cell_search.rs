@@ -0,0 +1,93 @@
+use egui::{Align2, Key, Painter, Rect, Rounding, Stroke, TextEdit, Window};
+
+use crate::i18n::tr;
+use crate::numbering::CellNumbering;
+
+/// Time in s the found tile flashes after the search is closed
+const FLASH_DURATION: f64 = 5.0;
+
+/// Search box finding a cell tile by the label shown on it
+#[derive(Default)]
+pub struct CellSearch {
+    pub open: bool,
+    query: String,
+    /// Electrical index of the found cell and the input time in s it was found
+    found: Option<(usize, f64)>,
+}
+
+impl CellSearch {
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.query.clear();
+        self.found = None;
+    }
+
+    /// Shows the search box if it's open, returns whether a found cell was confirmed with enter
+    pub fn show(&mut self, ctx: &egui::Context, numbering: &CellNumbering, cells: usize) -> bool {
+        if !self.open {
+            return false;
+        }
+
+        let (enter, escape, time) = ctx.input(|i| {
+            (
+                i.key_pressed(Key::Enter),
+                i.key_pressed(Key::Escape),
+                i.time,
+            )
+        });
+        let mut changed = false;
+        Window::new(tr("Find cell"))
+            .id("cell_search".into())
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_TOP, [0.0, 60.0])
+            .show(ctx, |ui| {
+                let edit = ui.add(
+                    TextEdit::singleline(&mut self.query)
+                        .hint_text(tr("Cell number"))
+                        .desired_width(200.0),
+                );
+                edit.request_focus();
+                changed = edit.changed();
+                if !self.query.trim().is_empty() && self.found.is_none() {
+                    ui.colored_label(ui.visuals().warn_fg_color, tr("No such cell"));
+                }
+            });
+
+        if changed {
+            // the slash that opened the search is typed into the box in the same frame
+            self.query.retain(|c| c != '/');
+            let query = self.query.trim();
+            self.found = (0..cells)
+                .find(|i| numbering.label(*i).eq_ignore_ascii_case(query))
+                .map(|i| (i, time));
+        }
+        let confirmed = enter && self.found.is_some();
+        if let (true, Some((_, since))) = (confirmed, &mut self.found) {
+            *since = time;
+        }
+        if enter || escape {
+            self.open = false;
+        }
+        if escape {
+            self.found = None;
+        }
+        confirmed
+    }
+
+    /// Outlines the found cell, flashing while the search is open and shortly after
+    pub fn highlight(&self, painter: &Painter, rect: Rect, cell_index: usize, time: f64) {
+        let Some((index, since)) = self.found else {
+            return;
+        };
+        let elapsed = time - since;
+        if index != cell_index || (!self.open && elapsed > FLASH_DURATION) {
+            return;
+        }
+        if (elapsed * 2.0).fract() < 0.5 {
+            let color = painter.ctx().style().visuals.strong_text_color();
+            painter.rect_stroke(rect.shrink(2.0), Rounding::ZERO, Stroke::new(4.0, color));
+        }
+    }
+}
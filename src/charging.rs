@@ -0,0 +1,169 @@
+use egui::{Color32, DragValue, Grid, RichText, Ui};
+use egui_plot::{Legend, Line, Plot, PlotPoints};
+use serde::{Deserialize, Serialize};
+
+use crate::api::Data;
+use crate::history::{slope, History, Sample};
+use crate::i18n::tr;
+use crate::markers::Markers;
+use crate::units;
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChargingConfig {
+    /// Suggest the charging view when the current is below this value in mA
+    pub charge_current: f32,
+    /// Cell voltage in mV at which the pack is considered full
+    pub full_voltage: u16,
+    /// Cells more than this many mV above the lowest cell are considered to be balanced
+    pub balance_threshold: u16,
+    /// Window in s used for the trends
+    pub window: u64,
+}
+
+impl Default for ChargingConfig {
+    fn default() -> Self {
+        Self {
+            charge_current: -500.0,
+            full_voltage: 4200,
+            balance_threshold: 10,
+            window: 300,
+        }
+    }
+}
+
+impl ChargingConfig {
+    pub fn is_charging(&self, data: &Data) -> bool {
+        data.main.current < self.charge_current
+    }
+
+    pub fn settings(&mut self, ui: &mut Ui) {
+        Grid::new("charging_config").show(ui, |ui| {
+            ui.label(tr("Charge current"));
+            ui.add(
+                DragValue::new(&mut self.charge_current)
+                    .clamp_range(-100000.0..=0.0)
+                    .suffix(" mA"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Full cell voltage"));
+            ui.add(
+                DragValue::new(&mut self.full_voltage)
+                    .clamp_range(3000..=4500)
+                    .suffix(" mV"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Balancing threshold"));
+            ui.add(
+                DragValue::new(&mut self.balance_threshold)
+                    .clamp_range(1..=500)
+                    .suffix(" mV"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Trend window"));
+            ui.add(
+                DragValue::new(&mut self.window)
+                    .clamp_range(30..=3600)
+                    .suffix(" s"),
+            );
+            ui.end_row();
+        });
+    }
+}
+
+pub fn charging_view(
+    ui: &mut Ui,
+    config: &ChargingConfig,
+    data: &Data,
+    history: &History,
+    markers: &Markers,
+) {
+    let samples: Vec<_> = history.window(config.window as u128 * 1000).collect();
+    let start = samples.first().map_or(0, |s| s.time);
+    let minutes = |s: &&Sample| (s.time - start) as f64 / 60_000.0;
+
+    // trends in mV/min
+    let delta_slope = slope(
+        samples
+            .iter()
+            .map(|s| (minutes(s), (s.max_voltage - s.min_voltage) as f64)),
+    );
+    let max_slope = slope(samples.iter().map(|s| (minutes(s), s.max_voltage as f64)));
+
+    let ucell = &data.ucell;
+    let time_to_full = max_slope
+        .filter(|s| *s > 0.0)
+        .map(|s| (config.full_voltage as f64 - ucell.overall.max_voltage as f64).max(0.0) / s);
+    let time_to_balance = delta_slope
+        .filter(|s| *s < 0.0)
+        .map(|s| ucell.overall.delta_voltage as f64 / -s);
+    let format_minutes = |m: Option<f64>| match m {
+        Some(m) => format!("{m:.0} min"),
+        None => "-".into(),
+    };
+
+    Grid::new("charging_stats").show(ui, |ui| {
+        ui.label(tr("Charge current"));
+        ui.strong(format!("{:.1} A", -data.main.current / 1000.0));
+        ui.end_row();
+
+        ui.label(tr("Delta cell voltage"));
+        ui.strong(units::voltage(ucell.overall.delta_voltage));
+        ui.end_row();
+
+        ui.label(tr("Delta trend"));
+        ui.strong(match delta_slope {
+            Some(s) => units::voltage_trend(s),
+            None => "-".into(),
+        });
+        ui.end_row();
+
+        ui.label(tr("Estimated time to full"));
+        ui.strong(format_minutes(time_to_full));
+        ui.end_row();
+
+        ui.label(tr("Estimated time to balance"));
+        ui.strong(format_minutes(time_to_balance));
+        ui.end_row();
+    });
+
+    ui.separator();
+    ui.label(tr("Balancing activity (estimated from the cell voltages)"));
+    let threshold = ucell.overall.min_voltage + config.balance_threshold;
+    ui.horizontal_wrapped(|ui| {
+        for (i, v) in ucell.cell_voltage.iter().enumerate() {
+            let balancing = *v > threshold;
+            let text = RichText::new(format!("{:>3}", i + 1)).monospace();
+            if balancing {
+                ui.label(text.background_color(Color32::from_rgb(0x00, 0x80, 0xc0)))
+                    .on_hover_text(units::voltage(*v));
+            } else {
+                ui.label(text.weak()).on_hover_text(units::voltage(*v));
+            }
+        }
+    });
+
+    ui.separator();
+    let delta: PlotPoints = samples
+        .iter()
+        .map(|s| {
+            [
+                minutes(s),
+                units::voltage_scale((s.max_voltage - s.min_voltage) as f64),
+            ]
+        })
+        .collect();
+    Plot::new("charging_delta")
+        .legend(Legend::default())
+        .x_axis_label("min")
+        .y_axis_label(units::voltage_unit())
+        .show(ui, |plot| {
+            plot.line(Line::new(delta).name(tr("Delta cell voltage")));
+            markers.plot(plot, |t| {
+                (t >= start).then(|| (t - start) as f64 / 60_000.0)
+            });
+        });
+}
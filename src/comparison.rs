@@ -0,0 +1,116 @@
+use egui::{DragValue, Grid, Ui};
+use egui_plot::{Legend, Line, Plot, PlotPoints};
+
+use crate::api::Data;
+use crate::history::{History, Sample, Side};
+use crate::i18n::tr;
+use crate::markers::Markers;
+use crate::units;
+
+/// Plots the left and right container against each other to spot asymmetric cooling early
+pub fn comparison_view(
+    ui: &mut Ui,
+    data: &Data,
+    history: &History,
+    markers: &Markers,
+    window: &mut u64,
+) {
+    let (ucell, tcell) = (&data.ucell, &data.tcell);
+    Grid::new("comparison_stats").striped(true).show(ui, |ui| {
+        ui.label("");
+        ui.strong(tr("Left"));
+        ui.strong(tr("Right"));
+        ui.strong(tr("Difference"));
+        ui.end_row();
+
+        ui.label(tr("Avg cell voltage"));
+        ui.label(units::voltage(ucell.left.avg_voltage));
+        ui.label(units::voltage(ucell.right.avg_voltage));
+        ui.strong(units::voltage_diff(
+            ucell.left.avg_voltage as i32 - ucell.right.avg_voltage as i32,
+        ));
+        ui.end_row();
+
+        ui.label(tr("Delta cell voltage"));
+        ui.label(units::voltage(ucell.left.delta_voltage));
+        ui.label(units::voltage(ucell.right.delta_voltage));
+        ui.strong(units::voltage_diff(
+            ucell.left.delta_voltage as i32 - ucell.right.delta_voltage as i32,
+        ));
+        ui.end_row();
+
+        ui.label(tr("Avg temperature"));
+        ui.label(units::temp(tcell.left.avg_temp));
+        ui.label(units::temp(tcell.right.avg_temp));
+        ui.strong(units::temp_diff(tcell.left.avg_temp - tcell.right.avg_temp));
+        ui.end_row();
+
+        ui.label(tr("Max temperature"));
+        ui.label(units::temp(tcell.left.max_temp));
+        ui.label(units::temp(tcell.right.max_temp));
+        ui.strong(units::temp_diff(tcell.left.max_temp - tcell.right.max_temp));
+        ui.end_row();
+
+        ui.label(tr("Window"));
+        ui.add(DragValue::new(window).clamp_range(30..=3600).suffix(" s"));
+        ui.end_row();
+    });
+
+    let samples: Vec<_> = history.window(*window as u128 * 1000).collect();
+    let start = samples.first().map_or(0, |s| s.time);
+    let minutes = |s: &Sample| (s.time - start) as f64 / 60_000.0;
+    let line = |side: fn(&Sample) -> &Side, value: fn(&Side) -> f64| -> PlotPoints {
+        samples
+            .iter()
+            .map(|s| [minutes(s), value(side(s))])
+            .collect()
+    };
+    let marker_x = |t: u128| (t >= start).then(|| (t - start) as f64 / 60_000.0);
+
+    ui.separator();
+    let height = (ui.available_height() / 3.0 - 8.0).max(80.0);
+    Plot::new("comparison_voltage")
+        .legend(Legend::default())
+        .height(height)
+        .x_axis_label("min")
+        .y_axis_label(units::voltage_unit())
+        .show(ui, |plot| {
+            let avg = |s: &Side| units::voltage_scale(s.avg_voltage as f64);
+            plot.line(Line::new(line(left, avg)).name(tr("Left avg")));
+            plot.line(Line::new(line(right, avg)).name(tr("Right avg")));
+            markers.plot(plot, marker_x);
+        });
+    Plot::new("comparison_delta")
+        .legend(Legend::default())
+        .height(height)
+        .x_axis_label("min")
+        .y_axis_label(units::voltage_unit())
+        .show(ui, |plot| {
+            let delta = |s: &Side| units::voltage_scale(s.delta_voltage as f64);
+            plot.line(Line::new(line(left, delta)).name(tr("Left delta")));
+            plot.line(Line::new(line(right, delta)).name(tr("Right delta")));
+            markers.plot(plot, marker_x);
+        });
+    Plot::new("comparison_temp")
+        .legend(Legend::default())
+        .height(height)
+        .x_axis_label("min")
+        .y_axis_label(units::temp_unit())
+        .show(ui, |plot| {
+            let avg = |s: &Side| units::temp_scale(s.avg_temp as f64);
+            let max = |s: &Side| units::temp_scale(s.max_temp as f64);
+            plot.line(Line::new(line(left, avg)).name(tr("Left avg")));
+            plot.line(Line::new(line(right, avg)).name(tr("Right avg")));
+            plot.line(Line::new(line(left, max)).name(tr("Left max")));
+            plot.line(Line::new(line(right, max)).name(tr("Right max")));
+            markers.plot(plot, marker_x);
+        });
+}
+
+fn left(sample: &Sample) -> &Side {
+    &sample.left
+}
+
+fn right(sample: &Sample) -> &Side {
+    &sample.right
+}
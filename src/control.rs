@@ -0,0 +1,118 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::thread::JoinHandle;
+
+use egui::{Align2, Color32, RichText, Window};
+
+use crate::api::{send_command, Command, Connection};
+use crate::app::now;
+use crate::i18n::tr;
+
+const COMMAND_LOG_FILE: &str = "commands.log";
+
+impl Command {
+    fn name(self) -> &'static str {
+        match self {
+            Command::EnableBalancing => tr("Enable balancing"),
+            Command::DisableBalancing => tr("Disable balancing"),
+            Command::ResetErrors => tr("Reset latched errors"),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct ControlPanel {
+    pub show: bool,
+    unlocked: bool,
+    confirm: Option<Command>,
+    request: Option<(Command, JoinHandle<anyhow::Result<()>>)>,
+}
+
+impl ControlPanel {
+    /// Returns a status message once a command has been sent
+    pub fn window(
+        &mut self,
+        ctx: &egui::Context,
+        connection: &Connection,
+        log_directory: &Path,
+    ) -> Option<String> {
+        let mut status = None;
+        if self.request.as_ref().is_some_and(|(_, r)| r.is_finished()) {
+            let (command, request) = self.request.take().unwrap();
+            let result = match request.join() {
+                Ok(Ok(())) => tr("sent").to_string(),
+                Ok(Err(e)) => format!("{}: {e}", tr("failed")),
+                Err(_) => tr("Unexpected error").to_string(),
+            };
+            let message = format!("{} {result}", command.name());
+            status = Some(match log(log_directory, &connection.ip, &message) {
+                Ok(()) => message,
+                Err(e) => format!("{message}, {}: {e}", tr("Error logging command")),
+            });
+        }
+
+        if !self.show {
+            self.unlocked = false;
+        }
+        let mut show = self.show;
+        Window::new(tr("Control"))
+            .id("control".into())
+            .open(&mut show)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.checkbox(&mut self.unlocked, tr("Unlock commands"));
+                ui.add_enabled_ui(self.unlocked && self.request.is_none(), |ui| {
+                    for command in [
+                        Command::EnableBalancing,
+                        Command::DisableBalancing,
+                        Command::ResetErrors,
+                    ] {
+                        if ui.button(command.name()).clicked() {
+                            self.confirm = Some(command);
+                        }
+                    }
+                });
+                if self.request.is_some() {
+                    ui.spinner();
+                }
+            });
+        self.show = show;
+
+        if let Some(command) = self.confirm {
+            Window::new(tr("Confirm command"))
+                .id("control_confirm".into())
+                .collapsible(false)
+                .resizable(false)
+                .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(
+                        RichText::new(format!("{} \"{}\"?", tr("Send command"), command.name()))
+                            .color(Color32::RED)
+                            .strong(),
+                    );
+                    ui.horizontal(|ui| {
+                        if ui.button(tr("Send")).clicked() {
+                            self.request = Some((command, send_command(connection, command)));
+                            self.confirm = None;
+                        }
+                        if ui.button(tr("cancel")).clicked() {
+                            self.confirm = None;
+                        }
+                    });
+                });
+        }
+
+        status
+    }
+}
+
+/// Appends every sent command to the command log in the log directory
+pub fn log(directory: &Path, ip: &str, message: &str) -> io::Result<()> {
+    fs::create_dir_all(directory)?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(directory.join(COMMAND_LOG_FILE))?;
+    writeln!(file, "{},{ip},{message}", now())
+}
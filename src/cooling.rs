@@ -0,0 +1,175 @@
+use egui::{Color32, DragValue, Grid, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::history::{slope, History, Sample};
+use crate::i18n::tr;
+use crate::temp_map::TempMapping;
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct CoolingConfig {
+    pub enabled: bool,
+    /// Window in s over which the temperature rise is correlated with the current
+    pub window: u64,
+    /// Length in s of the intervals the temperature rise rate is computed over
+    pub interval: u64,
+    /// Stacks heating up this many % faster than the median stack at the same current are
+    /// degraded, twice as many % is poor
+    pub margin: f32,
+}
+
+impl Default for CoolingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window: 1800,
+            interval: 30,
+            margin: 30.0,
+        }
+    }
+}
+
+impl CoolingConfig {
+    pub fn settings(&mut self, ui: &mut Ui) {
+        ui.checkbox(&mut self.enabled, tr("Monitor cooling effectiveness"));
+        Grid::new("cooling_config").show(ui, |ui| {
+            ui.label(tr("Window"));
+            ui.add(
+                DragValue::new(&mut self.window)
+                    .clamp_range(300..=7200)
+                    .suffix(" s"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Interval"));
+            ui.add(
+                DragValue::new(&mut self.interval)
+                    .clamp_range(5..=300)
+                    .suffix(" s"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Margin"));
+            ui.add(
+                DragValue::new(&mut self.margin)
+                    .clamp_range(5.0..=200.0)
+                    .suffix(" %"),
+            );
+            ui.end_row();
+        });
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum CoolingHealth {
+    Good,
+    Degraded,
+    Poor,
+}
+
+impl CoolingHealth {
+    pub fn name(self) -> &'static str {
+        match self {
+            CoolingHealth::Good => tr("Good"),
+            CoolingHealth::Degraded => tr("Degraded"),
+            CoolingHealth::Poor => tr("Poor"),
+        }
+    }
+
+    pub fn color(self) -> Color32 {
+        match self {
+            CoolingHealth::Good => Color32::GREEN,
+            CoolingHealth::Degraded => Color32::YELLOW,
+            CoolingHealth::Poor => Color32::RED,
+        }
+    }
+}
+
+pub struct StackCooling {
+    /// Temperature rise rate in °C/min per 1000 A² of current squared, the heat generated in the
+    /// cells grows with the square of the current
+    pub sensitivity: f64,
+    /// Sensitivity relative to the median stack
+    pub ratio: f64,
+    pub health: CoolingHealth,
+}
+
+/// Correlates the temperature rise of every stack with the current, `None` for stacks without
+/// enough data. A stack heating up much faster than the others at the same current points to a
+/// clogged duct or a failed fan.
+pub fn cooling_health(
+    config: &CoolingConfig,
+    history: &History,
+    temp_mapping: &TempMapping,
+    stacks: usize,
+) -> Vec<Option<StackCooling>> {
+    if !config.enabled {
+        return Vec::new();
+    }
+    let samples: Vec<&Sample> = history.window(config.window as u128 * 1000).collect();
+    let Some(first) = samples.first() else {
+        return Vec::new();
+    };
+    let sensors = first.temp.len() / stacks.max(1);
+    let stack_temp = |sample: &Sample, stack: usize| -> Option<f64> {
+        let temps: Vec<f32> = (stack * sensors..(stack + 1) * sensors)
+            .filter(|i| !temp_mapping.is_dead(*i))
+            .filter_map(|i| sample.temp.get(i).copied())
+            .collect();
+        (!temps.is_empty()).then(|| temps.iter().sum::<f32>() as f64 / temps.len() as f64)
+    };
+
+    // split the window into intervals, the rise rate of single samples is mostly quantization noise
+    let interval = config.interval as u128 * 1000;
+    let mut bins: Vec<&[&Sample]> = Vec::new();
+    let mut start = 0;
+    for i in 1..samples.len() {
+        if samples[i].time - samples[start].time >= interval {
+            bins.push(&samples[start..=i]);
+            start = i;
+        }
+    }
+
+    let sensitivity: Vec<Option<f64>> = (0..stacks)
+        .map(|stack| {
+            let points = bins.iter().filter_map(|bin| {
+                let (first, last) = (bin.first()?, bin.last()?);
+                let minutes = (last.time - first.time) as f64 / 60_000.0;
+                let rate = (stack_temp(last, stack)? - stack_temp(first, stack)?) / minutes;
+                let current_squared = bin
+                    .iter()
+                    .map(|s| (s.current as f64 / 1000.0).powi(2))
+                    .sum::<f64>()
+                    / bin.len() as f64;
+                Some((current_squared / 1000.0, rate))
+            });
+            slope(points)
+        })
+        .collect();
+
+    let mut sorted: Vec<f64> = sensitivity.iter().flatten().copied().collect();
+    sorted.sort_by(f64::total_cmp);
+    let Some(median) = sorted.get(sorted.len() / 2).copied().filter(|m| *m > 0.0) else {
+        return sensitivity.iter().map(|_| None).collect();
+    };
+    let margin = config.margin as f64 / 100.0;
+    sensitivity
+        .into_iter()
+        .map(|sensitivity| {
+            let sensitivity = sensitivity?;
+            let ratio = sensitivity / median;
+            let health = if ratio <= 1.0 + margin {
+                CoolingHealth::Good
+            } else if ratio <= 1.0 + 2.0 * margin {
+                CoolingHealth::Degraded
+            } else {
+                CoolingHealth::Poor
+            };
+            Some(StackCooling {
+                sensitivity,
+                ratio,
+                health,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,116 @@
+use egui::{DragValue, Grid, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::api::Data;
+use crate::i18n::tr;
+
+/// Gaps in ms between two samples longer than this are not integrated, e.g. after a seek in a
+/// replay or a lost connection
+const MAX_GAP: u128 = 5000;
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct CurrentLimitConfig {
+    pub enabled: bool,
+    /// Discharge current in A the driver may never exceed
+    pub peak: f32,
+    /// Discharge current in A the pack may deliver for a whole stint
+    pub continuous: f32,
+    /// Energy in kA²s that may be drawn above the continuous limit before the pit is warned, like
+    /// the I²t rating of a fuse
+    pub budget: f32,
+}
+
+impl Default for CurrentLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            peak: 300.0,
+            continuous: 150.0,
+            budget: 500.0,
+        }
+    }
+}
+
+impl CurrentLimitConfig {
+    pub fn settings(&mut self, ui: &mut Ui) {
+        ui.checkbox(
+            &mut self.enabled,
+            tr("Warn when exceeding the current limits"),
+        );
+        Grid::new("current_limit_config").show(ui, |ui| {
+            ui.label(tr("Peak current"));
+            ui.add(
+                DragValue::new(&mut self.peak)
+                    .clamp_range(1.0..=2000.0)
+                    .suffix(" A"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Continuous current"));
+            ui.add(
+                DragValue::new(&mut self.continuous)
+                    .clamp_range(1.0..=2000.0)
+                    .suffix(" A"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Overload budget"));
+            ui.add(
+                DragValue::new(&mut self.budget)
+                    .clamp_range(1.0..=100_000.0)
+                    .suffix(" kA²s"),
+            );
+            ui.end_row();
+        });
+    }
+}
+
+/// Tracks how far the driver is exceeding the agreed current limits during the stint
+#[derive(Default)]
+pub struct CurrentWatchdog {
+    /// Accumulated I²t above the continuous limit in kA²s, drains again below the limit
+    pub overload: f32,
+    /// Total time in ms above the continuous limit
+    pub time_above: u128,
+    /// Highest discharge current in A
+    pub max_current: f32,
+    /// Current in A of the last sample if it exceeded the peak limit
+    pub peak: Option<f32>,
+    last_time: Option<u128>,
+}
+
+impl CurrentWatchdog {
+    pub fn update(&mut self, config: &CurrentLimitConfig, data: &Data, time: u128) {
+        let current = data.main.current.max(0.0) / 1000.0;
+        let dt = self
+            .last_time
+            .replace(time)
+            .map_or(0, |last| time.saturating_sub(last))
+            .min(MAX_GAP);
+        if !config.enabled {
+            self.peak = None;
+            return;
+        }
+
+        let seconds = dt as f32 / 1000.0;
+        let excess = (current.powi(2) - config.continuous.powi(2)) / 1_000_000.0;
+        self.overload = (self.overload + excess * seconds).max(0.0);
+        if current > config.continuous {
+            self.time_above += dt;
+        }
+        self.max_current = self.max_current.max(current);
+        self.peak = (current > config.peak).then_some(current);
+    }
+
+    /// Used part of the overload budget in %
+    pub fn budget_used(&self, config: &CurrentLimitConfig) -> f32 {
+        self.overload / config.budget * 100.0
+    }
+
+    /// Used part of the budget in % if it has been used up
+    pub fn exceeded(&self, config: &CurrentLimitConfig) -> Option<f32> {
+        let used = self.budget_used(config);
+        (config.enabled && used >= 100.0).then_some(used)
+    }
+}
@@ -0,0 +1,426 @@
+use egui::{Color32, Grid, RichText, TextEdit, Ui, Window};
+use egui_plot::{Legend, Line, Plot, PlotPoints};
+use serde::{Deserialize, Serialize};
+
+use crate::api::{Data, TempStats, VoltageStats};
+use crate::history::History;
+use crate::i18n::tr;
+
+/// A channel computed from the received values, e.g. `power = voltage * current / 1000`
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Channel {
+    /// Only letters, digits and underscores, also used as column name in the recordings
+    pub name: String,
+    pub expression: String,
+    pub unit: String,
+}
+
+impl Channel {
+    pub fn is_valid_name(name: &str) -> bool {
+        let mut chars = name.chars();
+        chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+    }
+
+    /// Column name in the recordings, invalid characters are replaced
+    pub fn column(&self) -> String {
+        let name: String = self
+            .name
+            .chars()
+            .map(|c| match c.is_ascii_alphanumeric() {
+                true => c,
+                false => '_',
+            })
+            .collect();
+        format!("derived_{name}")
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct DerivedConfig {
+    pub channels: Vec<Channel>,
+}
+
+impl Default for DerivedConfig {
+    fn default() -> Self {
+        Self {
+            channels: vec![Channel {
+                name: "power".into(),
+                expression: "voltage * current / 1000".into(),
+                unit: "W".into(),
+            }],
+        }
+    }
+}
+
+impl DerivedConfig {
+    /// The value of every channel, `None` if it is invalid or a value is missing
+    pub fn evaluate(&self, data: &Data) -> Vec<Option<f64>> {
+        self.channels
+            .iter()
+            .map(|c| {
+                let expr = Expr::parse(&c.expression).ok()?;
+                expr.eval(data).filter(|v| v.is_finite())
+            })
+            .collect()
+    }
+
+    pub fn columns(&self) -> Vec<String> {
+        self.channels.iter().map(Channel::column).collect()
+    }
+
+    pub fn settings(&mut self, ui: &mut Ui) {
+        let mut remove = None;
+        Grid::new("derived_channels").show(ui, |ui| {
+            ui.label(tr("Name"));
+            ui.label(tr("Expression"));
+            ui.label(tr("Unit"));
+            ui.end_row();
+
+            for (i, channel) in self.channels.iter_mut().enumerate() {
+                ui.add(TextEdit::singleline(&mut channel.name).desired_width(100.0));
+                ui.add(TextEdit::singleline(&mut channel.expression).desired_width(250.0));
+                ui.add(TextEdit::singleline(&mut channel.unit).desired_width(40.0));
+                if ui.button("🗑").clicked() {
+                    remove = Some(i);
+                }
+                ui.end_row();
+
+                let error = match Expr::parse(&channel.expression) {
+                    _ if !Channel::is_valid_name(&channel.name) => {
+                        Some(tr("Names may only contain letters, digits and underscores").into())
+                    }
+                    Ok(_) => None,
+                    Err(e) => Some(e),
+                };
+                if let Some(error) = error {
+                    ui.label("");
+                    ui.label(RichText::new(error).color(Color32::RED));
+                    ui.end_row();
+                }
+            }
+        });
+        if let Some(i) = remove {
+            self.channels.remove(i);
+        }
+        if ui.button(tr("Add channel")).clicked() {
+            self.channels.push(Channel::default());
+        }
+        ui.collapsing(tr("Available values"), |ui| {
+            ui.label(
+                "voltage, current, soc, dc_link, temp_master, \
+                 cell.{min,avg,max,delta}, left.{min,avg,max,delta}, right.{min,avg,max,delta}, \
+                 temp.{min,avg,max,delta}, left.temp_{min,avg,max,delta}, \
+                 right.temp_{min,avg,max,delta}, cell_<n>, temp_<n>, abs(x), min(a, b), max(a, b)",
+            );
+        });
+    }
+}
+
+/// Plots the derived channels over the history
+#[derive(Default)]
+pub struct DerivedWindow {
+    pub show: bool,
+}
+
+impl DerivedWindow {
+    pub fn window(&mut self, ctx: &egui::Context, config: &DerivedConfig, history: &History) {
+        let mut show = self.show;
+        Window::new(tr("Derived channels"))
+            .id("derived_channels".into())
+            .open(&mut show)
+            .default_size([450.0, 300.0])
+            .show(ctx, |ui| {
+                let start = history.first().map_or(0, |s| s.time);
+                let minutes = |time: u128| (time - start) as f64 / 60_000.0;
+                Plot::new("derived_channels")
+                    .legend(Legend::default())
+                    .x_axis_label("min")
+                    .show(ui, |plot| {
+                        for (i, channel) in config.channels.iter().enumerate() {
+                            let points: PlotPoints = history
+                                .samples()
+                                .filter_map(|s| Some([minutes(s.time), (*s.derived.get(i)?)?]))
+                                .collect();
+                            let name = format!("{} ({})", channel.name, channel.unit);
+                            plot.line(Line::new(points).name(name));
+                        }
+                    });
+            });
+        self.show = show;
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Clone, Copy)]
+enum Function {
+    Abs,
+    Min,
+    Max,
+}
+
+impl Function {
+    fn parse(name: &str) -> Option<(Self, usize)> {
+        match name {
+            "abs" => Some((Function::Abs, 1)),
+            "min" => Some((Function::Min, 2)),
+            "max" => Some((Function::Max, 2)),
+            _ => None,
+        }
+    }
+}
+
+enum Expr {
+    Number(f64),
+    Variable(String),
+    Neg(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+impl Expr {
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            chars: text.chars().collect(),
+            pos: 0,
+        };
+        let expr = parser.expr()?;
+        parser.skip_whitespace();
+        match parser.peek() {
+            None => Ok(expr),
+            Some(c) => Err(format!("{} '{c}'", tr("Unexpected character"))),
+        }
+    }
+
+    fn eval(&self, data: &Data) -> Option<f64> {
+        Some(match self {
+            Expr::Number(n) => *n,
+            Expr::Variable(name) => variable(name, data)?,
+            Expr::Neg(e) => -e.eval(data)?,
+            Expr::Binary(op, a, b) => {
+                let (a, b) = (a.eval(data)?, b.eval(data)?);
+                match op {
+                    Op::Add => a + b,
+                    Op::Sub => a - b,
+                    Op::Mul => a * b,
+                    Op::Div => a / b,
+                }
+            }
+            Expr::Call(function, args) => {
+                let args: Vec<f64> = args.iter().map(|a| a.eval(data)).collect::<Option<_>>()?;
+                match function {
+                    Function::Abs => args[0].abs(),
+                    Function::Min => args[0].min(args[1]),
+                    Function::Max => args[0].max(args[1]),
+                }
+            }
+        })
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    /// Consumes `c` if it is the next non-whitespace character
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        let found = self.peek() == Some(c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut expr = self.term()?;
+        loop {
+            let op = if self.eat('+') {
+                Op::Add
+            } else if self.eat('-') {
+                Op::Sub
+            } else {
+                return Ok(expr);
+            };
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.term()?));
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut expr = self.factor()?;
+        loop {
+            let op = if self.eat('*') {
+                Op::Mul
+            } else if self.eat('/') {
+                Op::Div
+            } else {
+                return Ok(expr);
+            };
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.factor()?));
+        }
+    }
+
+    fn factor(&mut self) -> Result<Expr, String> {
+        if self.eat('-') {
+            return Ok(Expr::Neg(Box::new(self.factor()?)));
+        }
+        if self.eat('(') {
+            let expr = self.expr()?;
+            return match self.eat(')') {
+                true => Ok(expr),
+                false => Err(tr("Missing ')'").into()),
+            };
+        }
+
+        self.skip_whitespace();
+        let start = self.pos;
+        match self.peek() {
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                while self.peek().is_some_and(|c| c.is_ascii_digit() || c == '.') {
+                    self.pos += 1;
+                }
+                let text: String = self.chars[start..self.pos].iter().collect();
+                text.parse()
+                    .map(Expr::Number)
+                    .map_err(|_| format!("{} {text}", tr("Invalid number")))
+            }
+            Some(c) if c.is_ascii_alphabetic() => {
+                while self
+                    .peek()
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+                {
+                    self.pos += 1;
+                }
+                let name: String = self.chars[start..self.pos].iter().collect();
+                if self.eat('(') {
+                    return self.call(&name);
+                }
+                match is_variable(&name) {
+                    true => Ok(Expr::Variable(name)),
+                    false => Err(format!("{} {name}", tr("Unknown value"))),
+                }
+            }
+            Some(c) => Err(format!("{} '{c}'", tr("Unexpected character"))),
+            None => Err(tr("Unexpected end of expression").into()),
+        }
+    }
+
+    /// Parses the arguments of a function call after the opening parenthesis
+    fn call(&mut self, name: &str) -> Result<Expr, String> {
+        let Some((function, arity)) = Function::parse(name) else {
+            return Err(format!("{} {name}", tr("Unknown function")));
+        };
+        let mut args = vec![self.expr()?];
+        while self.eat(',') {
+            args.push(self.expr()?);
+        }
+        if !self.eat(')') {
+            return Err(tr("Missing ')'").into());
+        }
+        match args.len() == arity {
+            true => Ok(Expr::Call(function, args)),
+            false => Err(format!("{name}: {arity} {}", tr("arguments expected"))),
+        }
+    }
+}
+
+fn is_variable(name: &str) -> bool {
+    let indexed = ["cell_", "temp_"].iter().any(|prefix| {
+        name.strip_prefix(prefix)
+            .and_then(|n| n.parse::<usize>().ok())
+            .is_some_and(|n| n > 0)
+    });
+    let stats = ["min", "avg", "max", "delta"];
+    let field = match name.split_once('.') {
+        Some(("cell" | "temp", field)) => stats.contains(&field),
+        Some(("left" | "right", field)) => {
+            stats.contains(&field.strip_prefix("temp_").unwrap_or(field))
+        }
+        _ => false,
+    };
+    indexed
+        || field
+        || matches!(
+            name,
+            "voltage" | "current" | "soc" | "dc_link" | "temp_master"
+        )
+}
+
+fn variable(name: &str, data: &Data) -> Option<f64> {
+    let voltage = |stats: &VoltageStats, field: &str| {
+        Some(match field {
+            "min" => stats.min_voltage,
+            "avg" => stats.avg_voltage,
+            "max" => stats.max_voltage,
+            "delta" => stats.delta_voltage,
+            _ => return None,
+        } as f64)
+    };
+    let temp = |stats: &TempStats, field: &str| {
+        Some(match field {
+            "min" => stats.min_temp,
+            "avg" => stats.avg_temp,
+            "max" => stats.max_temp,
+            "delta" => stats.delta_temp,
+            _ => return None,
+        } as f64)
+    };
+    let (ucell, tcell) = (&data.ucell, &data.tcell);
+
+    if let Some(n) = name
+        .strip_prefix("cell_")
+        .and_then(|n| n.parse::<usize>().ok())
+    {
+        return ucell.cell_voltage.get(n.checked_sub(1)?).map(|v| *v as f64);
+    }
+    if let Some(n) = name
+        .strip_prefix("temp_")
+        .and_then(|n| n.parse::<usize>().ok())
+    {
+        return tcell.temp.get(n.checked_sub(1)?).map(|t| *t as f64);
+    }
+    match name.split_once('.') {
+        Some(("cell", field)) => voltage(&ucell.overall, field),
+        Some(("temp", field)) => temp(&tcell.overall, field),
+        Some((side, field)) => {
+            let (v, t) = match side {
+                "left" => (&ucell.left, &tcell.left),
+                "right" => (&ucell.right, &tcell.right),
+                _ => return None,
+            };
+            match field.strip_prefix("temp_") {
+                Some(field) => temp(t, field),
+                None => voltage(v, field),
+            }
+        }
+        None => match name {
+            "voltage" => Some(data.main.voltage as f64),
+            "current" => Some(data.main.current as f64),
+            "soc" => Some(data.main.state_of_charge as f64),
+            "dc_link" => data.main.dc_link_voltage.map(|v| v as f64),
+            "temp_master" => Some(data.main.temp_master as f64),
+            _ => None,
+        },
+    }
+}
@@ -0,0 +1,138 @@
+use std::net::{TcpStream, ToSocketAddrs};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use egui::{Color32, Grid, RichText, Window};
+
+use crate::api::{check_endpoint, probe_format, Connection, EndpointCheck, Format, ENDPOINTS};
+use crate::http::parse_address;
+use crate::i18n::tr;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+struct Report {
+    /// Time to open a TCP connection to the BMS
+    connect: Result<Duration, String>,
+    endpoints: Vec<EndpointCheck>,
+}
+
+/// Checks the connection to the BMS step by step to find out why polling fails
+#[derive(Default)]
+pub struct Diagnostics {
+    pub show: bool,
+    request: Option<JoinHandle<Report>>,
+    report: Option<Report>,
+}
+
+impl Diagnostics {
+    /// `format` is the manually selected one, otherwise it is probed like when polling
+    pub fn window(&mut self, ctx: &egui::Context, connection: &Connection, format: Option<Format>) {
+        if self.request.as_ref().is_some_and(|r| r.is_finished()) {
+            self.report = self.request.take().unwrap().join().ok();
+        }
+
+        let mut show = self.show;
+        Window::new(tr("Network diagnostics"))
+            .id("diagnostics".into())
+            .open(&mut show)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(&connection.ip);
+                    if self.request.is_some() {
+                        ui.spinner();
+                    } else if ui.button(tr("Run test")).clicked() {
+                        let connection = connection.clone();
+                        self.request = Some(thread::spawn(move || run(&connection, format)));
+                    }
+                });
+                if connection.proxy.is_some() {
+                    ui.label(tr("The connection test bypasses the proxy"));
+                }
+
+                let Some(report) = &self.report else {
+                    return;
+                };
+                let ok = |ui: &mut egui::Ui, text: String| {
+                    ui.label(RichText::new(text).color(Color32::GREEN));
+                };
+                let failed = |ui: &mut egui::Ui, text: String| {
+                    ui.label(RichText::new(text).color(Color32::RED));
+                };
+                Grid::new("diagnostics").striped(true).show(ui, |ui| {
+                    ui.strong(tr("Test"));
+                    ui.strong(tr("Status"));
+                    ui.strong(tr("Latency"));
+                    ui.strong(tr("Result"));
+                    ui.end_row();
+
+                    ui.label(tr("TCP connection"));
+                    match &report.connect {
+                        Ok(latency) => {
+                            ok(ui, "OK".into());
+                            ui.label(format!("{} ms", latency.as_millis()));
+                            ui.label("");
+                        }
+                        Err(e) => {
+                            failed(ui, tr("failed").into());
+                            ui.label("-");
+                            failed(ui, e.clone());
+                        }
+                    }
+                    ui.end_row();
+
+                    for check in &report.endpoints {
+                        ui.label(check.page);
+                        match check.status {
+                            Some(code) if (200..300).contains(&code) => ok(ui, code.to_string()),
+                            Some(code) => failed(ui, code.to_string()),
+                            None => failed(ui, "-".into()),
+                        }
+                        ui.label(format!("{} ms", check.latency.as_millis()));
+                        match &check.result {
+                            Ok(summary) => ok(ui, summary.clone()),
+                            Err(e) => failed(ui, e.to_string()),
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+        self.show = show;
+    }
+}
+
+fn run(connection: &Connection, format: Option<Format>) -> Report {
+    let format = format
+        .or_else(|| probe_format(connection).ok())
+        .unwrap_or_default();
+    let endpoints = ENDPOINTS
+        .into_iter()
+        .map(|page| {
+            // a panic while parsing only fails this endpoint
+            let owned = connection.clone();
+            thread::spawn(move || check_endpoint(&owned, page, format))
+                .join()
+                .unwrap_or_else(|_| EndpointCheck {
+                    page,
+                    status: None,
+                    latency: Duration::ZERO,
+                    result: Err(anyhow::anyhow!(tr("Unexpected error"))),
+                })
+        })
+        .collect();
+    Report {
+        connect: connect(&connection.ip),
+        endpoints,
+    }
+}
+
+fn connect(address: &str) -> Result<Duration, String> {
+    let (host, port) = parse_address(address)?;
+    let socket = (host.as_str(), port)
+        .to_socket_addrs()
+        .map_err(|e| e.to_string())?
+        .next()
+        .ok_or_else(|| tr("Hostname not found").to_string())?;
+    let start = Instant::now();
+    TcpStream::connect_timeout(&socket, CONNECT_TIMEOUT).map_err(|e| e.to_string())?;
+    Ok(start.elapsed())
+}
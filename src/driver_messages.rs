@@ -0,0 +1,46 @@
+use egui::{Grid, TextEdit, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::tr;
+use crate::keymap::{Action, Keymap};
+
+/// Number of messages that can be bound to a shortcut
+pub const SLOTS: usize = 5;
+
+/// Predefined calls to the driver, logged as markers with a single key press so strategy calls
+/// end up next to the battery data they were based on
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct DriverMessages {
+    pub messages: Vec<String>,
+}
+
+impl Default for DriverMessages {
+    fn default() -> Self {
+        let mut messages: Vec<String> = ["Pit now", "Lift and coast", "Temperature warning given"]
+            .map(String::from)
+            .into();
+        messages.resize(SLOTS, String::new());
+        Self { messages }
+    }
+}
+
+impl DriverMessages {
+    /// Text of a slot, `None` if it is unused
+    pub fn text(&self, slot: u8) -> Option<&str> {
+        let text = self.messages.get(slot as usize)?.trim();
+        (!text.is_empty()).then_some(text)
+    }
+
+    pub fn settings(&mut self, ui: &mut Ui, keymap: &Keymap) {
+        self.messages.resize(SLOTS, String::new());
+        Grid::new("driver_messages").show(ui, |ui| {
+            for (slot, message) in self.messages.iter_mut().enumerate() {
+                ui.monospace(keymap.format(ui.ctx(), Action::DriverMessage(slot as u8)));
+                ui.add(TextEdit::singleline(message).hint_text(tr("Unused")));
+                ui.end_row();
+            }
+        });
+        ui.label(tr("The shortcuts can be changed below"));
+    }
+}
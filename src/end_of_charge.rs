@@ -0,0 +1,213 @@
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use egui::{DragValue, Grid, Ui, UserAttentionType, ViewportCommand, Window};
+use serde::{Deserialize, Serialize};
+
+use crate::api::Data;
+use crate::charging::ChargingConfig;
+use crate::i18n::tr;
+use crate::units;
+
+/// The highest cell has to drop this many mV below the cutoff before another end of charge is
+/// detected, the cells relax a little after the charger stopped
+const REARM_MARGIN: u16 = 50;
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct EndOfChargeConfig {
+    pub enabled: bool,
+    /// Charge current in mA below which the charger is considered done
+    pub current: f32,
+    /// Delta between the highest and lowest cell in mV the balancing has to reach
+    pub delta: u16,
+    /// Voltage in mV the highest cell has to reach
+    pub cutoff: u16,
+    /// Time in s all conditions have to hold, rides out the pulses of the charger
+    pub hold: u64,
+    /// The notification is POSTed as plain text to this URL if it isn't empty, e.g. a push
+    /// notification service
+    pub url: String,
+}
+
+impl Default for EndOfChargeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            current: 300.0,
+            delta: 10,
+            cutoff: 4180,
+            hold: 60,
+            url: String::new(),
+        }
+    }
+}
+
+impl EndOfChargeConfig {
+    pub fn settings(&mut self, ui: &mut Ui) {
+        ui.checkbox(&mut self.enabled, tr("Detect the end of charge"));
+        Grid::new("end_of_charge_config").show(ui, |ui| {
+            ui.label(tr("End current"));
+            ui.add(
+                DragValue::new(&mut self.current)
+                    .clamp_range(0.0..=100000.0)
+                    .suffix(" mA"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Target delta"));
+            ui.add(
+                DragValue::new(&mut self.delta)
+                    .clamp_range(1..=500)
+                    .suffix(" mV"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Cutoff voltage"));
+            ui.add(
+                DragValue::new(&mut self.cutoff)
+                    .clamp_range(3000..=4500)
+                    .suffix(" mV"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Hold time"));
+            ui.add(
+                DragValue::new(&mut self.hold)
+                    .clamp_range(0..=3600)
+                    .suffix(" s"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Notification URL"));
+            ui.text_edit_singleline(&mut self.url);
+            ui.end_row();
+        });
+    }
+}
+
+/// Watches a charge until the current, the delta and the highest cell reached their targets
+#[derive(Default)]
+pub struct EndOfCharge {
+    /// Set when the pack was seen charging, only then an end of charge can be detected
+    armed: bool,
+    /// Set after an end of charge until the highest cell dropped below the cutoff again
+    full: bool,
+    /// Unix time in ms since when all conditions hold
+    met_since: Option<u128>,
+    /// Unix time in ms of the last detected end of charge, shown until it is dismissed
+    pub finished: Option<u128>,
+    attention: bool,
+    request: Option<JoinHandle<Result<(), String>>>,
+    /// Error of the last notification request
+    error: Option<String>,
+}
+
+impl EndOfCharge {
+    /// Whether the end of charge was detected with this sample
+    pub fn update(
+        &mut self,
+        config: &EndOfChargeConfig,
+        charging: &ChargingConfig,
+        data: &Data,
+        time: u128,
+    ) -> bool {
+        let ucell = &data.ucell.overall;
+        if !config.enabled || data.ucell.cell_voltage.is_empty() {
+            self.met_since = None;
+            return false;
+        }
+        if ucell.max_voltage + REARM_MARGIN < config.cutoff {
+            self.full = false;
+        }
+        if !self.full && charging.is_charging(data) {
+            self.armed = true;
+        }
+        if !self.armed {
+            return false;
+        }
+
+        let met = -data.main.current < config.current
+            && ucell.delta_voltage <= config.delta
+            && ucell.max_voltage >= config.cutoff;
+        if !met {
+            self.met_since = None;
+            return false;
+        }
+        let since = *self.met_since.get_or_insert(time);
+        if time - since < config.hold as u128 * 1000 {
+            return false;
+        }
+
+        self.armed = false;
+        self.full = true;
+        self.met_since = None;
+        self.finished = Some(time);
+        self.attention = true;
+        if !config.url.is_empty() {
+            let url = config.url.clone();
+            let text = format!(
+                "{}: {} {}, {} {}",
+                tr("Charging complete"),
+                tr("highest cell"),
+                units::voltage(ucell.max_voltage),
+                tr("delta"),
+                units::voltage(ucell.delta_voltage)
+            );
+            self.request = Some(thread::spawn(move || notify(&url, &text)));
+        }
+        true
+    }
+
+    pub fn window(&mut self, ctx: &egui::Context, now: u128) {
+        if std::mem::take(&mut self.attention) {
+            ctx.send_viewport_cmd(ViewportCommand::RequestUserAttention(
+                UserAttentionType::Critical,
+            ));
+        }
+        if self.request.as_ref().is_some_and(|r| r.is_finished()) {
+            self.error = match self.request.take().unwrap().join() {
+                Ok(result) => result.err(),
+                Err(_) => Some(tr("Unexpected error").to_string()),
+            };
+        }
+        let Some(finished) = self.finished else {
+            return;
+        };
+
+        let mut show = true;
+        Window::new(tr("Charging complete"))
+            .id("end_of_charge".into())
+            .open(&mut show)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                let minutes = now.saturating_sub(finished) / 60_000;
+                ui.label(format!(
+                    "{} {minutes} min {}",
+                    tr("The charge ended"),
+                    tr("ago")
+                ));
+                if let Some(error) = &self.error {
+                    ui.colored_label(
+                        ui.visuals().error_fg_color,
+                        format!("{}: {error}", tr("Error sending notification")),
+                    );
+                }
+                if ui.button(tr("Dismiss")).clicked() {
+                    self.finished = None;
+                }
+            });
+        if !show {
+            self.finished = None;
+        }
+    }
+}
+
+fn notify(url: &str, text: &str) -> Result<(), String> {
+    ureq::post(url)
+        .set("Content-Type", "text/plain; charset=utf-8")
+        .timeout(Duration::from_secs(10))
+        .send_string(text)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
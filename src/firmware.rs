@@ -0,0 +1,257 @@
+use std::fs;
+use std::path::Path;
+use std::thread::{self, JoinHandle};
+
+use egui::{Align2, Color32, RichText, Window};
+
+use crate::api::{fetch_info, upload_firmware, BmsInfo, Connection};
+use crate::app::now;
+use crate::control;
+use crate::i18n::tr;
+
+/// Time in ms the BMS gets to start flashing, an answer before is not taken as the restart
+const REBOOT_DELAY: u128 = 5000;
+/// Time in ms after which the BMS is considered not to have come back
+const REBOOT_TIMEOUT: u128 = 120_000;
+/// Interval in ms of the requests while waiting for the reboot
+const REBOOT_POLL_INTERVAL: u128 = 2000;
+
+/// Waiting for the BMS to answer again after the upload
+struct Reboot {
+    since: u128,
+    last_poll: u128,
+    request: Option<JoinHandle<anyhow::Result<BmsInfo>>>,
+    /// Firmware version before the upload, if it could be read
+    before: Option<String>,
+    /// Set once a request failed, the BMS went down to flash the image
+    went_down: bool,
+    /// Firmware version of the last answer
+    answered: Option<String>,
+}
+
+/// Maintenance dialog uploading a firmware image to the BMS and monitoring the reboot
+#[derive(Default)]
+pub struct FirmwareDialog {
+    pub show: bool,
+    path: String,
+    unlocked: bool,
+    confirm: bool,
+    /// Returns the firmware version before the upload
+    upload: Option<JoinHandle<anyhow::Result<Option<String>>>>,
+    reboot: Option<Reboot>,
+    /// Message of the last update, an error if it failed
+    result: Option<Result<String, String>>,
+}
+
+impl FirmwareDialog {
+    /// The dashboard stops polling while the BMS is flashed and restarting
+    pub fn is_busy(&self) -> bool {
+        self.upload.is_some() || self.reboot.is_some()
+    }
+
+    /// Returns a status message once the update has finished or failed. `ts_active` blocks the
+    /// upload, the BMS opens the AIRs while restarting.
+    pub fn window(
+        &mut self,
+        ctx: &egui::Context,
+        connection: &Connection,
+        ts_active: bool,
+        log_directory: &Path,
+    ) -> Option<String> {
+        let status = self.update(connection, log_directory);
+
+        if !self.show {
+            self.unlocked = false;
+        }
+        let mut show = self.show;
+        Window::new(tr("Firmware update"))
+            .id("firmware".into())
+            .open(&mut show)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(!self.is_busy(), |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(tr("Firmware image"));
+                        ui.text_edit_singleline(&mut self.path);
+                    });
+                    ui.checkbox(&mut self.unlocked, tr("Unlock firmware update"));
+                });
+                if ts_active {
+                    let text = tr("The tractive system is active, open the AIRs before updating");
+                    ui.label(RichText::new(text).color(Color32::RED));
+                }
+                let ready = self.unlocked && !ts_active && !self.is_busy();
+                if ui
+                    .add_enabled(ready, egui::Button::new(tr("Upload")))
+                    .clicked()
+                {
+                    self.confirm = true;
+                }
+
+                if self.upload.is_some() {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(tr("Uploading firmware"));
+                    });
+                }
+                if let Some(reboot) = &self.reboot {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        let seconds = now().saturating_sub(reboot.since) / 1000;
+                        ui.label(format!(
+                            "{} ({seconds} s)",
+                            tr("Waiting for the BMS to restart")
+                        ));
+                    });
+                }
+                match &self.result {
+                    Some(Ok(message)) => {
+                        ui.label(RichText::new(message).color(Color32::GREEN));
+                    }
+                    Some(Err(message)) => {
+                        ui.label(RichText::new(message).color(Color32::RED));
+                    }
+                    None => {}
+                }
+            });
+        self.show = show;
+
+        if self.confirm {
+            Window::new(tr("Confirm firmware update"))
+                .id("firmware_confirm".into())
+                .collapsible(false)
+                .resizable(false)
+                .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(
+                        RichText::new(format!(
+                            "{} \"{}\"?",
+                            tr("Flash the BMS with"),
+                            self.path.trim()
+                        ))
+                        .color(Color32::RED)
+                        .strong(),
+                    );
+                    ui.label(tr("Do not power off the BMS until it has restarted."));
+                    ui.horizontal(|ui| {
+                        if ui.button(tr("Upload")).clicked() {
+                            self.confirm = false;
+                            self.start(connection);
+                        }
+                        if ui.button(tr("cancel")).clicked() {
+                            self.confirm = false;
+                        }
+                    });
+                });
+        }
+
+        status
+    }
+
+    fn start(&mut self, connection: &Connection) {
+        let path = Path::new(self.path.trim());
+        self.result = None;
+        match fs::read(path) {
+            Ok(image) => {
+                let file_name = path
+                    .file_name()
+                    .map_or_else(String::new, |n| n.to_string_lossy().to_string());
+                let connection = connection.clone();
+                // the version before is compared to the one after the restart
+                self.upload = Some(thread::spawn(move || {
+                    let before = fetch_info(&connection).join().ok().and_then(Result::ok);
+                    match upload_firmware(&connection, file_name, image).join() {
+                        Ok(result) => result.map(|()| before.map(|i| i.firmware)),
+                        Err(_) => Err(anyhow::anyhow!(tr("Unexpected error"))),
+                    }
+                }));
+                self.unlocked = false;
+            }
+            Err(e) => {
+                self.result = Some(Err(format!("{}: {e}", tr("Error reading firmware image"))))
+            }
+        }
+    }
+
+    /// Advances the upload and the reboot monitoring
+    fn update(&mut self, connection: &Connection, log_directory: &Path) -> Option<String> {
+        let now = now();
+        if let Some(upload) = self.upload.take_if(|r| r.is_finished()) {
+            let error = match upload.join() {
+                Ok(Ok(before)) => {
+                    self.reboot = Some(Reboot {
+                        since: now,
+                        last_poll: 0,
+                        request: None,
+                        before,
+                        went_down: false,
+                        answered: None,
+                    });
+                    None
+                }
+                Ok(Err(e)) => Some(format!("{}: {e}", tr("Firmware upload failed"))),
+                Err(_) => Some(tr("Unexpected error").to_string()),
+            };
+            if let Some(error) = error {
+                return self.finish(Err(error), &connection.ip, log_directory);
+            }
+        }
+
+        let reboot = self.reboot.as_mut()?;
+        if let Some(request) = reboot.request.take_if(|r| r.is_finished()) {
+            match request.join() {
+                Ok(Ok(info)) => {
+                    // an answer only proves the update if the BMS restarted or runs another version
+                    let changed = reboot.before.as_ref().is_some_and(|b| *b != info.firmware);
+                    let restarted = now.saturating_sub(reboot.since) > REBOOT_DELAY
+                        && (reboot.went_down || changed);
+                    if restarted {
+                        self.reboot = None;
+                        let message = format!(
+                            "{}: {}",
+                            tr("Firmware updated, the BMS runs"),
+                            info.firmware
+                        );
+                        return self.finish(Ok(message), &connection.ip, log_directory);
+                    }
+                    reboot.answered = Some(info.firmware);
+                }
+                _ => reboot.went_down = true,
+            }
+        }
+        if now.saturating_sub(reboot.since) > REBOOT_TIMEOUT {
+            let error = match self.reboot.take().and_then(|r| r.answered) {
+                Some(firmware) => format!(
+                    "{}: {firmware}",
+                    tr("Update unverified, the BMS never restarted and still runs")
+                ),
+                None => tr("The BMS did not come back after the update").to_string(),
+            };
+            return self.finish(Err(error), &connection.ip, log_directory);
+        }
+        // polled right away, so a quick restart isn't missed
+        let due = now.saturating_sub(reboot.last_poll) > REBOOT_POLL_INTERVAL;
+        if reboot.request.is_none() && due {
+            reboot.last_poll = now;
+            reboot.request = Some(fetch_info(connection));
+        }
+        None
+    }
+
+    fn finish(
+        &mut self,
+        result: Result<String, String>,
+        ip: &str,
+        log_directory: &Path,
+    ) -> Option<String> {
+        let message = match &result {
+            Ok(message) | Err(message) => message.clone(),
+        };
+        self.result = Some(result);
+        let entry = format!("{} {}: {message}", tr("Firmware update"), self.path.trim());
+        Some(match control::log(log_directory, ip, &entry) {
+            Ok(()) => message,
+            Err(e) => format!("{message}, {}: {e}", tr("Error logging command")),
+        })
+    }
+}
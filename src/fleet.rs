@@ -0,0 +1,212 @@
+use std::thread::{self, JoinHandle};
+
+use egui::{Color32, DragValue, Grid, RichText, TextEdit, Ui, Window};
+use serde::{Deserialize, Serialize};
+
+use crate::api::{self, Data, DataSource, HttpSource, PollOptions};
+use crate::charging::ChargingConfig;
+use crate::http::HttpConfig;
+use crate::i18n::tr;
+use crate::units;
+
+/// Another dashboard or pack polled in the background, e.g. the spare pack on the bench charger
+#[derive(Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Pack {
+    pub name: String,
+    /// Address of the BMS web interface
+    pub ip: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct FleetConfig {
+    pub packs: Vec<Pack>,
+    /// Interval in s between the requests to every pack
+    pub poll_interval: u64,
+}
+
+impl Default for FleetConfig {
+    fn default() -> Self {
+        Self {
+            packs: Vec::new(),
+            poll_interval: 10,
+        }
+    }
+}
+
+impl FleetConfig {
+    pub fn settings(&mut self, ui: &mut Ui) {
+        Grid::new("fleet_config").show(ui, |ui| {
+            ui.label(tr("Poll interval"));
+            ui.add(
+                DragValue::new(&mut self.poll_interval)
+                    .clamp_range(1..=600)
+                    .suffix(" s"),
+            );
+            ui.end_row();
+        });
+        let mut remove = None;
+        Grid::new("fleet_packs").show(ui, |ui| {
+            for (i, pack) in self.packs.iter_mut().enumerate() {
+                ui.add(
+                    TextEdit::singleline(&mut pack.name)
+                        .hint_text(tr("Name"))
+                        .desired_width(120.0),
+                );
+                ui.add(
+                    TextEdit::singleline(&mut pack.ip)
+                        .hint_text("http://192.168.0.200")
+                        .desired_width(200.0),
+                );
+                if ui.button("🗑").clicked() {
+                    remove = Some(i);
+                }
+                ui.end_row();
+            }
+        });
+        if let Some(i) = remove {
+            self.packs.remove(i);
+        }
+        if ui.button(tr("Add pack")).clicked() {
+            self.packs.push(Pack::default());
+        }
+    }
+}
+
+/// Last sample or error of one pack
+#[derive(Default)]
+struct PackState {
+    ip: String,
+    data: Option<Data>,
+    /// Unix time in ms of the last sample
+    received: u128,
+    error: Option<String>,
+    last_poll: u128,
+    request: Option<JoinHandle<Result<Data, api::Error>>>,
+}
+
+/// Overview of all registered packs
+#[derive(Default)]
+pub struct Fleet {
+    states: Vec<PackState>,
+    pub show: bool,
+}
+
+impl Fleet {
+    /// Polls every pack whose interval has passed, also while the overview is closed so it is
+    /// current when opened
+    pub fn update(&mut self, config: &FleetConfig, http: &HttpConfig, safe: bool, now: u128) {
+        self.states
+            .resize_with(config.packs.len(), PackState::default);
+        for (pack, state) in config.packs.iter().zip(&mut self.states) {
+            if state.ip != pack.ip {
+                *state = PackState {
+                    ip: pack.ip.clone(),
+                    ..Default::default()
+                };
+            }
+
+            if let Some(request) = state.request.take_if(|r| r.is_finished()) {
+                match request.join() {
+                    Ok(Ok(data)) => {
+                        state.data = Some(data);
+                        state.received = now;
+                        state.error = None;
+                    }
+                    Ok(Err(api::Error::Fetch(e))) => state.error = Some(e.to_string()),
+                    _ => state.error = Some(tr("Unexpected error").to_string()),
+                }
+            }
+
+            let due = state.last_poll + config.poll_interval as u128 * 1000 <= now;
+            if state.request.is_some() || !due || pack.ip.trim().is_empty() {
+                continue;
+            }
+            state.last_poll = now;
+            let mut source = HttpSource::new(http.connection(&pack.ip));
+            // the dead sensors of the main pack don't apply to the others
+            let options = PollOptions {
+                safe,
+                dead_sensors: Vec::new(),
+                pipelining: false,
+                format: http.format,
+            };
+            state.request = Some(thread::spawn(move || source.poll(&options)));
+        }
+    }
+
+    /// Shows one row per pack, returns the index of the pack to open in the dashboard
+    pub fn window(
+        &mut self,
+        ctx: &egui::Context,
+        config: &FleetConfig,
+        charging: &ChargingConfig,
+        now: u128,
+    ) -> Option<usize> {
+        let mut open = None;
+        let mut show = self.show;
+        Window::new(tr("Fleet"))
+            .id("fleet".into())
+            .open(&mut show)
+            .default_size([600.0, 200.0])
+            .show(ctx, |ui| {
+                if config.packs.is_empty() {
+                    ui.label(tr("Add packs in the settings"));
+                    return;
+                }
+                let stale = config.poll_interval as u128 * 3000;
+                Grid::new("fleet").striped(true).show(ui, |ui| {
+                    for name in ["Name", "State", "SOC", "Voltage", "Current", "Min cell"] {
+                        ui.label(RichText::new(tr(name)).strong());
+                    }
+                    ui.label(RichText::new(tr("Max temperature")).strong());
+                    ui.end_row();
+
+                    for (i, (pack, state)) in config.packs.iter().zip(&self.states).enumerate() {
+                        let name = match pack.name.is_empty() {
+                            true => &pack.ip,
+                            false => &pack.name,
+                        };
+                        ui.label(name);
+                        let (text, color) = match (&state.error, &state.data) {
+                            (Some(_), _) => (tr("Error"), Color32::RED),
+                            (None, None) => (tr("Connecting"), Color32::GRAY),
+                            (None, Some(_)) if now - state.received > stale => {
+                                (tr("Stale"), Color32::YELLOW)
+                            }
+                            (None, Some(data)) if charging.is_charging(data) => {
+                                (tr("Charging"), Color32::GREEN)
+                            }
+                            (None, Some(_)) => (tr("Online"), Color32::GREEN),
+                        };
+                        let label = ui.label(RichText::new(text).color(color));
+                        if let Some(error) = &state.error {
+                            label.on_hover_text(error);
+                        }
+
+                        match &state.data {
+                            Some(data) => {
+                                ui.label(format!("{:.1} %", data.main.state_of_charge));
+                                ui.label(format!("{:.1} V", data.main.voltage));
+                                ui.label(format!("{:.1} A", data.main.current / 1000.0));
+                                ui.label(units::voltage(data.ucell.overall.min_voltage));
+                                ui.label(units::temp(data.tcell.overall.max_temp));
+                            }
+                            None => {
+                                for _ in 0..5 {
+                                    ui.label("-");
+                                }
+                            }
+                        }
+                        if ui.button(tr("Open")).clicked() {
+                            open = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+        self.show = show;
+        open
+    }
+}
@@ -0,0 +1,116 @@
+use egui::{Align2, Color32, DragValue, FontId, Grid, Rect, Sense, Stroke, Ui, Vec2};
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::tr;
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct FuelGaugeConfig {
+    pub enabled: bool,
+    /// Compensated minimum cell voltage in mV shown as an empty gauge
+    pub empty_voltage: u16,
+    /// Compensated minimum cell voltage in mV shown as a full gauge
+    pub full_voltage: u16,
+    /// Levels in % below which the gauge turns yellow and red
+    pub yellow: f32,
+    pub red: f32,
+}
+
+impl Default for FuelGaugeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            empty_voltage: 3300,
+            full_voltage: 4150,
+            yellow: 40.0,
+            red: 15.0,
+        }
+    }
+}
+
+impl FuelGaugeConfig {
+    pub fn settings(&mut self, ui: &mut Ui) {
+        ui.checkbox(&mut self.enabled, tr("Show fuel gauge in driver mode"));
+        Grid::new("fuel_gauge_config").show(ui, |ui| {
+            ui.label(tr("Empty at"));
+            ui.add(
+                DragValue::new(&mut self.empty_voltage)
+                    .clamp_range(2500..=self.full_voltage.saturating_sub(1))
+                    .suffix(" mV"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Full at"));
+            ui.add(
+                DragValue::new(&mut self.full_voltage)
+                    .clamp_range(self.empty_voltage + 1..=4500)
+                    .suffix(" mV"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Yellow below"));
+            ui.add(
+                DragValue::new(&mut self.yellow)
+                    .clamp_range(self.red..=100.0)
+                    .suffix(" %"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Red below"));
+            ui.add(
+                DragValue::new(&mut self.red)
+                    .clamp_range(0.0..=self.yellow)
+                    .suffix(" %"),
+            );
+            ui.end_row();
+        });
+        ui.label(tr(
+            "The weakest cell's voltage is compensated for the sag under load",
+        ));
+    }
+
+    /// Level in % of the weakest cell's compensated voltage in mV
+    pub fn level(&self, min_voltage: u16) -> f32 {
+        let range = self.full_voltage.saturating_sub(self.empty_voltage).max(1) as f32;
+        let level = (min_voltage as f32 - self.empty_voltage as f32) / range * 100.0;
+        level.clamp(0.0, 100.0)
+    }
+
+    fn color(&self, level: f32) -> Color32 {
+        if level < self.red {
+            Color32::from_rgb(0xe5, 0x39, 0x35)
+        } else if level < self.yellow {
+            Color32::from_rgb(0xfd, 0xd8, 0x35)
+        } else {
+            Color32::from_rgb(0x43, 0xa0, 0x47)
+        }
+    }
+
+    /// A horizontal bar over the full width, raw millivolts mean nothing to the driver
+    pub fn show(&self, ui: &mut Ui, min_voltage: u16, height: f32) {
+        let level = self.level(min_voltage);
+        let (rect, _) =
+            ui.allocate_exact_size(Vec2::new(ui.available_width(), height), Sense::hover());
+        let painter = ui.painter_at(rect);
+        let visuals = ui.visuals();
+        painter.rect_filled(rect, 4.0, visuals.extreme_bg_color);
+        let filled = Rect::from_min_size(
+            rect.min,
+            Vec2::new(rect.width() * level / 100.0, rect.height()),
+        );
+        painter.rect_filled(filled, 4.0, self.color(level));
+        // zone boundaries
+        for zone in [self.red, self.yellow] {
+            let x = rect.left() + rect.width() * zone / 100.0;
+            painter.vline(x, rect.y_range(), Stroke::new(2.0, visuals.text_color()));
+        }
+        painter.rect_stroke(rect, 4.0, Stroke::new(2.0, visuals.text_color()));
+        painter.text(
+            rect.center(),
+            Align2::CENTER_CENTER,
+            format!("{level:.0} %"),
+            FontId::monospace(height * 0.6),
+            visuals.strong_text_color(),
+        );
+    }
+}
@@ -0,0 +1,267 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use egui::{ComboBox, Grid, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::app::now;
+use crate::i18n::tr;
+
+/// A fix older than this in ms is not shown anymore
+const STALE_FIX: u128 = 3000;
+/// Time in ms after which a receiver that failed is opened again
+const RETRY_INTERVAL: u128 = 10_000;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum GpsSource {
+    /// NMEA sentences from a USB receiver
+    Serial,
+    /// NMEA sentences relayed by gpsd
+    Gpsd,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GpsConfig {
+    pub enabled: bool,
+    pub source: GpsSource,
+    pub port: String,
+    pub baud_rate: u32,
+    pub gpsd_address: String,
+}
+
+impl Default for GpsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            source: GpsSource::Serial,
+            port: "/dev/ttyACM0".into(),
+            baud_rate: 9600,
+            gpsd_address: "localhost:2947".into(),
+        }
+    }
+}
+
+impl GpsConfig {
+    pub fn settings(&mut self, ui: &mut Ui) {
+        ui.checkbox(&mut self.enabled, tr("Use GPS"));
+        Grid::new("gps_config").show(ui, |ui| {
+            ui.label(tr("Source"));
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.source, GpsSource::Serial, tr("Serial port"));
+                ui.selectable_value(&mut self.source, GpsSource::Gpsd, "gpsd");
+            });
+            ui.end_row();
+
+            match self.source {
+                GpsSource::Serial => {
+                    ui.label(tr("Port"));
+                    committed_text_edit(ui, &mut self.port);
+                    ui.end_row();
+
+                    ui.label(tr("Baud rate"));
+                    ComboBox::from_id_source("gps_baud_rate")
+                        .selected_text(self.baud_rate.to_string())
+                        .show_ui(ui, |ui| {
+                            for rate in [4800, 9600, 19200, 38400, 57600, 115200] {
+                                ui.selectable_value(&mut self.baud_rate, rate, rate.to_string());
+                            }
+                        });
+                    ui.end_row();
+                }
+                GpsSource::Gpsd => {
+                    ui.label(tr("Address"));
+                    committed_text_edit(ui, &mut self.gpsd_address);
+                    ui.end_row();
+                }
+            }
+        });
+    }
+}
+
+/// A text field that only writes to `value` when it loses focus or Enter is pressed, so the
+/// receiver isn't reopened with every keystroke
+fn committed_text_edit(ui: &mut Ui, value: &mut String) {
+    let id = ui.next_auto_id();
+    let mut text = ui
+        .data(|d| d.get_temp::<String>(id))
+        .unwrap_or_else(|| value.clone());
+    let response = ui.text_edit_singleline(&mut text);
+    if response.has_focus() {
+        ui.data_mut(|d| d.insert_temp(id, text));
+    } else {
+        if response.lost_focus() {
+            *value = text;
+        }
+        ui.data_mut(|d| d.remove::<String>(id));
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Fix {
+    /// Unix time in ms when the fix was received
+    pub time: u128,
+    // in degrees
+    pub latitude: f64,
+    pub longitude: f64,
+    // in km/h
+    pub speed: f32,
+}
+
+/// Opens the receiver and reads NMEA sentences in a background thread until dropped
+pub struct Gps {
+    pub config: GpsConfig,
+    fix: Arc<Mutex<Option<Fix>>>,
+    error: Arc<Mutex<Option<io::Error>>>,
+    /// Unix time in ms when opening or reading failed
+    failed: Arc<Mutex<Option<u128>>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Gps {
+    /// Opening may block for a while, e.g. connecting to an unreachable gpsd, so it is left to the
+    /// thread and its errors are reported by `take_error` like the read errors
+    pub fn open(config: &GpsConfig) -> Self {
+        let fix = Arc::new(Mutex::new(None));
+        let error = Arc::new(Mutex::new(None));
+        let failed = Arc::new(Mutex::new(None));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let (thread_fix, thread_error, thread_failed, thread_stop) =
+            (fix.clone(), error.clone(), failed.clone(), stop.clone());
+        let thread_config = config.clone();
+        thread::spawn(move || {
+            let result = connect(&thread_config)
+                .and_then(|reader| read_fixes(reader, &thread_fix, &thread_stop));
+            if let Err(e) = result {
+                *thread_error.lock().unwrap() = Some(e);
+                *thread_failed.lock().unwrap() = Some(now());
+            }
+        });
+
+        Self {
+            config: config.clone(),
+            fix,
+            error,
+            failed,
+            stop,
+        }
+    }
+
+    /// The latest fix unless it is stale
+    pub fn fix(&self) -> Option<Fix> {
+        let fix = (*self.fix.lock().unwrap())?;
+        (now().saturating_sub(fix.time) < STALE_FIX).then_some(fix)
+    }
+
+    pub fn take_error(&self) -> Option<io::Error> {
+        self.error.lock().unwrap().take()
+    }
+
+    /// Whether the receiver failed long enough ago to be opened again
+    pub fn retry_due(&self) -> bool {
+        self.failed
+            .lock()
+            .unwrap()
+            .is_some_and(|t| now().saturating_sub(t) >= RETRY_INTERVAL)
+    }
+}
+
+fn connect(config: &GpsConfig) -> io::Result<Box<dyn Read + Send>> {
+    match config.source {
+        GpsSource::Serial => Ok(Box::new(
+            serialport::new(&config.port, config.baud_rate)
+                .timeout(Duration::from_millis(200))
+                .open()?,
+        )),
+        GpsSource::Gpsd => {
+            let mut addresses = config.gpsd_address.to_socket_addrs()?;
+            let address = addresses
+                .next()
+                .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))?;
+            let mut stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)?;
+            stream.set_read_timeout(Some(Duration::from_millis(200)))?;
+            stream.write_all(b"?WATCH={\"enable\":true,\"nmea\":true}\n")?;
+            Ok(Box::new(stream))
+        }
+    }
+}
+
+fn read_fixes(
+    reader: Box<dyn Read + Send>,
+    fix: &Mutex<Option<Fix>>,
+    stop: &AtomicBool,
+) -> io::Result<()> {
+    let mut reader = BufReader::new(reader);
+    let mut line = String::new();
+    while !stop.load(Ordering::Relaxed) {
+        match reader.read_line(&mut line) {
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(_) => (),
+            // a timeout may leave a partial line, which is completed by the next read
+            Err(e) if is_timeout(&e) => continue,
+            Err(e) => return Err(e),
+        }
+        if line.ends_with('\n') {
+            if let Some(f) = parse_rmc(line.trim()) {
+                *fix.lock().unwrap() = Some(f);
+            }
+            line.clear();
+        }
+    }
+    Ok(())
+}
+
+impl Drop for Gps {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Parses the recommended minimum data sentence, e.g.
+/// `$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6A`
+fn parse_rmc(sentence: &str) -> Option<Fix> {
+    let sentence = sentence.split('*').next()?;
+    let mut fields = sentence.split(',');
+    if !fields.next()?.ends_with("RMC") {
+        return None;
+    }
+    let _utc = fields.next()?;
+    if fields.next()? != "A" {
+        return None;
+    }
+    let latitude = coordinate(fields.next()?, fields.next()?, 2)?;
+    let longitude = coordinate(fields.next()?, fields.next()?, 3)?;
+    let knots: f32 = fields.next()?.parse().ok()?;
+
+    Some(Fix {
+        time: now(),
+        latitude,
+        longitude,
+        speed: knots * 1.852,
+    })
+}
+
+/// Converts `ddmm.mmmm` into decimal degrees
+fn coordinate(value: &str, hemisphere: &str, degree_digits: usize) -> Option<f64> {
+    let degrees: f64 = value.get(..degree_digits)?.parse().ok()?;
+    let minutes: f64 = value.get(degree_digits..)?.parse().ok()?;
+    let coordinate = degrees + minutes / 60.0;
+    match hemisphere {
+        "N" | "E" => Some(coordinate),
+        "S" | "W" => Some(-coordinate),
+        _ => None,
+    }
+}
+
+fn is_timeout(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock
+    )
+}
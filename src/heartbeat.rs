@@ -0,0 +1,111 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use egui::{DragValue, Grid, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::tr;
+
+/// Sysfs interface of the GPIOs, e.g. on a Raspberry Pi
+const GPIO: &str = "/sys/class/gpio";
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct HeartbeatConfig {
+    pub enabled: bool,
+    /// Time in s between two beats
+    pub interval: u64,
+    /// Rewritten with the current Unix time in ms on every beat, skipped if empty
+    pub file: String,
+    /// Toggle a GPIO on every beat
+    pub gpio: bool,
+    /// BCM number of the GPIO
+    pub pin: u32,
+}
+
+impl Default for HeartbeatConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval: 5,
+            file: "heartbeat".into(),
+            gpio: false,
+            pin: 17,
+        }
+    }
+}
+
+impl HeartbeatConfig {
+    pub fn settings(&mut self, ui: &mut Ui, heartbeat: &Heartbeat) {
+        ui.checkbox(
+            &mut self.enabled,
+            tr("Send a heartbeat to an external watchdog"),
+        );
+        Grid::new("heartbeat_config").show(ui, |ui| {
+            ui.label(tr("Interval"));
+            ui.add(
+                DragValue::new(&mut self.interval)
+                    .clamp_range(1..=300)
+                    .suffix(" s"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Heartbeat file"));
+            ui.text_edit_singleline(&mut self.file);
+            ui.end_row();
+
+            ui.checkbox(&mut self.gpio, tr("Toggle GPIO"));
+            ui.add_enabled(self.gpio, DragValue::new(&mut self.pin).clamp_range(0..=53));
+            ui.end_row();
+        });
+        ui.label(tr("The beats stop when the dashboard hangs"));
+        if let Some(error) = &heartbeat.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+    }
+}
+
+/// Proves to a watchdog outside the dashboard that the main loop is still running, so it can
+/// power-cycle a logger that hung instead of silently losing the session
+#[derive(Default)]
+pub struct Heartbeat {
+    /// Unix time in ms of the last beat
+    last: u128,
+    level: bool,
+    /// The pin that was exported and set up as an output
+    exported: Option<u32>,
+    /// Error of the last beat
+    pub error: Option<String>,
+}
+
+impl Heartbeat {
+    pub fn update(&mut self, config: &HeartbeatConfig, time: u128) {
+        if !config.enabled || time.saturating_sub(self.last) < config.interval as u128 * 1000 {
+            return;
+        }
+        self.last = time;
+        self.error = self.beat(config, time).err().map(|e| e.to_string());
+    }
+
+    fn beat(&mut self, config: &HeartbeatConfig, time: u128) -> io::Result<()> {
+        if !config.file.is_empty() {
+            fs::write(&config.file, format!("{time}\n"))?;
+        }
+        if config.gpio {
+            let gpio = Path::new(GPIO).join(format!("gpio{}", config.pin));
+            if self.exported != Some(config.pin) {
+                if !gpio.exists() {
+                    fs::write(Path::new(GPIO).join("export"), config.pin.to_string())?;
+                }
+                // fails until udev adjusted the permissions of a freshly exported pin, the next
+                // beat tries again
+                fs::write(gpio.join("direction"), "out")?;
+                self.exported = Some(config.pin);
+            }
+            self.level = !self.level;
+            fs::write(gpio.join("value"), if self.level { "1" } else { "0" })?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,230 @@
+use std::ops::RangeInclusive;
+
+use egui::{DragValue, Grid, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::tr;
+use crate::keymap::Action;
+use crate::units;
+
+/// A named heatmap range, e.g. for racing, charging or winter conditions
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HeatmapProfile {
+    pub name: String,
+    /// Difference to the average that is shown with full color
+    pub delta: f32,
+}
+
+impl HeatmapProfile {
+    fn new(name: &str, delta: f32) -> Self {
+        Self {
+            name: name.into(),
+            delta,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Metric {
+    Voltage,
+    Temperature,
+}
+
+impl Metric {
+    fn name(self) -> &'static str {
+        match self {
+            Metric::Voltage => tr("Voltage"),
+            Metric::Temperature => tr("Temperature"),
+        }
+    }
+
+    fn range(self) -> RangeInclusive<f32> {
+        match self {
+            Metric::Voltage => 5.0..=1000.0,
+            Metric::Temperature => 0.5..=25.0,
+        }
+    }
+
+    fn speed(self) -> f32 {
+        match self {
+            Metric::Voltage => 1.0,
+            Metric::Temperature => 0.1,
+        }
+    }
+
+    /// A delta in mV or °C in the display unit
+    fn format(self, delta: f64) -> String {
+        match self {
+            Metric::Voltage => units::voltage(delta),
+            Metric::Temperature => units::temp_delta(delta),
+        }
+    }
+
+    /// A typed delta in the display unit converted back to mV or °C
+    fn parse(self, text: &str) -> Option<f64> {
+        let (unit, scale) = match self {
+            Metric::Voltage => (units::voltage_unit(), units::voltage_scale(1.0)),
+            Metric::Temperature => (units::temp_unit(), units::temp_delta_scale(1.0)),
+        };
+        let number: String = text
+            .trim()
+            .trim_end_matches(unit)
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        number.parse::<f64>().ok().map(|value| value / scale)
+    }
+
+    fn drag_value(self, delta: &mut f32) -> DragValue<'_> {
+        DragValue::new(delta)
+            .clamp_range(self.range())
+            .speed(self.speed())
+            .custom_formatter(move |value, _| self.format(value))
+            .custom_parser(move |text| self.parse(text))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct HeatmapConfig {
+    pub voltage: Vec<HeatmapProfile>,
+    pub temp: Vec<HeatmapProfile>,
+    pub active_voltage: usize,
+    pub active_temp: usize,
+    /// Compare each cell to the average of its side instead of the whole pack
+    pub relative: bool,
+    /// Compare each cell to its series neighbors instead, a bad interconnect stands out even if
+    /// the whole pack is drifting. Takes precedence over `relative` for the cell voltages.
+    pub neighbors: bool,
+    /// Color the cells by their estimated open circuit voltage, so the sag under load doesn't
+    /// hide or fake an imbalance
+    pub compensated: bool,
+}
+
+impl Default for HeatmapConfig {
+    fn default() -> Self {
+        Self {
+            voltage: vec![
+                HeatmapProfile::new("Race voltage", 100.0),
+                HeatmapProfile::new("Charging voltage", 20.0),
+            ],
+            temp: vec![
+                HeatmapProfile::new("Summer temperatures", 5.0),
+                HeatmapProfile::new("Winter temperatures", 10.0),
+            ],
+            active_voltage: 0,
+            active_temp: 0,
+            relative: false,
+            neighbors: false,
+            compensated: false,
+        }
+    }
+}
+
+impl HeatmapConfig {
+    pub fn voltage_delta(&self) -> f32 {
+        self.voltage
+            .get(self.active_voltage)
+            .map_or(100.0, |p| p.delta)
+    }
+
+    pub fn temp_delta(&self) -> f32 {
+        self.temp.get(self.active_temp).map_or(5.0, |p| p.delta)
+    }
+
+    /// Applies the single deltas of versions before the profiles to the first profiles
+    pub fn migrate(&mut self, voltage: Option<f32>, temp: Option<f32>, relative: Option<bool>) {
+        if let (Some(delta), Some(profile)) = (voltage, self.voltage.first_mut()) {
+            profile.delta = delta;
+        }
+        if let (Some(delta), Some(profile)) = (temp, self.temp.first_mut()) {
+            profile.delta = delta;
+        }
+        if let Some(relative) = relative {
+            self.relative = relative;
+        }
+    }
+
+    /// Commands switching to each of the profiles with their labels
+    pub fn actions(&self) -> Vec<(Action, String)> {
+        let voltage = self.voltage.iter().enumerate().map(|(i, p)| {
+            let action = Action::VoltageProfile(i as u8);
+            (action, format!("{}: {}", action.name(), p.name))
+        });
+        let temp = self.temp.iter().enumerate().map(|(i, p)| {
+            let action = Action::TempProfile(i as u8);
+            (action, format!("{}: {}", action.name(), p.name))
+        });
+        voltage.chain(temp).collect()
+    }
+
+    fn profiles(&mut self, metric: Metric) -> (&mut Vec<HeatmapProfile>, &mut usize) {
+        match metric {
+            Metric::Voltage => (&mut self.voltage, &mut self.active_voltage),
+            Metric::Temperature => (&mut self.temp, &mut self.active_temp),
+        }
+    }
+
+    /// Profile selection and fine tuning of the active profiles
+    pub fn menu(&mut self, ui: &mut Ui) {
+        for metric in [Metric::Voltage, Metric::Temperature] {
+            ui.label(metric.name());
+            let (profiles, active) = self.profiles(metric);
+            for (i, profile) in profiles.iter().enumerate() {
+                ui.radio_value(active, i, &profile.name);
+            }
+            if let Some(profile) = profiles.get_mut(*active) {
+                ui.add(metric.drag_value(&mut profile.delta));
+            }
+            ui.separator();
+        }
+        ui.checkbox(&mut self.relative, tr("Relative heatmap"));
+        ui.checkbox(&mut self.neighbors, tr("Compare to neighbors"));
+        ui.checkbox(&mut self.compensated, tr("Compensate voltage sag"));
+    }
+
+    pub fn settings(&mut self, ui: &mut Ui) {
+        ui.checkbox(&mut self.relative, tr("Relative heatmap"));
+        ui.checkbox(&mut self.neighbors, tr("Compare to neighbors"));
+        ui.checkbox(&mut self.compensated, tr("Compensate voltage sag"));
+        for metric in [Metric::Voltage, Metric::Temperature] {
+            ui.label(metric.name());
+            let (profiles, active) = self.profiles(metric);
+            let mut remove = None;
+            Grid::new(("heatmap_profiles", metric.name())).show(ui, |ui| {
+                for (i, profile) in profiles.iter_mut().enumerate() {
+                    ui.radio_value(active, i, "");
+                    ui.text_edit_singleline(&mut profile.name);
+                    ui.add(metric.drag_value(&mut profile.delta));
+                    if ui.button("🗑").clicked() {
+                        remove = Some(i);
+                    }
+                    ui.end_row();
+                }
+            });
+            if let Some(i) = remove {
+                profiles.remove(i);
+                if *active >= i && *active > 0 {
+                    *active -= 1;
+                }
+            }
+            if ui.button(tr("Add profile")).clicked() {
+                let delta = profiles
+                    .get(*active)
+                    .map_or(*metric.range().start(), |p| p.delta);
+                profiles.push(HeatmapProfile::new(tr("New profile"), delta));
+            }
+        }
+    }
+}
+
+/// Average voltage of the cells before and after `index` in the series connection
+pub fn neighbor_average(cells: &[u16], index: usize) -> Option<f32> {
+    let neighbors: Vec<f32> = [index.checked_sub(1), Some(index + 1)]
+        .into_iter()
+        .flatten()
+        .filter_map(|i| cells.get(i))
+        .map(|v| *v as f32)
+        .collect();
+    (!neighbors.is_empty()).then(|| neighbors.iter().sum::<f32>() / neighbors.len() as f32)
+}
@@ -0,0 +1,227 @@
+use std::collections::VecDeque;
+use std::mem::size_of;
+
+use egui::{DragValue, Grid, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::api::{Data, TempStats, VoltageStats};
+use crate::i18n::tr;
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryConfig {
+    /// Duration in min for which every sample is kept
+    pub full_rate: u64,
+    /// Interval in s between the downsampled samples older than `full_rate`
+    pub downsample_interval: u64,
+    /// Memory in MB the history may use, the oldest samples are dropped beyond it
+    pub memory_limit: usize,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            full_rate: 10,
+            downsample_interval: 10,
+            memory_limit: 64,
+        }
+    }
+}
+
+impl HistoryConfig {
+    pub fn settings(&mut self, ui: &mut Ui, history: &History) {
+        Grid::new("history_config").show(ui, |ui| {
+            ui.label(tr("Full rate"));
+            ui.add(
+                DragValue::new(&mut self.full_rate)
+                    .clamp_range(1..=120)
+                    .suffix(" min"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Downsampled interval"));
+            ui.add(
+                DragValue::new(&mut self.downsample_interval)
+                    .clamp_range(1..=600)
+                    .suffix(" s"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Memory limit"));
+            ui.add(
+                DragValue::new(&mut self.memory_limit)
+                    .clamp_range(8..=4096)
+                    .suffix(" MB"),
+            );
+            ui.end_row();
+        });
+        let (recent, archive) = (history.recent.len(), history.archive.len());
+        let mb = history.bytes as f64 / 1_000_000.0;
+        ui.label(format!(
+            "{recent} {}, {archive} {}, {mb:.1} MB",
+            tr("samples at full rate"),
+            tr("downsampled")
+        ));
+    }
+}
+
+pub struct Sample {
+    /// Unix time in ms
+    pub time: u128,
+    // in V
+    pub voltage: f32,
+    // in mA
+    pub current: f32,
+    // in mV
+    pub min_voltage: u16,
+    pub max_voltage: u16,
+    pub cell_voltage: Vec<u16>,
+    // in °C
+    pub temp: Vec<f32>,
+    pub left: Side,
+    pub right: Side,
+    /// Values of the derived channels
+    pub derived: Vec<Option<f64>>,
+}
+
+/// Statistics of one accumulator container
+pub struct Side {
+    // in mV
+    pub avg_voltage: u16,
+    pub delta_voltage: u16,
+    // in °C
+    pub avg_temp: f32,
+    pub max_temp: f32,
+}
+
+/// Samples of the session in two tiers: every sample of the last minutes and a downsampled
+/// archive of everything older, bounded by the memory limit
+#[derive(Default)]
+pub struct History {
+    archive: VecDeque<Sample>,
+    recent: VecDeque<Sample>,
+    /// Estimated memory used by the samples
+    bytes: usize,
+}
+
+impl History {
+    pub fn push(
+        &mut self,
+        config: &HistoryConfig,
+        data: &Data,
+        derived: Vec<Option<f64>>,
+        time: u128,
+    ) {
+        let sample = Sample {
+            time,
+            voltage: data.main.voltage,
+            current: data.main.current,
+            min_voltage: data.ucell.overall.min_voltage,
+            max_voltage: data.ucell.overall.max_voltage,
+            cell_voltage: data.ucell.cell_voltage.clone(),
+            temp: data.tcell.temp.clone(),
+            left: Side::new(&data.ucell.left, &data.tcell.left),
+            right: Side::new(&data.ucell.right, &data.tcell.right),
+            derived,
+        };
+        self.bytes += sample.size();
+        self.recent.push_back(sample);
+
+        // move samples leaving the full rate window to the archive, keeping one per interval
+        let full_rate_start = time.saturating_sub(config.full_rate as u128 * 60_000);
+        let interval = config.downsample_interval as u128 * 1000;
+        while self
+            .recent
+            .front()
+            .is_some_and(|s| s.time < full_rate_start)
+        {
+            let sample = self.recent.pop_front().unwrap();
+            let keep = self
+                .archive
+                .back()
+                .is_none_or(|last| sample.time >= last.time + interval);
+            if keep {
+                self.archive.push_back(sample);
+            } else {
+                self.bytes -= sample.size();
+            }
+        }
+
+        let limit = config.memory_limit * 1_000_000;
+        while self.bytes > limit && self.len() > 1 {
+            let sample = match self.archive.pop_front() {
+                Some(sample) => sample,
+                None => self.recent.pop_front().unwrap(),
+            };
+            self.bytes -= sample.size();
+        }
+    }
+
+    /// All samples from the oldest to the newest
+    pub fn samples(&self) -> impl DoubleEndedIterator<Item = &Sample> + Clone {
+        self.archive.iter().chain(&self.recent)
+    }
+
+    pub fn first(&self) -> Option<&Sample> {
+        self.archive.front().or(self.recent.front())
+    }
+
+    pub fn last(&self) -> Option<&Sample> {
+        self.recent.back().or(self.archive.back())
+    }
+
+    pub fn len(&self) -> usize {
+        self.archive.len() + self.recent.len()
+    }
+
+    /// Samples of the last `duration` ms
+    pub fn window(&self, duration: u128) -> impl DoubleEndedIterator<Item = &Sample> {
+        let end = self.last().map_or(0, |s| s.time);
+        let start = end.saturating_sub(duration);
+        let archive = self.archive.partition_point(|s| s.time < start);
+        let recent = self.recent.partition_point(|s| s.time < start);
+        self.archive
+            .range(archive..)
+            .chain(self.recent.range(recent..))
+    }
+}
+
+impl Sample {
+    /// Estimated memory used by the sample including its vectors
+    fn size(&self) -> usize {
+        size_of::<Self>()
+            + self.cell_voltage.capacity() * size_of::<u16>()
+            + self.temp.capacity() * size_of::<f32>()
+            + self.derived.capacity() * size_of::<Option<f64>>()
+    }
+}
+
+impl Side {
+    fn new(voltage: &VoltageStats, temp: &TempStats) -> Self {
+        Self {
+            avg_voltage: voltage.avg_voltage,
+            delta_voltage: voltage.delta_voltage,
+            avg_temp: temp.avg_temp,
+            max_temp: temp.max_temp,
+        }
+    }
+}
+
+/// Least squares slope of `y` over `x`
+pub fn slope(points: impl Iterator<Item = (f64, f64)>) -> Option<f64> {
+    let mut n = 0.0;
+    let (mut sum_x, mut sum_y, mut sum_xx, mut sum_xy) = (0.0, 0.0, 0.0, 0.0);
+    for (x, y) in points {
+        n += 1.0;
+        sum_x += x;
+        sum_y += y;
+        sum_xx += x * x;
+        sum_xy += x * y;
+    }
+
+    let denominator = n * sum_xx - sum_x * sum_x;
+    if n < 2.0 || denominator.abs() < f64::EPSILON {
+        return None;
+    }
+    Some((n * sum_xy - sum_x * sum_y) / denominator)
+}
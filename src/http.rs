@@ -0,0 +1,194 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, ToSocketAddrs};
+use std::thread::{self, JoinHandle};
+
+use egui::{Color32, ComboBox, Grid, RichText, TextEdit, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::api::{Connection, Format};
+use crate::i18n::tr;
+
+/// Settings of the requests sent to the BMS web interface
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpConfig {
+    /// Extra headers sent with every request, e.g. an API key or a cookie
+    pub headers: Vec<(String, String)>,
+    /// Empty to connect directly
+    pub proxy: String,
+    /// Request the next sample while the previous one is still being received, so fast poll
+    /// rates aren't limited by the latency of the BMS
+    pub pipelining: bool,
+    /// Layout of the pages if detecting it from the firmware version fails
+    pub format: Option<Format>,
+}
+
+impl HttpConfig {
+    pub fn connection(&self, ip: &str) -> Connection {
+        Connection {
+            ip: ip.trim().trim_end_matches('/').to_string(),
+            headers: self
+                .headers
+                .iter()
+                .filter(|(name, _)| !name.trim().is_empty())
+                .map(|(name, value)| (name.trim().to_string(), value.clone()))
+                .collect(),
+            proxy: Some(self.proxy.trim())
+                .filter(|p| !p.is_empty())
+                .map(str::to_string),
+        }
+    }
+
+    pub fn settings(&mut self, ui: &mut Ui) {
+        let name = |format: Option<Format>| format.map_or(tr("Detect automatically"), Format::name);
+        ui.horizontal(|ui| {
+            ui.label(tr("Firmware format"));
+            ComboBox::from_id_source("http_format")
+                .selected_text(name(self.format))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.format, None, name(None));
+                    for format in Format::ALL {
+                        ui.selectable_value(&mut self.format, Some(format), name(Some(format)));
+                    }
+                });
+        });
+        ui.checkbox(&mut self.pipelining, tr("Pipeline requests"))
+            .on_hover_text(tr("Requests the next sample while the previous one is still being received, only at poll rates below 1 s"));
+        ui.horizontal(|ui| {
+            ui.label(tr("Proxy"));
+            ui.add(
+                TextEdit::singleline(&mut self.proxy)
+                    .hint_text("socks5://host:1080")
+                    .desired_width(200.0),
+            );
+        });
+        ui.label(tr("Request headers"));
+        let mut remove = None;
+        Grid::new("http_headers").show(ui, |ui| {
+            for (i, (name, value)) in self.headers.iter_mut().enumerate() {
+                ui.add(
+                    TextEdit::singleline(name)
+                        .hint_text(tr("Name"))
+                        .desired_width(120.0),
+                );
+                ui.add(
+                    TextEdit::singleline(value)
+                        .hint_text(tr("Value"))
+                        .desired_width(200.0)
+                        .password(true),
+                );
+                if ui.button("🗑").clicked() {
+                    remove = Some(i);
+                }
+                ui.end_row();
+            }
+        });
+        if let Some(i) = remove {
+            self.headers.remove(i);
+        }
+        if ui.button(tr("Add header")).clicked() {
+            self.headers.push(Default::default());
+        }
+    }
+}
+
+/// Host and port of a BMS address like `http://192.168.0.200` or `http://[fd00::1]:8080`
+pub fn parse_address(address: &str) -> Result<(String, u16), String> {
+    let address = address.trim().trim_end_matches('/');
+    let (rest, default_port) = if let Some(rest) = address.strip_prefix("http://") {
+        (rest, 80)
+    } else if let Some(rest) = address.strip_prefix("https://") {
+        (rest, 443)
+    } else {
+        return Err(tr("The address has to start with http:// or https://").to_string());
+    };
+    let authority = rest.split('/').next().unwrap_or_default();
+    if authority.is_empty() {
+        return Err(tr("The host is missing").to_string());
+    }
+
+    let (host, port) = if let Some(literal) = authority.strip_prefix('[') {
+        let Some((host, port)) = literal.split_once(']') else {
+            return Err(tr("The closing bracket of the IPv6 address is missing").to_string());
+        };
+        if host.parse::<Ipv6Addr>().is_err() {
+            return Err(format!("{}: {host}", tr("Invalid IPv6 address")));
+        }
+        (host, port)
+    } else if authority.matches(':').count() > 1 {
+        return Err(tr("IPv6 addresses have to be enclosed in brackets").to_string());
+    } else {
+        let host = authority.split(':').next().unwrap_or_default();
+        (host, &authority[host.len()..])
+    };
+
+    let port = match port {
+        "" => default_port,
+        port => match port.strip_prefix(':').and_then(|p| p.parse().ok()) {
+            Some(port) => port,
+            None => return Err(format!("{}: {port}", tr("Invalid port"))),
+        },
+    };
+
+    let is_ipv4 = host.chars().all(|c| c.is_ascii_digit() || c == '.');
+    if is_ipv4 && host.parse::<Ipv4Addr>().is_err() {
+        return Err(format!("{}: {host}", tr("Invalid IPv4 address")));
+    }
+    let valid_hostname = host.split('.').all(|label| {
+        !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    });
+    if !authority.starts_with('[') && !valid_hostname {
+        return Err(format!("{}: {host}", tr("Invalid hostname")));
+    }
+    Ok((host.to_string(), port))
+}
+
+/// Validates the address field while typing, hostnames are resolved in the background
+#[derive(Default)]
+pub struct AddressCheck {
+    address: String,
+    result: Option<Result<(), String>>,
+    resolving: Option<JoinHandle<Result<(), String>>>,
+}
+
+impl AddressCheck {
+    pub fn show(&mut self, ui: &mut Ui, address: &str) {
+        if address != self.address {
+            self.address = address.to_string();
+            self.resolving = None;
+            self.result = match parse_address(address) {
+                Ok((host, port)) if host.parse::<IpAddr>().is_err() => {
+                    self.resolving = Some(thread::spawn(move || resolve(&host, port)));
+                    None
+                }
+                Ok(_) => Some(Ok(())),
+                Err(e) => Some(Err(e)),
+            };
+        }
+        if self.resolving.as_ref().is_some_and(|r| r.is_finished()) {
+            let result = self.resolving.take().unwrap().join();
+            self.result = Some(result.unwrap_or_else(|_| Err(tr("Unexpected error").into())));
+        }
+
+        match &self.result {
+            None => {
+                ui.spinner().on_hover_text(tr("Resolving hostname"));
+            }
+            Some(Ok(())) => {
+                ui.label(RichText::new("✔").color(Color32::GREEN))
+                    .on_hover_text(tr("Valid address"));
+            }
+            Some(Err(e)) => {
+                ui.label(RichText::new("✖").color(Color32::RED))
+                    .on_hover_text(e);
+            }
+        }
+    }
+}
+
+fn resolve(host: &str, port: u16) -> Result<(), String> {
+    match (host, port).to_socket_addrs() {
+        Ok(addresses) if addresses.len() > 0 => Ok(()),
+        Ok(_) => Err(format!("{}: {host}", tr("Hostname not found"))),
+        Err(e) => Err(format!("{}: {e}", tr("Hostname not found"))),
+    }
+}
@@ -0,0 +1,819 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use egui::{ComboBox, Ui};
+use serde::{Deserialize, Serialize};
+
+static CURRENT: AtomicU8 = AtomicU8::new(Language::English as u8);
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::German];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::German => "Deutsch",
+        }
+    }
+
+    pub fn current() -> Self {
+        match CURRENT.load(Ordering::Relaxed) {
+            1 => Language::German,
+            _ => Language::English,
+        }
+    }
+
+    pub fn set_current(self) {
+        CURRENT.store(self as u8, Ordering::Relaxed);
+    }
+
+    pub fn settings(&mut self, ui: &mut Ui) {
+        ComboBox::from_label(tr("Language"))
+            .selected_text(self.name())
+            .show_ui(ui, |ui| {
+                for lang in Language::ALL {
+                    ui.selectable_value(self, lang, lang.name());
+                }
+            });
+    }
+}
+
+/// Translates an english UI text into the current language, falling back to the english text
+/// if no translation exists.
+pub fn tr(text: &'static str) -> &'static str {
+    match Language::current() {
+        Language::English => text,
+        Language::German => german(text).unwrap_or(text),
+    }
+}
+
+fn german(text: &str) -> Option<&'static str> {
+    let translated = match text {
+        // top bar
+        "Poll rate" => "Abfragerate",
+        "Interface" => "Schnittstelle",
+        "Port" => "Port",
+        "Relative heatmap" => "Relative Heatmap",
+        "Compare to neighbors" => "Mit Nachbarzellen vergleichen",
+        "View" => "Ansicht",
+        "Pack" => "Akku",
+        "Slaves" => "Slaves",
+        "Charging" => "Laden",
+        "BMS info" => "BMS-Info",
+        "Internal resistance" => "Innenwiderstand",
+        "Left/right" => "Links/rechts",
+        "Stats panel" => "Statistik",
+        "Temperatures" => "Temperaturen",
+        "Stacks" => "Stacks",
+        "Temperature height" => "Höhe Temperaturen",
+        "Reset layout" => "Layout zurücksetzen",
+        "Settings" => "Einstellungen",
+        "cancel" => "abbrechen",
+        "Paused" => "Pausiert",
+        "Saved" => "Gespeichert",
+        "Error saving screenshot" => "Fehler beim Speichern des Screenshots",
+        "● REC" => "● AUFN",
+        "Recording to" => "Aufzeichnung nach",
+        "Error recording data" => "Fehler bei der Aufzeichnung",
+
+        // settings
+        "Language" => "Sprache",
+        "Appearance" => "Darstellung",
+        "Theme" => "Farbschema",
+        "System" => "System",
+        "Dark" => "Dunkel",
+        "Light" => "Hell",
+        "Custom colors" => "Eigene Farben",
+        "Background" => "Hintergrund",
+        "Panel" => "Panel",
+        "Accent" => "Akzent",
+        "Reset colors" => "Farben zurücksetzen",
+        "Font sizes" => "Schriftgrößen",
+        "Automatic cell sizes" => "Automatische Zellgrößen",
+        "Cell values" => "Zellwerte",
+        "Cell indices" => "Zellnummern",
+        "Side panel" => "Seitenleiste",
+        "Estimate SoC from open-circuit voltage" => "Ladezustand aus Ruhespannung schätzen",
+        "Rest current" => "Ruhestrom",
+        "Rest time" => "Ruhezeit",
+        "OCV table" => "Ruhespannungstabelle",
+        "Add point" => "Punkt hinzufügen",
+        "Reset table" => "Tabelle zurücksetzen",
+        "Temperature sensors" => "Temperatursensoren",
+        "Show temperatures as cell borders" => "Temperaturen als Zellrahmen anzeigen",
+        "Sensor mapping" => "Sensorzuordnung",
+        "Sensor" => "Sensor",
+        "First cell" => "Erste Zelle",
+        "Last cell" => "Letzte Zelle",
+        "Dead" => "Defekt",
+        "Reset mapping" => "Zuordnung zurücksetzen",
+        "Prediction" => "Prognose",
+        "Show prediction" => "Prognose anzeigen",
+        "Usable pack energy" => "Nutzbare Akkuenergie",
+        "Cutoff voltage" => "Abschaltspannung",
+        "Averaging window" => "Mittelungsfenster",
+        "Weakest cell trend" => "Trend schwächste Zelle",
+        "Warn about the weakest cell trend" => "Vor Trend der schwächsten Zelle warnen",
+        "Alarm voltage" => "Alarmspannung",
+        "Tracking window" => "Beobachtungsfenster",
+        "Warning horizon" => "Warnhorizont",
+        "Anomaly detection" => "Anomalieerkennung",
+        "Detect diverging cells" => "Abweichende Zellen erkennen",
+        "Rolling window" => "Gleitendes Fenster",
+        "Minimum z-score" => "Minimaler z-Wert",
+        "Minimum growth" => "Minimaler Anstieg",
+        "Insulation monitoring" => "Isolationsüberwachung",
+        "Warning threshold" => "Warnschwelle",
+        "Charge current" => "Ladestrom",
+        "Full cell voltage" => "Ladeschlussspannung",
+        "Balancing threshold" => "Balancing-Schwelle",
+        "Trend window" => "Trendfenster",
+        "Recording" => "Aufzeichnung",
+        "Record data" => "Daten aufzeichnen",
+        "Log directory" => "Log-Verzeichnis",
+        "State of health" => "Alterungszustand",
+        "Discharge current" => "Entladestrom",
+        "Cycle end time" => "Zyklusende nach",
+        "Minimum cycle charge" => "Minimale Zyklusladung",
+        "Capture precharge" => "Vorladung aufzeichnen",
+        "Capture duration" => "Aufzeichnungsdauer",
+        "Precharge target" => "Vorladeziel",
+        "Maximum precharge time" => "Maximale Vorladezeit",
+
+        // precharge
+        "No precharge captured yet" => "Noch keine Vorladung aufgezeichnet",
+        "Time to target" => "Zeit bis zum Ziel",
+        "Precharge too slow" => "Vorladung zu langsam",
+        "Precharge target not reached" => "Vorladeziel nicht erreicht",
+        "DC link voltage (V)" => "Zwischenkreisspannung (V)",
+
+        // prediction
+        "Remaining energy" => "Restenergie",
+        "Power" => "Leistung",
+        "Time at current pace" => "Zeit bei aktuellem Verbrauch",
+        "Time to cutoff" => "Zeit bis Abschaltung",
+
+        // keyboard shortcuts
+        "Keyboard shortcuts" => "Tastenkürzel",
+        "Show keyboard shortcuts" => "Tastenkürzel anzeigen",
+        "Toggle safe mode" => "Sicherer Modus umschalten",
+        "Pause polling" => "Abfrage pausieren",
+        "Driver mode" => "Fahrermodus",
+        "Screenshot" => "Screenshot",
+        "Press keys..." => "Tasten drücken...",
+        "Reset shortcuts" => "Tastenkürzel zurücksetzen",
+        "Already used by" => "Bereits belegt durch",
+        "Command palette" => "Befehlspalette",
+        "Search commands..." => "Befehle suchen...",
+        "Toggle stats panel" => "Statistik ein-/ausblenden",
+        "Toggle temperatures" => "Temperaturen ein-/ausblenden",
+        "Toggle stacks" => "Stacks ein-/ausblenden",
+        "Start/stop recording" => "Aufzeichnung starten/stoppen",
+        "Show pack view" => "Akkuansicht anzeigen",
+        "Show slave view" => "Slave-Ansicht anzeigen",
+        "Show charging view" => "Ladeansicht anzeigen",
+        "Show BMS info" => "BMS-Info anzeigen",
+        "Show internal resistance" => "Innenwiderstand anzeigen",
+        "Show left/right comparison" => "Links/rechts-Vergleich anzeigen",
+
+        // charging
+        "The pack is charging." => "Der Akku wird geladen.",
+        "Dismiss" => "Ausblenden",
+        "Delta trend" => "Delta-Trend",
+        "Estimated time to full" => "Geschätzte Zeit bis voll",
+        "Estimated time to balance" => "Geschätzte Zeit bis ausgeglichen",
+        "Balancing activity (estimated from the cell voltages)" => {
+            "Balancing-Aktivität (aus den Zellspannungen geschätzt)"
+        }
+
+        // slaves
+        "Slave" => "Slave",
+        "Min" => "Min.",
+        "Avg" => "Mittel",
+        "Max" => "Max.",
+        "Delta" => "Delta",
+        "Communication" => "Kommunikation",
+        "OK" => "OK",
+        "invalid values" => "ungültige Werte",
+        "No updates" => "Keine Aktualisierung",
+
+        // state of health
+        "No cycles recorded yet, enable recording to track cycles" => {
+            "Noch keine Zyklen aufgezeichnet, Aufzeichnung aktivieren um Zyklen zu erfassen"
+        }
+        "Per cycle" => "Pro Zyklus",
+        "Imbalance (mV)" => "Ungleichgewicht (mV)",
+        "Lowest cell capacity (Ah)" => "Kleinste Zellkapazität (Ah)",
+        "Cell capacity of the last cycle" => "Zellkapazität des letzten Zyklus",
+        "Capacity (Ah)" => "Kapazität (Ah)",
+
+        // indicators
+        "Shutdown circuit" => "Abschaltkreis",
+        "Precharge" => "Vorladung",
+        "TS active" => "HV aktiv",
+
+        // BMS info
+        "Reload" => "Neu laden",
+        "Reported:" => "Gemeldet:",
+        "Firmware version" => "Firmware-Version",
+        "Cells" => "Zellen",
+        "Maximum discharge current" => "Maximaler Entladestrom",
+        "Maximum charge current" => "Maximaler Ladestrom",
+        "Minimum cell voltage" => "Minimale Zellspannung",
+        "Maximum cell voltage" => "Maximale Zellspannung",
+        "Maximum cell temperature" => "Maximale Zelltemperatur",
+
+        // control
+        "Control" => "Steuerung",
+        "Unlock commands" => "Befehle entsperren",
+        "Enable balancing" => "Balancing aktivieren",
+        "Disable balancing" => "Balancing deaktivieren",
+        "Reset latched errors" => "Gespeicherte Fehler zurücksetzen",
+        "Confirm command" => "Befehl bestätigen",
+        "Send command" => "Befehl senden",
+        "Send" => "Senden",
+        "sent" => "gesendet",
+        "failed" => "fehlgeschlagen",
+        "Error logging command" => "Fehler beim Protokollieren des Befehls",
+
+        // gps
+        "Use GPS" => "GPS verwenden",
+        "Source" => "Quelle",
+        "Serial port" => "Serielle Schnittstelle",
+        "Baud rate" => "Baudrate",
+        "Address" => "Adresse",
+        "GPS error" => "GPS-Fehler",
+        "Speed" => "Geschwindigkeit",
+
+        // laps
+        "Laps" => "Runden",
+        "Lap" => "Runde",
+        "Mark lap" => "Runde markieren",
+        "Set finish line here" => "Ziellinie hier setzen",
+        "Clear laps" => "Runden löschen",
+        "Finish line" => "Ziellinie",
+        "Finish line radius" => "Radius Ziellinie",
+        "Minimum lap time" => "Minimale Rundenzeit",
+        "Time" => "Zeit",
+        "Energy" => "Energie",
+        "current" => "aktuell",
+
+        // sessions
+        "Session" => "Sitzung",
+        "No session" => "Keine Sitzung",
+        "Name" => "Name",
+        "Car" => "Fahrzeug",
+        "Driver" => "Fahrer",
+        "Notes" => "Notizen",
+        "Save" => "Speichern",
+        "End session" => "Sitzung beenden",
+        "New session" => "Neue Sitzung",
+        "Start session" => "Sitzung starten",
+        "Continue without session" => "Ohne Sitzung fortfahren",
+        "Previous sessions" => "Frühere Sitzungen",
+        "Resume" => "Fortsetzen",
+        "Session saved" => "Sitzung gespeichert",
+        "Error saving session" => "Fehler beim Speichern der Sitzung",
+
+        // markers
+        "Add marker" => "Markierung setzen",
+        "Driver message 1" => "Fahrernachricht 1",
+        "Driver message 2" => "Fahrernachricht 2",
+        "Driver message 3" => "Fahrernachricht 3",
+        "Driver message 4" => "Fahrernachricht 4",
+        "Driver message 5" => "Fahrernachricht 5",
+        "Optional description" => "Optionale Beschreibung",
+        "Error saving marker" => "Fehler beim Speichern der Markierung",
+
+        // replay
+        "Replay" => "Wiedergabe",
+        "Replay recording" => "Aufzeichnung wiedergeben",
+        "No recordings found" => "Keine Aufzeichnungen gefunden",
+        "Stop replay" => "Wiedergabe beenden",
+        "Bookmark" => "Lesezeichen",
+        "Bookmarks" => "Lesezeichen",
+        "Clear loop" => "Schleife löschen",
+        "Error saving bookmarks" => "Fehler beim Speichern der Lesezeichen",
+        "Not a recording" => "Keine Aufzeichnung",
+        "Error loading recording" => "Fehler beim Laden der Aufzeichnung",
+
+        // internal resistance
+        "Window" => "Fenster",
+        "Pack resistance" => "Akkuwiderstand",
+        "Weakest cell resistance" => "Widerstand schwächste Zelle",
+        "Pack voltage (V)" => "Akkuspannung (V)",
+        "Min cell voltage (mV)" => "Min. Zellspannung (mV)",
+        "Fit" => "Ausgleichsgerade",
+
+        // left/right comparison
+        "Left" => "Links",
+        "Right" => "Rechts",
+        "Difference" => "Differenz",
+        "Left avg" => "Links Mittel",
+        "Right avg" => "Rechts Mittel",
+        "Left delta" => "Links Delta",
+        "Right delta" => "Rechts Delta",
+        "Left max" => "Links Max",
+        "Right max" => "Rechts Max",
+
+        "Highlight changed cells" => "Geänderte Zellen hervorheben",
+
+        // smoothing
+        "Smoothing" => "Glättung",
+        "Moving average window" => "Fenster gleitender Mittelwert",
+        " samples" => " Messwerte",
+        "Smooth pack view" => "Akkuansicht glätten",
+        "Smooth slave view" => "Slave-Ansicht glätten",
+        "Smooth driver mode" => "Fahrermodus glätten",
+
+        // outliers
+        "Outliers" => "Ausreißer",
+        "outliers" => "Ausreißer",
+        "Below alarm voltage" => "Unter Alarmspannung",
+        "Diverging" => "Driftend",
+        "Alarm projected" => "Alarm prognostiziert",
+        "No cells were flagged" => "Keine Zellen markiert",
+        "Acknowledge all" => "Alle quittieren",
+        "Acknowledge" => "Quittieren",
+        "ago" => "her",
+        "Reason" => "Grund",
+        "First" => "Erstmals",
+        "Last" => "Zuletzt",
+        "Count" => "Anzahl",
+
+        // cell detail
+        "Temperature sensor" => "Temperatursensor",
+        "Temperature" => "Temperatur",
+        "Export CSV" => "CSV exportieren",
+        "Exported to" => "Exportiert nach",
+        "Error exporting cell" => "Fehler beim Exportieren der Zelle",
+        "No recording of the cell, exported the downsampled in-memory history to" => {
+            "Keine Aufzeichnung der Zelle, der reduzierte Verlauf im Speicher wurde exportiert nach"
+        }
+
+        // live streaming
+        "Live streaming" => "Live-Übertragung",
+        "Push samples to a server" => "Messwerte an einen Server senden",
+        "Batch size" => "Stapelgröße",
+        "Maximum delay" => "Maximale Verzögerung",
+        "Retries" => "Wiederholungen",
+
+        // BMS requests
+        "BMS requests" => "BMS-Anfragen",
+        "Request headers" => "Anfrage-Header",
+        "Pipeline requests" => "Anfragen überlappen",
+        "BMS uptime" => "BMS-Laufzeit",
+        "Firmware format" => "Firmware-Format",
+        "Detect automatically" => "Automatisch erkennen",
+        "Current firmware" => "Aktuelle Firmware",
+        "Firmware before 2.0" => "Firmware vor 2.0",
+        "Requests the next sample while the previous one is still being received, only at poll rates below 1 s" => {
+            "Fordert den nächsten Messwert an, während der vorherige noch empfangen wird, nur bei Abfrageraten unter 1 s"
+        }
+        "Value" => "Wert",
+        "Add header" => "Header hinzufügen",
+        "Proxy" => "Proxy",
+        "The address has to start with http:// or https://" => {
+            "Die Adresse muss mit http:// oder https:// beginnen"
+        }
+        "The host is missing" => "Der Host fehlt",
+        "The closing bracket of the IPv6 address is missing" => {
+            "Die schließende Klammer der IPv6-Adresse fehlt"
+        }
+        "Invalid IPv6 address" => "Ungültige IPv6-Adresse",
+        "IPv6 addresses have to be enclosed in brackets" => {
+            "IPv6-Adressen müssen in eckigen Klammern stehen"
+        }
+        "Invalid port" => "Ungültiger Port",
+        "Invalid IPv4 address" => "Ungültige IPv4-Adresse",
+        "Invalid hostname" => "Ungültiger Hostname",
+        "Resolving hostname" => "Hostname wird aufgelöst",
+        "Valid address" => "Gültige Adresse",
+        "Hostname not found" => "Hostname nicht gefunden",
+
+        // network diagnostics
+        "Network diagnostics" => "Netzwerkdiagnose",
+        "Run test" => "Test starten",
+        "The connection test bypasses the proxy" => "Der Verbindungstest umgeht den Proxy",
+        "Test" => "Test",
+        "Diagnose" => "Diagnose",
+        "Status" => "Status",
+        "Latency" => "Latenz",
+        "Result" => "Ergebnis",
+        "TCP connection" => "TCP-Verbindung",
+
+        "Save a screenshot every" => "Screenshot speichern alle",
+
+        // log rotation
+        "Maximum file size" => "Maximale Dateigröße",
+        "Maximum file duration" => "Maximale Dateidauer",
+        "Compress completed files" => "Abgeschlossene Dateien komprimieren",
+        "Compression" => "Komprimierung",
+        "Error finishing recording" => "Fehler beim Abschließen der Aufzeichnung",
+        "Convert completed files to Parquet" => "Abgeschlossene Dateien in Parquet umwandeln",
+        "Export completed files to MoTeC CSV" => "Abgeschlossene Dateien als MoTeC-CSV exportieren",
+        "UTC offset of the ECU logs" => "UTC-Versatz der ECU-Logs",
+        "Export MoTeC CSV" => "MoTeC-CSV exportieren",
+        "Error exporting recording" => "Fehler beim Exportieren der Aufzeichnung",
+
+        // window
+        "Always on top" => "Immer im Vordergrund",
+        "Minimize instead of closing" => "Minimieren statt schließen",
+        "Polling and recording continue in the background" => {
+            "Abfrage und Aufzeichnung laufen im Hintergrund weiter"
+        }
+        "Quit" => "Beenden",
+
+        // launch
+        "Launch" => "Programmstart",
+        "Start minimized" => "Minimiert starten",
+        "Start recording on launch" => "Aufzeichnung beim Start beginnen",
+        "The session picker is skipped when either option is enabled" => {
+            "Die Sitzungsauswahl wird übersprungen, wenn eine der Optionen aktiv ist"
+        }
+
+        // heatmap
+        "Heatmap" => "Heatmap",
+        "Add profile" => "Profil hinzufügen",
+        "New profile" => "Neues Profil",
+        "Voltage heatmap profile" => "Spannungs-Heatmap-Profil",
+        "Temperature heatmap profile" => "Temperatur-Heatmap-Profil",
+
+        // cell numbering
+        "Cell numbering" => "Zellnummerierung",
+        "Show physical cell numbers" => "Physische Zellnummern anzeigen",
+        "Toggle physical cell numbers" => "Physische Zellnummern umschalten",
+        "Mapping file" => "Zuordnungsdatei",
+        "Load" => "Laden",
+        "cells mapped" => "Zellen zugeordnet",
+        "Invalid line" => "Ungültige Zeile",
+        "Connect to the pack to check the cell numbers" => {
+            "Mit dem Akku verbinden, um die Zellnummern zu prüfen"
+        }
+
+        // mirroring
+        "Mirror horizontally" => "Horizontal spiegeln",
+        "Mirror vertically" => "Vertikal spiegeln",
+        "Toggle horizontal mirroring" => "Horizontale Spiegelung umschalten",
+
+        // units
+        "Units" => "Einheiten",
+        "Cell voltage" => "Zellspannung",
+        "Voltage decimal places" => "Nachkommastellen Spannung",
+        "Temperature decimal places" => "Nachkommastellen Temperatur",
+        "Thousands separator" => "Tausendertrennzeichen",
+
+        // alarms
+        "Alarms" => "Alarme",
+        "Acknowledge alarms" => "Alarme quittieren",
+        "Undervoltage" => "Unterspannung",
+        "Overvoltage" => "Überspannung",
+        "Overtemperature" => "Übertemperatur",
+        "Insulation fault" => "Isolationsfehler",
+        "active" => "aktiv",
+        "cleared" => "behoben",
+        "worst" => "Extremwert",
+        "first" => "zuerst",
+        "Alarm log" => "Alarmprotokoll",
+        "No alarms so far" => "Bisher keine Alarme",
+        "Fired" => "Ausgelöst",
+        "Acknowledged" => "Quittiert",
+        "Cleared" => "Behoben",
+        "Event" => "Ereignis",
+        "Alarm" => "Alarm",
+        "Error exporting alarm log" => "Fehler beim Exportieren des Alarmprotokolls",
+        "Error saving alarm log" => "Fehler beim Speichern des Alarmprotokolls",
+        "The undervoltage alarm uses the alarm voltage of the trend settings" => {
+            "Der Unterspannungsalarm verwendet die Alarmspannung der Trend-Einstellungen"
+        }
+
+        // derived channels
+        "Derived channels" => "Abgeleitete Kanäle",
+        "Expression" => "Ausdruck",
+        "Unit" => "Einheit",
+        "Add channel" => "Kanal hinzufügen",
+        "Available values" => "Verfügbare Werte",
+        "Names may only contain letters, digits and underscores" => {
+            "Namen dürfen nur Buchstaben, Ziffern und Unterstriche enthalten"
+        }
+        "Unexpected character" => "Unerwartetes Zeichen",
+        "Unexpected end of expression" => "Unerwartetes Ende des Ausdrucks",
+        "Missing ')'" => "Fehlende ')'",
+        "Invalid number" => "Ungültige Zahl",
+        "Unknown value" => "Unbekannter Wert",
+        "Unknown function" => "Unbekannte Funktion",
+        "arguments expected" => "Argumente erwartet",
+
+        // scripting
+        "Script" => "Skript",
+        "Script panel" => "Skript-Panel",
+        "Run script on every sample" => "Skript bei jedem Messwert ausführen",
+        "Script file" => "Skriptdatei",
+        "Script API" => "Skript-API",
+        "Script error" => "Skriptfehler",
+        "Error loading script" => "Fehler beim Laden des Skripts",
+        "The script runs on every sample with `data` (e.g. data.voltage, data.cells, \
+         data.left.avg) and `state`, a map kept between samples. It can call \
+         alarm(text), alarm(text, value), value(name, value, unit), label(text) and print." => {
+            "Das Skript läuft bei jedem Messwert mit `data` (z. B. data.voltage, data.cells, \
+             data.left.avg) und `state`, einer Map, die zwischen Messwerten erhalten bleibt. \
+             Es kann alarm(text), alarm(text, value), value(name, value, unit), label(text) \
+             und print aufrufen."
+        }
+
+        // history
+        "History" => "Verlauf",
+        "Full rate" => "Volle Rate",
+        "Downsampled interval" => "Intervall reduziert",
+        "Memory limit" => "Speicherlimit",
+        "samples at full rate" => "Messwerte mit voller Rate",
+        "downsampled" => "reduziert",
+
+        // fleet
+        "Fleet" => "Flotte",
+        "Add pack" => "Akku hinzufügen",
+        "Add packs in the settings" => "Akkus in den Einstellungen hinzufügen",
+        "Poll interval" => "Abfrageintervall",
+        "State" => "Zustand",
+        "SOC" => "SOC",
+        "Min cell" => "Min. Zelle",
+        "Error" => "Fehler",
+        "Connecting" => "Verbinde",
+        "Stale" => "Veraltet",
+        "Online" => "Online",
+        "Open" => "Öffnen",
+
+        // firmware update
+        "Firmware update" => "Firmware-Update",
+        "Firmware image" => "Firmware-Image",
+        "Unlock firmware update" => "Firmware-Update entsperren",
+        "The tractive system is active, open the AIRs before updating" => {
+            "Das Hochvoltsystem ist aktiv, vor dem Update die AIRs öffnen"
+        }
+        "Upload" => "Hochladen",
+        "Uploading firmware" => "Firmware wird hochgeladen",
+        "Waiting for the BMS to restart" => "Warte auf Neustart des BMS",
+        "Confirm firmware update" => "Firmware-Update bestätigen",
+        "Flash the BMS with" => "BMS flashen mit",
+        "Do not power off the BMS until it has restarted." => {
+            "Das BMS nicht ausschalten, bis es neu gestartet ist."
+        }
+        "Error reading firmware image" => "Fehler beim Lesen des Firmware-Images",
+        "Firmware upload failed" => "Hochladen der Firmware fehlgeschlagen",
+        "Firmware updated, the BMS runs" => "Firmware aktualisiert, das BMS läuft mit",
+        "The BMS did not come back after the update" => {
+            "Das BMS hat sich nach dem Update nicht zurückgemeldet"
+        }
+        "Update unverified, the BMS never restarted and still runs" => {
+            "Update nicht bestätigt, das BMS ist nie neu gestartet und läuft weiter mit"
+        }
+
+        // test procedure
+        "Test procedure" => "Testablauf",
+        "Hold" => "Halten",
+        "Wait" => "Warten",
+        "Mark" => "Markieren",
+        "Prompt" => "Anweisung",
+        "Add step" => "Schritt hinzufügen",
+        "Start test" => "Test starten",
+        "Error starting test" => "Fehler beim Starten des Tests",
+        "Error saving test" => "Fehler beim Speichern des Tests",
+        "Step" => "Schritt",
+        "Confirm" => "Bestätigen",
+        "Start step" => "Schritt starten",
+        "Abort test" => "Test abbrechen",
+        "Discharged charge" => "Entnommene Ladung",
+        "Saved to" => "Gespeichert in",
+        "Resistance (mΩ)" => "Widerstand (mΩ)",
+
+        // capacity fade
+        "Charging current" => "Ladestrom",
+        "Capacity fade margin" => "Toleranz Kapazitätsverlust",
+        "Cell capacity over discharges and charges" => {
+            "Zellkapazität über Entlade- und Ladevorgänge"
+        }
+        "capacity faded by" => "Kapazität gesunken um",
+        "Median cell capacity (Ah)" => "Median der Zellkapazität (Ah)",
+
+        // balancing recommendation
+        "Balancing recommendation" => "Balancing-Empfehlung",
+        "Target delta" => "Ziel-Delta",
+        "Cell capacity" => "Zellkapazität",
+        "Bleed current" => "Entladestrom Widerstand",
+        "No data" => "Keine Daten",
+        "The pack is not at rest, the recommendation is inaccurate" => {
+            "Der Akku ist nicht in Ruhe, die Empfehlung ist ungenau"
+        }
+        "All cells are within the target delta" => "Alle Zellen liegen im Ziel-Delta",
+        "cells to bleed" => "Zellen zu entladen",
+        "longest" => "längste",
+        "Export checklist" => "Checkliste exportieren",
+        "Error exporting checklist" => "Fehler beim Exportieren der Checkliste",
+        "Bleed" => "Entladen",
+        "bleed" => "entladen",
+
+        // cooling
+        "Cooling" => "Kühlung",
+        "Stack" => "Stack",
+        "Good" => "Gut",
+        "Degraded" => "Verschlechtert",
+        "Poor" => "Schlecht",
+        "Monitor cooling effectiveness" => "Kühlwirkung überwachen",
+        "Interval" => "Intervall",
+        "Margin" => "Toleranz",
+
+        // current limits
+        "Current limits" => "Stromgrenzen",
+        "Warn when exceeding the current limits" => "Bei Überschreiten der Stromgrenzen warnen",
+        "Peak current" => "Spitzenstrom",
+        "Continuous current" => "Dauerstrom",
+        "Overcurrent" => "Überstrom",
+        "Overload budget" => "Überlastbudget",
+        "Time above limit" => "Zeit über Grenze",
+        "Max current" => "Max. Strom",
+
+        // safe mode
+        "Safe mode" => "Sicherer Modus",
+        "Raw values" => "Rohwerte",
+        "Implausible values are replaced by the average" => {
+            "Unplausible Werte werden durch den Mittelwert ersetzt"
+        }
+        "All values are shown as received" => "Alle Werte werden wie empfangen angezeigt",
+        "Disable safe mode" => "Sicheren Modus deaktivieren",
+        "Implausible cell voltages and temperatures will be shown as received." => {
+            "Unplausible Zellspannungen und Temperaturen werden wie empfangen angezeigt."
+        }
+
+        // stack bands
+        "Stack bands" => "Stack-Bänder",
+        "Show stack bands" => "Stack-Bänder anzeigen",
+
+        // cell search
+        "Find cell" => "Zelle suchen",
+        "Cell number" => "Zellnummer",
+        "No such cell" => "Zelle nicht gefunden",
+
+        // mirror
+        "Mirror" => "Spiegel",
+        "Serve data to mirror clients" => "Daten an Spiegel-Clients bereitstellen",
+        "Dashboard" => "Dashboard",
+        "The mirrored dashboard receives no data" => {
+            "Das gespiegelte Dashboard empfängt keine Daten"
+        }
+        "The BMS info is not available to mirror clients" => {
+            "Die BMS-Infos sind für Spiegel-Clients nicht verfügbar"
+        }
+
+        // rolling statistics
+        "Latest sample" => "Letzter Messwert",
+        "Last 30 s" => "Letzte 30 s",
+        "Last 5 min" => "Letzte 5 min",
+        "Whole session" => "Ganze Sitzung",
+
+        // voltage sag
+        "Voltage sag" => "Spannungseinbruch",
+        "Compensate voltage sag" => "Spannungseinbruch kompensieren",
+        "Resistance window" => "Zeitfenster für Widerstand",
+        "Default cell resistance" => "Standard-Zellwiderstand",
+
+        // display interval
+        "Display interval" => "Anzeigeintervall",
+        "0 shows every sample" => "0 zeigt jeden Messwert",
+        "Combine samples by" => "Messwerte zusammenfassen per",
+        "Mean" => "Mittelwert",
+        "Minimum" => "Minimum",
+        "Maximum" => "Maximum",
+
+        // integrity checks
+        "Integrity checks" => "Plausibilitätsprüfung",
+        "Quarantine inconsistent samples" => "Widersprüchliche Messwerte zurückhalten",
+        "Pack voltage tolerance" => "Toleranz der Packspannung",
+        "Cell voltages" => "Zellspannungen",
+        "Pack voltage" => "Packspannung",
+        "sum of cells" => "Summe der Zellen",
+        "Quarantined samples" => "Zurückgehaltene Messwerte",
+        "quarantined" => "zurückgehalten",
+        "All samples were consistent" => "Alle Messwerte waren plausibel",
+        "Clear" => "Leeren",
+
+        // cell inventory
+        "Cell inventory" => "Zellinventar",
+        "File" => "Datei",
+        "Serial number" => "Seriennummer",
+        "Module" => "Modul",
+        "Position" => "Position",
+        "Import" => "Importieren",
+        "Export" => "Exportieren",
+        "Inventory imported" => "Inventar importiert",
+        "Inventory exported" => "Inventar exportiert",
+        "cells with serial number" => "Zellen mit Seriennummer",
+        "Filter" => "Filter",
+
+        // current direction
+        "Current direction" => "Stromrichtung",
+        "Discharge color" => "Farbe Entladen",
+        "Regen color" => "Farbe Rekuperation",
+        "Deadband" => "Totband",
+        "Peak discharge current" => "Max. Entladestrom",
+        "Peak regen current" => "Max. Rekuperationsstrom",
+        "Peak discharge power" => "Max. Entladeleistung",
+        "Peak regen power" => "Max. Rekuperationsleistung",
+
+        // fuel gauge
+        "Fuel gauge" => "Tankanzeige",
+        "Show fuel gauge in driver mode" => "Tankanzeige im Fahrermodus anzeigen",
+        "Empty at" => "Leer bei",
+        "Full at" => "Voll bei",
+        "Yellow below" => "Gelb unter",
+        "Red below" => "Rot unter",
+        "The weakest cell's voltage is compensated for the sag under load" => {
+            "Die Spannung der schwächsten Zelle wird um den Einbruch unter Last korrigiert"
+        }
+
+        // svg export
+        "Export pack as SVG" => "Pack als SVG exportieren",
+        "Error exporting SVG" => "Fehler beim Exportieren als SVG",
+
+        // settings window
+        "Connection" => "Verbindung",
+        "Display" => "Anzeige",
+        "Logging" => "Aufzeichnung",
+        "Analysis" => "Analyse",
+        "Polling" => "Abfrage",
+        "Layout" => "Layout",
+        "Search settings..." => "Einstellungen suchen...",
+        "No matching settings" => "Keine passenden Einstellungen",
+
+        // heartbeat
+        "Watchdog heartbeat" => "Watchdog-Heartbeat",
+        "Send a heartbeat to an external watchdog" => "Heartbeat an externen Watchdog senden",
+        "Heartbeat file" => "Heartbeat-Datei",
+        "Toggle GPIO" => "GPIO umschalten",
+        "The beats stop when the dashboard hangs" => "Die Heartbeats enden, wenn das Dashboard hängt",
+
+        // end of charge
+        "End of charge" => "Ladeende",
+        "Detect the end of charge" => "Ladeende erkennen",
+        "End current" => "Abschaltstrom",
+        "Hold time" => "Haltezeit",
+        "Notification URL" => "Benachrichtigungs-URL",
+        "Charging complete" => "Laden abgeschlossen",
+        "highest cell" => "höchste Zelle",
+        "delta" => "Delta",
+        "The charge ended" => "Das Laden endete",
+        "Error sending notification" => "Fehler beim Senden der Benachrichtigung",
+
+        "Driver messages" => "Fahrernachrichten",
+        "Driver message logged" => "Fahrernachricht protokolliert",
+        "Unused" => "Unbenutzt",
+        "The shortcuts can be changed below" => "Die Tastenkürzel können unten geändert werden",
+
+        // warnings
+        "Cell" => "Zelle",
+        "projected to reach the alarm voltage in" => {
+            "erreicht voraussichtlich die Alarmspannung in"
+        }
+
+        // errors
+        "Error loading data" => "Fehler beim Laden der Daten",
+        "Unexpected error" => "Unerwarteter Fehler",
+
+        // side panel
+        "Current" => "Strom",
+        "Voltage" => "Spannung",
+        "State of charge" => "Ladezustand",
+        "Insulation resistance" => "Isolationswiderstand",
+        "Below the warning threshold" => "Unter der Warnschwelle",
+        "IMD fault" => "IMD-Fehler",
+        "OCV state of charge" => "Ladezustand (Ruhespannung)",
+        "Pack not at rest, showing the last estimate" => "Akku nicht in Ruhe, letzte Schätzung",
+        "Both accumulators" => "Beide Akkus",
+        "Right accumulator" => "Rechter Akku",
+        "Left accumulator" => "Linker Akku",
+        "Min cell voltage" => "Min. Zellspannung",
+        "Avg cell voltage" => "Mittl. Zellspannung",
+        "Max cell voltage" => "Max. Zellspannung",
+        "Delta cell voltage" => "Delta Zellspannung",
+        "Weakest cell" => "Schwächste Zelle",
+        "Diverging cell" => "Abweichende Zelle",
+        "Min temperature" => "Min. Temperatur",
+        "Avg temperature" => "Mittl. Temperatur",
+        "Max temperature" => "Max. Temperatur",
+        "Delta temperature" => "Delta Temperatur",
+        "Master temperature" => "Master-Temperatur",
+        "#Slaves" => "#Slaves",
+        "#Cells" => "#Zellen",
+        "#Cells / #Slaves" => "#Zellen / #Slaves",
+        "#Temperature sensors" => "#Temperatursensoren",
+        "#Safe resistors" => "#Sicherheitswiderstände",
+
+        _ => return None,
+    };
+    Some(translated)
+}
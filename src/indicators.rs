@@ -0,0 +1,28 @@
+use egui::{Color32, Sense, Ui, Vec2};
+
+use crate::api::Relays;
+use crate::i18n::tr;
+
+pub fn relay_indicators(ui: &mut Ui, relays: &Relays) {
+    ui.horizontal(|ui| {
+        indicator(ui, tr("Shutdown circuit"), relays.shutdown_circuit);
+        indicator(ui, tr("Precharge"), relays.precharge);
+        indicator(ui, "AIR-", relays.air_minus);
+        indicator(ui, "AIR+", relays.air_plus);
+        indicator(ui, tr("TS active"), relays.ts_active());
+    });
+}
+
+fn indicator(ui: &mut Ui, name: &str, on: bool) {
+    let size = ui.text_style_height(&egui::TextStyle::Body);
+    let (rect, _) = ui.allocate_exact_size(Vec2::splat(size), Sense::hover());
+    let color = if on {
+        Color32::from_rgb(0x20, 0xc0, 0x20)
+    } else {
+        ui.visuals().widgets.inactive.bg_fill
+    };
+    ui.painter()
+        .circle_filled(rect.center(), size / 2.0 - 1.0, color);
+    ui.label(name);
+    ui.add_space(size);
+}
@@ -0,0 +1,121 @@
+use std::thread::JoinHandle;
+
+use egui::{Color32, Grid, RichText, Ui};
+
+use crate::api::{fetch_info, BmsInfo, Connection, Data, Format};
+use crate::i18n::tr;
+use crate::units;
+
+#[derive(Default)]
+pub struct InfoPage {
+    request: Option<JoinHandle<anyhow::Result<BmsInfo>>>,
+    info: Option<Result<BmsInfo, String>>,
+}
+
+impl InfoPage {
+    fn poll(&mut self) {
+        if !self.request.as_ref().is_some_and(|r| r.is_finished()) {
+            return;
+        }
+        let result = match self.request.take().unwrap().join() {
+            Ok(Ok(info)) => Ok(info),
+            Ok(Err(e)) => Err(e.to_string()),
+            Err(_) => Err(tr("Unexpected error").to_string()),
+        };
+        self.info = Some(result);
+    }
+
+    pub fn view(&mut self, ui: &mut Ui, connection: &Connection, data: Option<&Data>) {
+        self.poll();
+        if self.info.is_none() && self.request.is_none() {
+            self.request = Some(fetch_info(connection));
+        }
+
+        ui.horizontal(|ui| {
+            ui.heading(tr("BMS info"));
+            if self.request.is_some() {
+                ui.spinner();
+            } else if ui.button(tr("Reload")).clicked() {
+                self.request = Some(fetch_info(connection));
+            }
+        });
+
+        let info = match &self.info {
+            Some(Ok(info)) => info,
+            Some(Err(e)) => {
+                ui.label(
+                    RichText::new(format!("{}: {e}", tr("Error loading data"))).color(Color32::RED),
+                );
+                return;
+            }
+            None => return,
+        };
+
+        // the configured counts should match what the BMS actually reports
+        let ucell = data.map(|d| &d.ucell);
+        let count = |ui: &mut Ui, configured: usize, reported: Option<usize>| match reported {
+            Some(r) if r != configured => {
+                ui.label(RichText::new(configured.to_string()).color(Color32::RED))
+                    .on_hover_text(format!("{} {r}", tr("Reported:")));
+            }
+            _ => {
+                ui.label(configured.to_string());
+            }
+        };
+
+        Grid::new("bms_info").striped(true).show(ui, |ui| {
+            ui.label(tr("Firmware version"));
+            ui.label(&info.firmware);
+            ui.end_row();
+
+            ui.label(tr("Firmware format"));
+            ui.label(Format::detect(&info.firmware).name());
+            ui.end_row();
+
+            // recorded with every sample to align the logs with the BMS's error log
+            if let Some(uptime) = data.and_then(|d| d.main.uptime) {
+                let seconds = uptime / 1000;
+                ui.label(tr("BMS uptime"));
+                ui.label(format!(
+                    "{}:{:02}:{:02}",
+                    seconds / 3600,
+                    seconds / 60 % 60,
+                    seconds % 60
+                ));
+                ui.end_row();
+            }
+
+            ui.label(tr("Slaves"));
+            count(ui, info.num_slaves, ucell.map(|u| u.num_slaves));
+            ui.end_row();
+
+            ui.label(tr("Cells"));
+            count(ui, info.num_cells, ucell.map(|u| u.num_cells));
+            ui.end_row();
+
+            ui.label(tr("Temperature sensors"));
+            count(ui, info.num_temp_sensors, ucell.map(|u| u.num_temp_sensors));
+            ui.end_row();
+
+            ui.label(tr("Maximum discharge current"));
+            ui.label(format!("{:.0} A", info.max_discharge_current));
+            ui.end_row();
+
+            ui.label(tr("Maximum charge current"));
+            ui.label(format!("{:.0} A", info.max_charge_current));
+            ui.end_row();
+
+            ui.label(tr("Minimum cell voltage"));
+            ui.label(units::voltage(info.min_cell_voltage));
+            ui.end_row();
+
+            ui.label(tr("Maximum cell voltage"));
+            ui.label(units::voltage(info.max_cell_voltage));
+            ui.end_row();
+
+            ui.label(tr("Maximum cell temperature"));
+            ui.label(units::temp(info.max_cell_temp));
+            ui.end_row();
+        });
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::VecDeque;
+
+use egui::{DragValue, Grid, ScrollArea, Ui, Window};
+use serde::{Deserialize, Serialize};
+
+use crate::api::Data;
+use crate::i18n::tr;
+
+/// Only the latest quarantined samples are kept for review
+const MAX_KEPT: usize = 100;
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct IntegrityConfig {
+    pub enabled: bool,
+    /// Allowed difference in % between the pack voltage and the sum of the cells
+    pub voltage_tolerance: f32,
+}
+
+impl Default for IntegrityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            voltage_tolerance: 3.0,
+        }
+    }
+}
+
+impl IntegrityConfig {
+    pub fn settings(&mut self, ui: &mut Ui) {
+        ui.checkbox(&mut self.enabled, tr("Quarantine inconsistent samples"));
+        Grid::new("integrity_config").show(ui, |ui| {
+            ui.label(tr("Pack voltage tolerance"));
+            ui.add(
+                DragValue::new(&mut self.voltage_tolerance)
+                    .clamp_range(0.1..=20.0)
+                    .speed(0.1)
+                    .suffix(" %"),
+            );
+            ui.end_row();
+        });
+    }
+}
+
+/// An inconsistency within a parsed sample, e.g. after a page was only partly received
+pub enum Issue {
+    CellCount {
+        expected: usize,
+        received: usize,
+    },
+    TempCount {
+        expected: usize,
+        received: usize,
+    },
+    /// in V
+    PackVoltage {
+        reported: f32,
+        sum: f32,
+    },
+}
+
+impl Issue {
+    fn describe(&self) -> String {
+        match self {
+            Issue::CellCount { expected, received } => {
+                format!("{}: {received}/{expected}", tr("Cell voltages"))
+            }
+            Issue::TempCount { expected, received } => {
+                format!("{}: {received}/{expected}", tr("Temperatures"))
+            }
+            Issue::PackVoltage { reported, sum } => format!(
+                "{} {reported:.1} V, {} {sum:.1} V",
+                tr("Pack voltage"),
+                tr("sum of cells")
+            ),
+        }
+    }
+}
+
+/// Compares the sample to the counts reported by the BMS itself, counts of 0 or a pack voltage
+/// of 0 aren't provided by every source and are skipped
+pub fn check(config: &IntegrityConfig, data: &Data) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    if !config.enabled {
+        return issues;
+    }
+
+    let ucell = &data.ucell;
+    let cells = ucell.cell_voltage.len();
+    if ucell.num_cells != 0 && cells != ucell.num_cells {
+        issues.push(Issue::CellCount {
+            expected: ucell.num_cells,
+            received: cells,
+        });
+    }
+    let temps = data.tcell.temp.len();
+    if ucell.num_temp_sensors != 0 && temps != ucell.num_temp_sensors {
+        issues.push(Issue::TempCount {
+            expected: ucell.num_temp_sensors,
+            received: temps,
+        });
+    }
+    let reported = data.main.voltage;
+    let sum = ucell.cell_voltage.iter().map(|v| *v as f32).sum::<f32>() / 1000.0;
+    if reported > 0.0
+        && cells != 0
+        && (sum - reported).abs() > reported * config.voltage_tolerance / 100.0
+    {
+        issues.push(Issue::PackVoltage { reported, sum });
+    }
+    issues
+}
+
+/// Samples that failed the checks, they are neither displayed nor recorded
+#[derive(Default)]
+pub struct Quarantine {
+    /// Unix time in ms and issues of the latest quarantined samples
+    pub samples: VecDeque<(u128, Vec<Issue>)>,
+    /// All samples quarantined during the session
+    pub count: usize,
+    pub show: bool,
+}
+
+impl Quarantine {
+    pub fn add(&mut self, time: u128, issues: Vec<Issue>) {
+        self.samples.push_back((time, issues));
+        if self.samples.len() > MAX_KEPT {
+            self.samples.pop_front();
+        }
+        self.count += 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.count = 0;
+    }
+
+    pub fn window(&mut self, ctx: &egui::Context, now: u128) {
+        let mut show = self.show;
+        Window::new(tr("Quarantined samples"))
+            .id("quarantine".into())
+            .open(&mut show)
+            .default_size([500.0, 300.0])
+            .show(ctx, |ui| {
+                if self.samples.is_empty() {
+                    ui.label(tr("All samples were consistent"));
+                    return;
+                }
+                ui.label(format!("{}: {}", tr("Quarantined samples"), self.count));
+                if ui.button(tr("Clear")).clicked() {
+                    self.clear();
+                    return;
+                }
+
+                let ago = |time: u128| {
+                    let seconds = now.saturating_sub(time) / 1000;
+                    format!("{}:{:02} {}", seconds / 60, seconds % 60, tr("ago"))
+                };
+                ScrollArea::vertical().show(ui, |ui| {
+                    Grid::new("quarantine").striped(true).show(ui, |ui| {
+                        for (time, issues) in self.samples.iter().rev() {
+                            ui.label(ago(*time));
+                            ui.vertical(|ui| {
+                                for issue in issues {
+                                    ui.label(issue.describe());
+                                }
+                            });
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+        self.show = show;
+    }
+}
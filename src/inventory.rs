@@ -0,0 +1,179 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use egui::{Grid, ScrollArea, TextEdit, Window};
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::tr;
+
+/// A physical cell as documented for scrutineering
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CellRecord {
+    pub serial: String,
+    pub module: String,
+    pub position: String,
+}
+
+/// Serial numbers and mounting positions of the cells, linked to the electrical index so the
+/// analyses can refer to the physical cells
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CellInventory {
+    /// Indexed by the 0-based electrical index
+    pub cells: Vec<CellRecord>,
+    /// File the inventory is imported from and exported to
+    pub path: String,
+    #[serde(skip)]
+    pub show: bool,
+    #[serde(skip)]
+    filter: String,
+    #[serde(skip)]
+    status: Option<Result<String, String>>,
+}
+
+impl CellInventory {
+    pub fn serial(&self, cell_index: usize) -> Option<&str> {
+        let serial = self.cells.get(cell_index)?.serial.trim();
+        (!serial.is_empty()).then_some(serial)
+    }
+
+    /// The 1-based electrical number followed by the serial number if it is known
+    pub fn label(&self, cell_index: usize) -> String {
+        match self.serial(cell_index) {
+            Some(serial) => format!("{} ({serial})", cell_index + 1),
+            None => (cell_index + 1).to_string(),
+        }
+    }
+
+    /// Reads a file with one `electrical,serial,module,position` line per cell, the electrical
+    /// number is 1-based and lines starting with `#` are ignored
+    fn import(path: &Path) -> io::Result<Vec<CellRecord>> {
+        let text = fs::read_to_string(path)?;
+        let mut cells = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = || {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{} {}: {line}", tr("Invalid line"), i + 1),
+                )
+            };
+            let mut fields = line.split(',').map(str::trim);
+            let index = match fields.next().map(str::parse::<usize>) {
+                Some(Ok(n)) if n > 0 => n - 1,
+                _ => return Err(invalid()),
+            };
+            let mut field = || fields.next().unwrap_or_default().to_string();
+            let record = CellRecord {
+                serial: field(),
+                module: field(),
+                position: field(),
+            };
+            if cells.len() <= index {
+                cells.resize(index + 1, CellRecord::default());
+            }
+            cells[index] = record;
+        }
+        Ok(cells)
+    }
+
+    fn export(&self, path: &Path) -> io::Result<()> {
+        let mut text = String::from("# electrical,serial,module,position\n");
+        for (i, cell) in self.cells.iter().enumerate() {
+            let clean = |s: &str| s.replace([',', '\n'], " ");
+            text.push_str(&format!(
+                "{},{},{},{}\n",
+                i + 1,
+                clean(&cell.serial),
+                clean(&cell.module),
+                clean(&cell.position)
+            ));
+        }
+        fs::write(path, text)
+    }
+
+    /// Editable table with a row for each of the `cells` of the pack
+    pub fn window(&mut self, ctx: &egui::Context, cells: usize) {
+        if !self.show {
+            return;
+        }
+        if self.cells.len() < cells {
+            self.cells.resize(cells, CellRecord::default());
+        }
+
+        let mut show = self.show;
+        Window::new(tr("Cell inventory"))
+            .id("cell_inventory".into())
+            .open(&mut show)
+            .default_size([520.0, 500.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(tr("File"));
+                    ui.add(TextEdit::singleline(&mut self.path).desired_width(200.0));
+                    if ui.button(tr("Import")).clicked() {
+                        self.status = Some(match Self::import(Path::new(&self.path)) {
+                            Ok(imported) => {
+                                self.cells = imported;
+                                Ok(tr("Inventory imported").to_string())
+                            }
+                            Err(e) => Err(e.to_string()),
+                        });
+                    }
+                    if ui.button(tr("Export")).clicked() {
+                        self.status = Some(match self.export(Path::new(&self.path)) {
+                            Ok(()) => Ok(tr("Inventory exported").to_string()),
+                            Err(e) => Err(e.to_string()),
+                        });
+                    }
+                });
+                match &self.status {
+                    Some(Ok(status)) => {
+                        ui.label(status);
+                    }
+                    Some(Err(error)) => {
+                        ui.colored_label(ui.visuals().error_fg_color, error);
+                    }
+                    None => (),
+                }
+                let documented = self.cells.iter().filter(|c| !c.serial.is_empty()).count();
+                ui.label(format!("{documented} {}", tr("cells with serial number")));
+                ui.add(
+                    TextEdit::singleline(&mut self.filter)
+                        .hint_text(tr("Filter"))
+                        .desired_width(200.0),
+                );
+                ui.separator();
+
+                let filter = self.filter.trim().to_lowercase();
+                ScrollArea::vertical().show(ui, |ui| {
+                    Grid::new("cell_inventory").striped(true).show(ui, |ui| {
+                        ui.strong(tr("Cell"));
+                        ui.strong(tr("Serial number"));
+                        ui.strong(tr("Module"));
+                        ui.strong(tr("Position"));
+                        ui.end_row();
+
+                        for (i, cell) in self.cells.iter_mut().enumerate() {
+                            let matches = [&cell.serial, &cell.module, &cell.position]
+                                .iter()
+                                .any(|s| s.to_lowercase().contains(&filter));
+                            if !filter.is_empty() && !matches && (i + 1).to_string() != filter {
+                                continue;
+                            }
+                            ui.label((i + 1).to_string());
+                            ui.add(TextEdit::singleline(&mut cell.serial).desired_width(160.0));
+                            ui.add(TextEdit::singleline(&mut cell.module).desired_width(80.0));
+                            ui.add(TextEdit::singleline(&mut cell.position).desired_width(80.0));
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+        self.show = show;
+    }
+}
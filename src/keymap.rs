@@ -0,0 +1,432 @@
+use std::collections::BTreeMap;
+
+use egui::{Align2, Event, Grid, Key, ModifierNames, Modifiers, Ui, Window};
+use serde::{Deserialize, Serialize};
+
+use crate::app::View;
+use crate::i18n::tr;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Action {
+    Help,
+    CommandPalette,
+    Settings,
+    ToggleSafe,
+    Pause,
+    DriverMode,
+    View(View),
+    ToggleStats,
+    ToggleTemps,
+    ToggleStacks,
+    ToggleNumbering,
+    MirrorLayout,
+    Screenshot,
+    ExportSvg,
+    ToggleRecording,
+    StateOfHealth,
+    Precharge,
+    Control,
+    Laps,
+    Outliers,
+    AcknowledgeAlarms,
+    AlarmLog,
+    DerivedChannels,
+    ScriptPanel,
+    Fleet,
+    FirmwareUpdate,
+    TestProcedure,
+    Balancing,
+    FindCell,
+    CellInventory,
+    Diagnostics,
+    MarkLap,
+    Session,
+    AddMarker,
+    DriverMessage(u8),
+    Replay,
+    Quit,
+    /// Selects a heatmap profile by its index, only offered in the command palette as the
+    /// profiles are user defined
+    VoltageProfile(u8),
+    TempProfile(u8),
+}
+
+impl Action {
+    pub const ALL: [Action; 47] = [
+        Action::Help,
+        Action::CommandPalette,
+        Action::Settings,
+        Action::ToggleSafe,
+        Action::Pause,
+        Action::DriverMode,
+        Action::View(View::Pack),
+        Action::View(View::Slaves),
+        Action::View(View::Charging),
+        Action::View(View::Info),
+        Action::View(View::Resistance),
+        Action::View(View::Comparison),
+        Action::View(View::StackBands),
+        Action::ToggleStats,
+        Action::ToggleTemps,
+        Action::ToggleStacks,
+        Action::ToggleNumbering,
+        Action::MirrorLayout,
+        Action::Screenshot,
+        Action::ExportSvg,
+        Action::ToggleRecording,
+        Action::StateOfHealth,
+        Action::Precharge,
+        Action::Control,
+        Action::Laps,
+        Action::Outliers,
+        Action::AcknowledgeAlarms,
+        Action::AlarmLog,
+        Action::DerivedChannels,
+        Action::ScriptPanel,
+        Action::Fleet,
+        Action::FirmwareUpdate,
+        Action::TestProcedure,
+        Action::Balancing,
+        Action::FindCell,
+        Action::CellInventory,
+        Action::Diagnostics,
+        Action::MarkLap,
+        Action::Session,
+        Action::AddMarker,
+        Action::DriverMessage(0),
+        Action::DriverMessage(1),
+        Action::DriverMessage(2),
+        Action::DriverMessage(3),
+        Action::DriverMessage(4),
+        Action::Replay,
+        Action::Quit,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Help => tr("Show keyboard shortcuts"),
+            Action::CommandPalette => tr("Command palette"),
+            Action::Settings => tr("Settings"),
+            Action::ToggleSafe => tr("Toggle safe mode"),
+            Action::Pause => tr("Pause polling"),
+            Action::DriverMode => tr("Driver mode"),
+            Action::View(View::Pack) => tr("Show pack view"),
+            Action::View(View::Slaves) => tr("Show slave view"),
+            Action::View(View::Charging) => tr("Show charging view"),
+            Action::View(View::Info) => tr("Show BMS info"),
+            Action::View(View::Resistance) => tr("Show internal resistance"),
+            Action::View(View::Comparison) => tr("Show left/right comparison"),
+            Action::View(View::StackBands) => tr("Show stack bands"),
+            Action::ToggleStats => tr("Toggle stats panel"),
+            Action::ToggleTemps => tr("Toggle temperatures"),
+            Action::ToggleStacks => tr("Toggle stacks"),
+            Action::ToggleNumbering => tr("Toggle physical cell numbers"),
+            Action::MirrorLayout => tr("Toggle horizontal mirroring"),
+            Action::Screenshot => tr("Screenshot"),
+            Action::ExportSvg => tr("Export pack as SVG"),
+            Action::ToggleRecording => tr("Start/stop recording"),
+            Action::StateOfHealth => tr("State of health"),
+            Action::Precharge => tr("Precharge"),
+            Action::Control => tr("Control"),
+            Action::Laps => tr("Laps"),
+            Action::Outliers => tr("Outliers"),
+            Action::AcknowledgeAlarms => tr("Acknowledge alarms"),
+            Action::AlarmLog => tr("Alarm log"),
+            Action::DerivedChannels => tr("Derived channels"),
+            Action::ScriptPanel => tr("Script panel"),
+            Action::Fleet => tr("Fleet"),
+            Action::FirmwareUpdate => tr("Firmware update"),
+            Action::TestProcedure => tr("Test procedure"),
+            Action::Balancing => tr("Balancing recommendation"),
+            Action::FindCell => tr("Find cell"),
+            Action::CellInventory => tr("Cell inventory"),
+            Action::Diagnostics => tr("Network diagnostics"),
+            Action::MarkLap => tr("Mark lap"),
+            Action::Session => tr("Session"),
+            Action::AddMarker => tr("Add marker"),
+            Action::DriverMessage(0) => tr("Driver message 1"),
+            Action::DriverMessage(1) => tr("Driver message 2"),
+            Action::DriverMessage(2) => tr("Driver message 3"),
+            Action::DriverMessage(3) => tr("Driver message 4"),
+            Action::DriverMessage(_) => tr("Driver message 5"),
+            Action::Replay => tr("Replay recording"),
+            Action::Quit => tr("Quit"),
+            Action::VoltageProfile(_) => tr("Voltage heatmap profile"),
+            Action::TempProfile(_) => tr("Temperature heatmap profile"),
+        }
+    }
+
+    /// Whether the action only changes what is displayed, the others are disabled on spectator
+    /// screens
+    pub fn display_only(self) -> bool {
+        matches!(
+            self,
+            Action::Help | Action::View(_) | Action::DriverMode | Action::FindCell
+        )
+    }
+
+    fn default_shortcut(self) -> Option<Shortcut> {
+        let shortcut = match self {
+            Action::Help => Shortcut::new(Key::F1),
+            Action::CommandPalette => Shortcut {
+                modifiers: Modifiers::COMMAND,
+                ..Shortcut::new(Key::P)
+            },
+            Action::Settings => Shortcut {
+                modifiers: Modifiers::COMMAND,
+                ..Shortcut::new(Key::Comma)
+            },
+            Action::ToggleSafe => Shortcut {
+                held: Some(Key::V),
+                ..Shortcut::new(Key::W)
+            },
+            Action::Pause => Shortcut::new(Key::Space),
+            Action::DriverMode => Shortcut::new(Key::D),
+            Action::Screenshot => Shortcut::new(Key::F12),
+            // stopping a recording or acknowledging alarms by a stray key press would lose data
+            Action::ToggleRecording => Shortcut {
+                modifiers: Modifiers::COMMAND | Modifiers::SHIFT,
+                ..Shortcut::new(Key::R)
+            },
+            Action::MarkLap => Shortcut::new(Key::L),
+            Action::AddMarker => Shortcut::new(Key::M),
+            Action::DriverMessage(0) => Shortcut::new(Key::F5),
+            Action::DriverMessage(1) => Shortcut::new(Key::F6),
+            Action::DriverMessage(2) => Shortcut::new(Key::F7),
+            Action::DriverMessage(3) => Shortcut::new(Key::F8),
+            Action::DriverMessage(_) => Shortcut::new(Key::F9),
+            Action::AcknowledgeAlarms => Shortcut {
+                modifiers: Modifiers::COMMAND | Modifiers::SHIFT,
+                ..Shortcut::new(Key::A)
+            },
+            Action::Quit => Shortcut {
+                modifiers: Modifiers::COMMAND,
+                ..Shortcut::new(Key::Q)
+            },
+            Action::View(View::Pack) => Shortcut::new(Key::Num1),
+            Action::View(View::Slaves) => Shortcut::new(Key::Num2),
+            Action::View(View::Charging) => Shortcut::new(Key::Num3),
+            Action::View(View::Info) => Shortcut::new(Key::Num4),
+            Action::View(View::Resistance) => Shortcut::new(Key::Num5),
+            Action::View(View::Comparison) => Shortcut::new(Key::Num6),
+            Action::View(View::StackBands) => Shortcut::new(Key::Num7),
+            Action::ToggleStats
+            | Action::ToggleTemps
+            | Action::ToggleStacks
+            | Action::ToggleNumbering
+            | Action::MirrorLayout
+            | Action::StateOfHealth
+            | Action::Precharge
+            | Action::Control
+            | Action::Laps
+            | Action::Outliers
+            | Action::AlarmLog
+            | Action::DerivedChannels
+            | Action::ScriptPanel
+            | Action::Fleet
+            | Action::FirmwareUpdate
+            | Action::TestProcedure
+            | Action::Balancing
+            | Action::FindCell
+            | Action::CellInventory
+            | Action::ExportSvg
+            | Action::Diagnostics
+            | Action::Session
+            | Action::Replay
+            | Action::VoltageProfile(_)
+            | Action::TempProfile(_) => return None,
+        };
+        Some(shortcut)
+    }
+}
+
+/// A key press, optionally combined with modifiers and another key that has to be held down
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Shortcut {
+    pub modifiers: Modifiers,
+    pub held: Option<Key>,
+    pub key: Key,
+}
+
+impl Shortcut {
+    const fn new(key: Key) -> Self {
+        Self {
+            modifiers: Modifiers::NONE,
+            held: None,
+            key,
+        }
+    }
+
+    /// Whether both shortcuts are triggered by the same key press, a shortcut without Shift also
+    /// matches while Shift is held
+    fn conflicts(&self, other: &Shortcut) -> bool {
+        self.key == other.key
+            && self.held == other.held
+            && (self.modifiers.matches_logically(other.modifiers)
+                || other.modifiers.matches_logically(self.modifiers))
+    }
+
+    pub fn format(&self, ctx: &egui::Context) -> String {
+        let is_mac = ctx.os() == egui::os::OperatingSystem::Mac;
+        let mut text = ModifierNames::NAMES.format(&self.modifiers, is_mac);
+        if let Some(held) = self.held {
+            text.push_str(held.name());
+            text.push('+');
+        }
+        text.push_str(self.key.name());
+        text
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Keymap {
+    /// Shortcuts that differ from the defaults
+    bindings: BTreeMap<Action, Shortcut>,
+    #[serde(skip)]
+    recording: Option<Action>,
+    /// Action already bound to the last recorded shortcut, which was rejected
+    #[serde(skip)]
+    conflict: Option<Action>,
+    #[serde(skip)]
+    pub show_help: bool,
+}
+
+impl Keymap {
+    pub fn shortcut(&self, action: Action) -> Option<Shortcut> {
+        self.bindings
+            .get(&action)
+            .copied()
+            .or_else(|| action.default_shortcut())
+    }
+
+    pub fn format(&self, ctx: &egui::Context, action: Action) -> String {
+        match self.shortcut(action) {
+            Some(shortcut) => shortcut.format(ctx),
+            None if action == Action::FindCell => "/".into(),
+            None => "-".into(),
+        }
+    }
+
+    /// Returns the actions whose shortcuts were pressed during this frame
+    pub fn pressed(&mut self, ctx: &egui::Context) -> Vec<Action> {
+        if let Some(action) = self.recording {
+            self.record(ctx, action);
+            return Vec::new();
+        }
+        if ctx.wants_keyboard_input() {
+            return Vec::new();
+        }
+
+        ctx.input(|i| {
+            let mut actions: Vec<Action> = Action::ALL
+                .into_iter()
+                .filter(|a| {
+                    self.shortcut(*a).is_some_and(|shortcut| {
+                        i.modifiers.matches_logically(shortcut.modifiers)
+                            && shortcut.held.is_none_or(|k| i.key_down(k))
+                            && i.key_pressed(shortcut.key)
+                    })
+                })
+                .collect();
+            // egui has no key for the slash, it is matched on the typed text unless rebound
+            let slash = i
+                .events
+                .iter()
+                .any(|e| matches!(e, Event::Text(t) if t == "/"));
+            if slash && self.shortcut(Action::FindCell).is_none() {
+                actions.push(Action::FindCell);
+            }
+            actions
+        })
+    }
+
+    fn record(&mut self, ctx: &egui::Context, action: Action) {
+        let shortcut = ctx.input(|i| {
+            i.events.iter().find_map(|e| match e {
+                Event::Key {
+                    key,
+                    pressed: true,
+                    modifiers,
+                    ..
+                } => Some(Shortcut {
+                    modifiers: *modifiers,
+                    held: i.keys_down.iter().copied().find(|k| k != key),
+                    key: *key,
+                }),
+                _ => None,
+            })
+        });
+
+        match shortcut {
+            None => (),
+            Some(Shortcut {
+                key: Key::Escape, ..
+            }) => {
+                self.recording = None;
+                self.conflict = None;
+            }
+            Some(shortcut) => {
+                self.conflict = Action::ALL.into_iter().find(|a| {
+                    *a != action && self.shortcut(*a).is_some_and(|s| s.conflicts(&shortcut))
+                });
+                if self.conflict.is_some() {
+                    // keeps recording until a free shortcut is pressed
+                    return;
+                }
+                if Some(shortcut) == action.default_shortcut() {
+                    self.bindings.remove(&action);
+                } else {
+                    self.bindings.insert(action, shortcut);
+                }
+                self.recording = None;
+            }
+        }
+    }
+
+    pub fn settings(&mut self, ui: &mut Ui) {
+        Grid::new("keymap").show(ui, |ui| {
+            for action in Action::ALL {
+                ui.label(action.name());
+                let text = if self.recording == Some(action) {
+                    tr("Press keys...").to_string()
+                } else {
+                    self.format(ui.ctx(), action)
+                };
+                if ui.button(text).clicked() {
+                    self.recording = Some(action);
+                    self.conflict = None;
+                }
+                ui.end_row();
+            }
+        });
+        if let Some(conflict) = self.conflict {
+            ui.colored_label(
+                ui.visuals().error_fg_color,
+                format!("{} {}", tr("Already used by"), conflict.name()),
+            );
+        }
+        if ui.button(tr("Reset shortcuts")).clicked() {
+            self.bindings.clear();
+        }
+    }
+
+    pub fn help_overlay(&mut self, ctx: &egui::Context) {
+        let shortcuts = Action::ALL.map(|a| (a, self.format(ctx, a)));
+        Window::new(tr("Keyboard shortcuts"))
+            .id("keyboard_shortcuts".into())
+            .open(&mut self.show_help)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                Grid::new("keymap_help").striped(true).show(ui, |ui| {
+                    for (action, shortcut) in shortcuts {
+                        ui.label(action.name());
+                        ui.monospace(shortcut);
+                        ui.end_row();
+                    }
+                });
+            });
+    }
+}
@@ -0,0 +1,207 @@
+use egui::{Button, DragValue, Grid, ScrollArea, Ui, Window};
+use serde::{Deserialize, Serialize};
+
+use crate::api::Data;
+use crate::gps::Fix;
+use crate::i18n::tr;
+
+/// Mean earth radius in m
+const EARTH_RADIUS: f64 = 6_371_000.0;
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct LapConfig {
+    /// Latitude and longitude of the start/finish line
+    pub finish_line: Option<(f64, f64)>,
+    /// Distance in m to the finish line at which a lap is counted
+    pub radius: f32,
+    /// Laps shorter than this in s are ignored to debounce the detection
+    pub min_lap_time: u64,
+}
+
+impl Default for LapConfig {
+    fn default() -> Self {
+        Self {
+            finish_line: None,
+            radius: 15.0,
+            min_lap_time: 20,
+        }
+    }
+}
+
+impl LapConfig {
+    pub fn settings(&mut self, ui: &mut Ui) {
+        Grid::new("lap_config").show(ui, |ui| {
+            ui.label(tr("Finish line radius"));
+            ui.add(
+                DragValue::new(&mut self.radius)
+                    .clamp_range(1.0..=200.0)
+                    .suffix(" m"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Minimum lap time"));
+            ui.add(
+                DragValue::new(&mut self.min_lap_time)
+                    .clamp_range(1..=600)
+                    .suffix(" s"),
+            );
+            ui.end_row();
+        });
+    }
+}
+
+pub struct Lap {
+    /// Unix time in ms of the start of the lap
+    pub start: u128,
+    // in ms
+    pub duration: u128,
+    // in Wh
+    pub energy: f32,
+    // in mV
+    pub min_voltage: u16,
+    // in °C
+    pub max_temp: f32,
+}
+
+impl Lap {
+    fn new(start: u128) -> Self {
+        Self {
+            start,
+            duration: 0,
+            energy: 0.0,
+            min_voltage: u16::MAX,
+            max_temp: f32::MIN,
+        }
+    }
+}
+
+/// Laps are marked manually, by a beacon receiver acting as a keyboard or by crossing the
+/// start/finish line
+#[derive(Default)]
+pub struct LapTracker {
+    current: Option<Lap>,
+    last_time: u128,
+    at_finish_line: bool,
+    pub laps: Vec<Lap>,
+    pub show: bool,
+}
+
+impl LapTracker {
+    pub fn update(&mut self, config: &LapConfig, data: &Data, fix: Option<Fix>, time: u128) {
+        if let (Some(line), Some(fix)) = (config.finish_line, fix) {
+            let near = distance(line, (fix.latitude, fix.longitude)) < config.radius as f64;
+            let long_enough = self
+                .current
+                .as_ref()
+                .is_none_or(|l| time.saturating_sub(l.start) >= config.min_lap_time as u128 * 1000);
+            if near && !self.at_finish_line && long_enough {
+                self.mark(time);
+            }
+            self.at_finish_line = near;
+        }
+
+        let hours = time.saturating_sub(self.last_time) as f32 / 3_600_000.0;
+        self.last_time = time;
+        let Some(lap) = &mut self.current else {
+            return;
+        };
+        lap.duration = time.saturating_sub(lap.start);
+        lap.energy += data.main.voltage * data.main.current / 1000.0 * hours;
+        lap.min_voltage = lap.min_voltage.min(data.ucell.overall.min_voltage);
+        lap.max_temp = lap.max_temp.max(data.tcell.overall.max_temp);
+    }
+
+    /// Finishes the current lap and starts the next one
+    pub fn mark(&mut self, time: u128) {
+        if let Some(mut lap) = self.current.take() {
+            lap.duration = time.saturating_sub(lap.start);
+            self.laps.push(lap);
+        }
+        self.current = Some(Lap::new(time));
+        self.last_time = time;
+    }
+
+    pub fn window(
+        &mut self,
+        ctx: &egui::Context,
+        config: &mut LapConfig,
+        fix: Option<Fix>,
+        time: u128,
+    ) {
+        let mut show = self.show;
+        Window::new(tr("Laps"))
+            .id("laps".into())
+            .open(&mut show)
+            .default_size([450.0, 400.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button(tr("Mark lap")).clicked() {
+                        self.mark(time);
+                    }
+                    let set_line =
+                        ui.add_enabled(fix.is_some(), Button::new(tr("Set finish line here")));
+                    if let (true, Some(fix)) = (set_line.clicked(), fix) {
+                        config.finish_line = Some((fix.latitude, fix.longitude));
+                    }
+                    if ui.button(tr("Clear laps")).clicked() {
+                        self.laps.clear();
+                        self.current = None;
+                    }
+                });
+                if let Some((lat, lon)) = config.finish_line {
+                    ui.label(format!("{}: {lat:.6}, {lon:.6}", tr("Finish line")));
+                }
+
+                ScrollArea::vertical().show(ui, |ui| {
+                    Grid::new("laps").striped(true).show(ui, |ui| {
+                        ui.strong(tr("Lap"));
+                        ui.strong(tr("Time"));
+                        ui.strong(tr("Energy"));
+                        ui.strong(tr("Min cell voltage"));
+                        ui.strong(tr("Max temperature"));
+                        ui.end_row();
+
+                        for (i, lap) in self.laps.iter().enumerate() {
+                            lap_row(ui, (i + 1).to_string(), lap);
+                        }
+                        if let Some(lap) = &self.current {
+                            lap_row(
+                                ui,
+                                format!("{} ({})", self.laps.len() + 1, tr("current")),
+                                lap,
+                            );
+                        }
+                    });
+                });
+            });
+        self.show = show;
+    }
+}
+
+fn lap_row(ui: &mut Ui, name: String, lap: &Lap) {
+    let seconds = lap.duration as f32 / 1000.0;
+    ui.label(name);
+    ui.label(format!(
+        "{:.0}:{:04.1}",
+        (seconds / 60.0).floor(),
+        seconds % 60.0
+    ));
+    ui.label(format!("{:.1} Wh", lap.energy));
+    if lap.min_voltage == u16::MAX {
+        ui.label("-");
+        ui.label("-");
+    } else {
+        ui.label(format!("{} mV", lap.min_voltage));
+        ui.label(format!("{:.1} °C", lap.max_temp));
+    }
+    ui.end_row();
+}
+
+/// Distance in m between two coordinates, accurate enough for the size of a track
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let lat = (a.0 + b.0).to_radians() / 2.0;
+    let dx = (b.1 - a.1).to_radians() * lat.cos();
+    let dy = (b.0 - a.0).to_radians();
+    (dx * dx + dy * dy).sqrt() * EARTH_RADIUS
+}
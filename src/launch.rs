@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::tr;
+
+/// Applied once at startup so a logger in the car needs no interaction after power-up. The data
+/// source and address are restored from the saved settings, so polling starts on its own.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LaunchOptions {
+    pub start_minimized: bool,
+    pub start_recording: bool,
+}
+
+impl LaunchOptions {
+    pub fn settings(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.start_minimized, tr("Start minimized"));
+        ui.checkbox(&mut self.start_recording, tr("Start recording on launch"));
+        ui.label(tr(
+            "The session picker is skipped when either option is enabled",
+        ));
+    }
+}
+
+/// Command line flags, enabled in addition to the saved launch options
+#[derive(Default)]
+pub struct LaunchArgs {
+    /// `--minimized`
+    pub minimized: bool,
+    /// `--record`
+    pub record: bool,
+    /// `--spectator`, always enabled when built with the `spectator` feature
+    pub spectator: bool,
+}
+
+impl LaunchArgs {
+    pub fn parse(args: impl Iterator<Item = String>) -> Self {
+        let mut launch = Self {
+            spectator: cfg!(feature = "spectator"),
+            ..Default::default()
+        };
+        for arg in args {
+            match arg.as_str() {
+                "--minimized" => launch.minimized = true,
+                "--record" => launch.record = true,
+                "--spectator" => launch.spectator = true,
+                _ => println!(
+                    "unknown argument {arg}, expected --minimized, --record or --spectator"
+                ),
+            }
+        }
+        launch
+    }
+}
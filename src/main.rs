@@ -1,14 +1,83 @@
 #![windows_subsystem = "windows"]
 use app::DashboardApp;
+use background::Dashboard;
 
 use eframe::NativeOptions;
+use launch::LaunchArgs;
 
+mod alarms;
+mod anomaly;
 mod api;
 mod app;
+mod background;
+mod balancing;
+mod can;
+mod cell_detail;
+mod cell_search;
+mod charging;
+mod comparison;
+mod control;
+mod cooling;
+mod current_limit;
+mod derived;
+mod diagnostics;
+mod driver_messages;
+mod end_of_charge;
+mod firmware;
+mod fleet;
+mod fuel_gauge;
+mod gps;
+mod heartbeat;
+mod heatmap;
+mod history;
+mod http;
+mod i18n;
+mod indicators;
+mod info;
+mod integrity;
+mod inventory;
+mod keymap;
+mod laps;
+mod launch;
+mod markers;
+mod mirror;
+mod motec_export;
+mod numbering;
+mod outliers;
+mod palette;
+mod parquet_export;
+mod precharge;
+mod prediction;
+mod preferences;
+mod procedure;
+mod push;
+mod recording;
+mod regen;
+mod replay;
+mod resistance;
+mod rolling_stats;
+mod sag;
+mod scripting;
+mod serial;
+mod session;
+mod slaves;
+mod smoothing;
+mod soc;
+mod soh;
+mod stack_bands;
+mod stream;
+mod svg;
+mod temp_map;
+mod theme;
+mod timelapse;
+mod trend;
+mod units;
+mod window;
 
 const APP_NAME: &str = "s3bmsdashboard";
 
 fn main() {
+    let args = LaunchArgs::parse(std::env::args().skip(1));
     let options = NativeOptions {
         follow_system_theme: true,
         ..Default::default()
@@ -16,7 +85,10 @@ fn main() {
     let res = eframe::run_native(
         APP_NAME,
         options,
-        Box::new(|c| Box::new(DashboardApp::new(c))),
+        Box::new(|c| {
+            let ctx = c.egui_ctx.clone();
+            Box::new(Dashboard::new(DashboardApp::new(c, args), ctx))
+        }),
     );
     if let Err(err) = res {
         println!("{err}");
@@ -0,0 +1,132 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+use egui::{Align2, Color32, Key, TextEdit, Window};
+use egui_plot::{PlotUi, Text, VLine};
+
+use crate::i18n::tr;
+
+const MARKERS_FILE: &str = "markers.csv";
+
+pub struct Marker {
+    /// Unix time in ms
+    pub time: u128,
+    pub text: String,
+}
+
+impl Marker {
+    fn to_csv(&self) -> String {
+        // the text is the last column, so it may contain commas
+        format!("{},{}", self.time, self.text.replace('\n', " "))
+    }
+
+    fn from_csv(line: &str) -> Option<Self> {
+        let (time, text) = line.split_once(',')?;
+        Some(Self {
+            time: time.parse().ok()?,
+            text: text.to_string(),
+        })
+    }
+
+    /// Reads the markers written to `directory` during a session
+    pub fn load(directory: &Path) -> Vec<Marker> {
+        fs::read_to_string(directory.join(MARKERS_FILE))
+            .map(|text| text.lines().filter_map(Marker::from_csv).collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Timestamped annotations dropped during a session
+#[derive(Default)]
+pub struct Markers {
+    pub markers: Vec<Marker>,
+    /// Time of the marker whose text is being entered
+    pending: Option<(u128, String)>,
+}
+
+impl Markers {
+    /// Starts a new marker, the time is taken now and the text can be entered afterwards
+    pub fn add(&mut self, time: u128) {
+        self.pending = Some((time, String::new()));
+    }
+
+    /// Adds a marker with a known text right away
+    pub fn insert(&mut self, directory: &Path, marker: Marker) -> io::Result<()> {
+        let result = append(directory, &marker);
+        self.markers.push(marker);
+        result
+    }
+
+    /// Shows the text input of a pending marker and saves it to `directory`
+    pub fn window(&mut self, ctx: &egui::Context, directory: &Path) -> Option<io::Result<()>> {
+        let (time, text) = self.pending.as_mut()?;
+
+        let mut save = false;
+        let mut cancel = false;
+        Window::new(tr("Add marker"))
+            .id("marker".into())
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_TOP, [0.0, 40.0])
+            .show(ctx, |ui| {
+                let response = ui.add(
+                    TextEdit::singleline(text)
+                        .hint_text(tr("Optional description"))
+                        .desired_width(300.0),
+                );
+                response.request_focus();
+                save = response.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter));
+                cancel = ui.input(|i| i.key_pressed(Key::Escape));
+                ui.horizontal(|ui| {
+                    save |= ui.button(tr("Save")).clicked();
+                    cancel |= ui.button(tr("cancel")).clicked();
+                });
+            });
+
+        if cancel {
+            self.pending = None;
+            return None;
+        }
+        if !save {
+            return None;
+        }
+        let marker = Marker {
+            time: *time,
+            text: text.trim().to_string(),
+        };
+        self.pending = None;
+        let result = append(directory, &marker);
+        self.markers.push(marker);
+        Some(result)
+    }
+
+    /// Draws the markers as vertical lines, `x` maps the marker time to the plot axis
+    pub fn plot(&self, plot: &mut PlotUi, x: impl Fn(u128) -> Option<f64>) {
+        let color = Color32::from_rgb(0xe0, 0xa0, 0x00);
+        let top = plot.plot_bounds().max()[1];
+        for marker in &self.markers {
+            let Some(x) = x(marker.time) else {
+                continue;
+            };
+            plot.vline(VLine::new(x).color(color));
+            if !marker.text.is_empty() {
+                let position = egui_plot::PlotPoint::new(x, top);
+                plot.text(
+                    Text::new(position, &marker.text)
+                        .color(color)
+                        .anchor(Align2::LEFT_TOP),
+                );
+            }
+        }
+    }
+}
+
+fn append(directory: &Path, marker: &Marker) -> io::Result<()> {
+    fs::create_dir_all(directory)?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(directory.join(MARKERS_FILE))?;
+    writeln!(file, "{}", marker.to_csv())
+}
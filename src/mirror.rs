@@ -0,0 +1,232 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use egui::{DragValue, Grid, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::api::{self, Data, DataSource, PollOptions};
+use crate::i18n::tr;
+
+/// Path of the latest sample on the embedded server
+const DATA_PATH: &str = "/data.json";
+/// A mirror client gives up waiting for a new sample after this long
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct MirrorConfig {
+    /// Serve the received data to mirror clients, e.g. the laptops on the pit wall
+    pub serve: bool,
+    pub port: u16,
+}
+
+impl Default for MirrorConfig {
+    fn default() -> Self {
+        Self {
+            serve: false,
+            port: 8090,
+        }
+    }
+}
+
+impl MirrorConfig {
+    pub fn settings(&mut self, ui: &mut Ui, server: &MirrorServer) {
+        ui.checkbox(&mut self.serve, tr("Serve data to mirror clients"));
+        Grid::new("mirror_config").show(ui, |ui| {
+            ui.label(tr("Port"));
+            ui.add(DragValue::new(&mut self.port).clamp_range(1024..=65535));
+            ui.end_row();
+        });
+        if let Some(error) = server.error() {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+    }
+}
+
+/// The latest sample with the time it was received, as sent to the clients
+#[derive(Serialize, Deserialize)]
+struct Sample {
+    time: u64,
+    data: Data,
+}
+
+/// Embedded HTTP server handing the already parsed samples to other dashboards, so only one
+/// machine talks to the car
+#[derive(Default)]
+pub struct MirrorServer {
+    /// Port of the running listener and the flag stopping it
+    listener: Option<(u16, Arc<AtomicBool>)>,
+    latest: Arc<Mutex<Option<String>>>,
+    error: Arc<Mutex<Option<String>>>,
+}
+
+impl MirrorServer {
+    pub fn publish(&mut self, config: &MirrorConfig, data: &Data, time: u128) {
+        if !config.serve {
+            self.stop();
+            return;
+        }
+        if self
+            .listener
+            .as_ref()
+            .is_none_or(|(p, _)| *p != config.port)
+        {
+            self.stop();
+            self.start(config.port);
+        }
+
+        let sample = Sample {
+            time: time as u64,
+            data: data.clone(),
+        };
+        match serde_json::to_string(&sample) {
+            Ok(json) => *self.latest.lock().unwrap() = Some(json),
+            Err(e) => *self.error.lock().unwrap() = Some(e.to_string()),
+        }
+    }
+
+    /// The error of the listener, cleared when it is restarted
+    pub fn error(&self) -> Option<String> {
+        self.error.lock().unwrap().clone()
+    }
+
+    fn start(&mut self, port: u16) {
+        *self.error.lock().unwrap() = None;
+        let listener = match TcpListener::bind(("0.0.0.0", port)) {
+            Ok(l) => l,
+            Err(e) => {
+                *self.error.lock().unwrap() = Some(format!("{} {port}: {e}", tr("Port")));
+                // remember the port anyway, otherwise every sample retries to bind it
+                self.listener = Some((port, Arc::new(AtomicBool::new(true))));
+                return;
+            }
+        };
+        let stop = Arc::new(AtomicBool::new(false));
+        let (latest, error, thread_stop) = (self.latest.clone(), self.error.clone(), stop.clone());
+        thread::spawn(move || {
+            if let Err(e) = serve(&listener, &latest, &thread_stop) {
+                *error.lock().unwrap() = Some(e.to_string());
+            }
+        });
+        self.listener = Some((port, stop));
+    }
+
+    fn stop(&mut self) {
+        if let Some((_, stop)) = self.listener.take() {
+            stop.store(true, Ordering::Relaxed);
+        }
+        *self.latest.lock().unwrap() = None;
+    }
+}
+
+impl Drop for MirrorServer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Accepts connections until stopped, every request is answered on its own thread
+fn serve(
+    listener: &TcpListener,
+    latest: &Arc<Mutex<Option<String>>>,
+    stop: &AtomicBool,
+) -> io::Result<()> {
+    // non-blocking, so the flag is checked regularly
+    listener.set_nonblocking(true)?;
+    while !stop.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let latest = latest.clone();
+                thread::spawn(move || {
+                    let _ = respond(stream, &latest);
+                });
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                thread::sleep(Duration::from_millis(50));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+fn respond(mut stream: TcpStream, latest: &Mutex<Option<String>>) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next(), parts.next());
+    let latest = latest.lock().unwrap().clone();
+    let (status, body) = match (method, path, latest) {
+        (Some("GET"), Some(DATA_PATH), Some(json)) => ("200 OK", json),
+        (Some("GET"), Some(DATA_PATH), None) => ("503 Service Unavailable", String::new()),
+        _ => ("404 Not Found", String::new()),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+/// Receives the samples of another dashboard instead of polling the BMS
+pub struct MirrorSource {
+    url: String,
+    /// Time of the last received sample, the same sample is never returned twice
+    last: Option<u64>,
+}
+
+impl MirrorSource {
+    /// `address` is the address of the serving dashboard, e.g. `http://192.168.0.10:8090`
+    pub fn new(address: &str) -> Self {
+        let address = address.trim().trim_end_matches('/');
+        let url = match address.contains("://") {
+            true => format!("{address}{DATA_PATH}"),
+            false => format!("http://{address}{DATA_PATH}"),
+        };
+        Self { url, last: None }
+    }
+
+    fn fetch(&self) -> anyhow::Result<Option<Sample>> {
+        let response = match ureq::get(&self.url).timeout(TIMEOUT).call() {
+            Ok(r) => r,
+            // the serving dashboard has not received anything yet
+            Err(ureq::Error::Status(503, _)) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Some(serde_json::from_reader(response.into_reader())?))
+    }
+}
+
+impl DataSource for MirrorSource {
+    /// The samples were already parsed with the options of the serving dashboard
+    fn poll(&mut self, _options: &PollOptions) -> Result<Data, api::Error> {
+        let start = Instant::now();
+        loop {
+            match self.fetch().map_err(api::Error::Fetch)? {
+                Some(sample) if self.last != Some(sample.time) => {
+                    self.last = Some(sample.time);
+                    return Ok(sample.data);
+                }
+                _ if start.elapsed() > TIMEOUT => {
+                    let message = tr("The mirrored dashboard receives no data");
+                    return Err(api::Error::Fetch(anyhow::anyhow!(message)));
+                }
+                _ => thread::sleep(Duration::from_millis(50)),
+            }
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::i18n::tr;
+use crate::recording;
+use crate::session::Session;
+
+/// A column of the recording as a MoTeC channel
+struct Channel {
+    name: String,
+    unit: &'static str,
+    /// Factor from the recorded value to the channel unit
+    scale: f64,
+    decimals: usize,
+    column: usize,
+}
+
+/// Writes a recording as `<time>.motec.csv` next to it, in the CSV layout of MoTeC i2 with the
+/// same channel names and units as the ECU logs. `utc_offset` in h moves the log date and time to
+/// the time zone of the ECU so both logs start on the same timeline.
+pub fn convert(path: &Path, utc_offset: f32) -> io::Result<PathBuf> {
+    let text = recording::read(path)?;
+    let mut lines = text.lines();
+    let header: Vec<&str> = lines.next().unwrap_or_default().split(',').collect();
+    let rows: Vec<Vec<&str>> = lines.map(|l| l.split(',').collect()).collect();
+    let times: Vec<u128> = rows
+        .iter()
+        .filter_map(|row| row.first()?.parse().ok())
+        .collect();
+    let (Some(&start), Some(&end)) = (times.first(), times.last()) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            tr("Not a recording"),
+        ));
+    };
+
+    let channels: Vec<Channel> = header
+        .iter()
+        .enumerate()
+        .skip(1)
+        .map(|(column, name)| channel(name, column))
+        .collect();
+    let cells: Vec<usize> = (0..header.len())
+        .filter(|i| header[*i].starts_with("cell_"))
+        .collect();
+    let temps: Vec<usize> = (0..header.len())
+        .filter(|i| header[*i].starts_with("temp_"))
+        .collect();
+
+    let session = path.parent().and_then(Session::load).unwrap_or_default();
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    let stem = name.split('.').next().unwrap_or_default();
+    let output = path.with_file_name(format!("{stem}.motec.csv"));
+    let mut file = BufWriter::new(File::create(&output)?);
+
+    let local = start as i128 + (utc_offset as f64 * 3_600_000.0) as i128;
+    let (date, time) = date_time(local.max(0) as u128);
+    let duration = (end - start) as f64 / 1000.0;
+    let rate = match times.len() {
+        0 | 1 => 0.0,
+        n => (n - 1) as f64 / duration.max(0.001),
+    };
+    let text = |s: &str| s.replace(['"', '\n'], " ");
+    writeln!(file, "\"Format\",\"MoTeC CSV File\",,,\"Workbook\",\"\"")?;
+    writeln!(
+        file,
+        "\"Venue\",\"{}\",,,\"Worksheet\",\"\"",
+        text(&session.name)
+    )?;
+    writeln!(
+        file,
+        "\"Vehicle\",\"{}\",,,\"Vehicle Desc\",\"\"",
+        text(&session.car)
+    )?;
+    writeln!(
+        file,
+        "\"Driver\",\"{}\",,,\"Engine ID\",\"\"",
+        text(&session.driver)
+    )?;
+    writeln!(file, "\"Device\",\"BMS\",,,\"Session\",\"\"")?;
+    writeln!(
+        file,
+        "\"Comment\",\"{}\",,,\"Origin Time\",\"0.000\",\"s\"",
+        text(&session.notes)
+    )?;
+    writeln!(
+        file,
+        "\"Log Date\",\"{date}\",,,\"Start Time\",\"0.000\",\"s\""
+    )?;
+    writeln!(
+        file,
+        "\"Log Time\",\"{time}\",,,\"End Time\",\"{duration:.3}\",\"s\""
+    )?;
+    writeln!(
+        file,
+        "\"Sample Rate\",\"{rate:.0}\",\"Hz\",,\"Start Distance\",\"0\",\"m\""
+    )?;
+    writeln!(
+        file,
+        "\"Duration\",\"{duration:.3}\",\"s\",,\"End Distance\",\"0\",\"m\""
+    )?;
+    writeln!(file, "\"Range\",\"entire outing\",,,,")?;
+    writeln!(file, "\"Beacon Markers\",")?;
+    writeln!(file)?;
+    writeln!(file)?;
+
+    let names = channels.iter().map(|c| c.name.clone());
+    let units = channels.iter().map(|c| c.unit);
+    let quote = |s: &str| format!("\"{s}\"");
+    let mut line = vec![quote("Time")];
+    line.extend(names.map(|n| quote(&n)));
+    line.extend(SUMMARY.iter().map(|(name, _)| quote(name)));
+    writeln!(file, "{}", line.join(","))?;
+    let mut line = vec![quote("s")];
+    line.extend(units.map(quote));
+    line.extend(SUMMARY.iter().map(|(_, unit)| quote(unit)));
+    writeln!(file, "{}", line.join(","))?;
+    writeln!(file)?;
+    writeln!(file)?;
+
+    for row in &rows {
+        let Some(time) = row.first().and_then(|t| t.parse::<u128>().ok()) else {
+            continue;
+        };
+        let mut line = vec![format!("{:.3}", time.saturating_sub(start) as f64 / 1000.0)];
+        for channel in &channels {
+            let value = row.get(channel.column).and_then(|v| v.parse::<f64>().ok());
+            // MoTeC has no empty values, missing GPS fixes and derived channels are written as 0
+            let value = value.unwrap_or_default() * channel.scale;
+            line.push(format!("{value:.*}", channel.decimals));
+        }
+        let parse = |columns: &[usize]| -> Vec<f64> {
+            columns
+                .iter()
+                .filter_map(|i| row.get(*i)?.parse().ok())
+                .collect()
+        };
+        let voltages = parse(&cells);
+        let min = voltages.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = voltages.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let temps = parse(&temps);
+        let max_temp = temps.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        match voltages.is_empty() {
+            true => line.extend(["0.000", "0.000", "0"].map(String::from)),
+            false => line.extend([
+                format!("{:.3}", min / 1000.0),
+                format!("{:.3}", max / 1000.0),
+                format!("{:.0}", max - min),
+            ]),
+        }
+        match temps.is_empty() {
+            true => line.push("0.0".into()),
+            false => line.push(format!("{max_temp:.1}")),
+        }
+        writeln!(file, "{}", line.join(","))?;
+    }
+    file.flush()?;
+    Ok(output)
+}
+
+/// Channels computed from the cells of every sample
+const SUMMARY: [(&str, &str); 4] = [
+    ("BMS Cell Voltage Min", "V"),
+    ("BMS Cell Voltage Max", "V"),
+    ("BMS Cell Voltage Delta", "mV"),
+    ("BMS Cell Temp Max", "C"),
+];
+
+/// MoTeC channel name and unit of a recording column
+fn channel(name: &str, column: usize) -> Channel {
+    let (name, unit, scale, decimals) = match name {
+        "voltage" => ("BMS Pack Voltage".into(), "V", 1.0, 2),
+        "current" => ("BMS Pack Current".into(), "A", 0.001, 2),
+        "state_of_charge" => ("BMS SOC".into(), "%", 1.0, 1),
+        "speed" => ("GPS Speed".into(), "km/h", 1.0, 1),
+        "latitude" => ("GPS Latitude".into(), "deg", 1.0, 7),
+        "longitude" => ("GPS Longitude".into(), "deg", 1.0, 7),
+        "bms_uptime" => ("BMS Uptime".into(), "s", 0.001, 3),
+        name => match (name.strip_prefix("cell_"), name.strip_prefix("temp_")) {
+            (Some(i), _) => (format!("BMS Cell Voltage {i:0>3}"), "V", 0.001, 3),
+            (_, Some(i)) => (format!("BMS Cell Temp {i:0>2}"), "C", 1.0, 1),
+            _ => {
+                let name = name.strip_prefix("derived_").unwrap_or(name);
+                (format!("BMS {}", name.replace('_', " ")), "", 1.0, 3)
+            }
+        },
+    };
+    Channel {
+        name,
+        unit,
+        scale,
+        decimals,
+        column,
+    }
+}
+
+/// Date as dd/mm/yyyy and time as hh:mm:ss of a Unix time in ms
+fn date_time(time: u128) -> (String, String) {
+    let seconds = (time / 1000) as i64;
+    let (days, seconds) = (seconds.div_euclid(86_400), seconds.rem_euclid(86_400));
+    // civil date from days since 1970-01-01, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    (
+        format!("{day:02}/{month:02}/{year}"),
+        format!(
+            "{:02}:{:02}:{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        ),
+    )
+}
@@ -0,0 +1,89 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use egui::{Button, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::tr;
+
+/// Physical cell labels used by the mechanics instead of the electrical index of the BMS
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CellNumbering {
+    pub physical: bool,
+    /// File the mapping was loaded from
+    pub path: String,
+    /// Physical label of every cell, indexed by the 0-based electrical index
+    pub labels: Vec<Option<String>>,
+    #[serde(skip)]
+    error: Option<String>,
+}
+
+impl CellNumbering {
+    /// The label shown on the cell tile, falls back to the electrical number for unmapped cells
+    pub fn label(&self, cell_index: usize) -> String {
+        if self.physical {
+            if let Some(Some(label)) = self.labels.get(cell_index) {
+                return label.clone();
+            }
+        }
+        (cell_index + 1).to_string()
+    }
+
+    /// Reads a mapping file with one `electrical,physical` pair per line, e.g. `37,C3-1`. The
+    /// electrical number is 1-based like the cell labels, lines starting with `#` are ignored.
+    /// Numbers beyond the `cells` of the pack are rejected.
+    fn load(path: &Path, cells: usize) -> io::Result<Vec<Option<String>>> {
+        let text = fs::read_to_string(path)?;
+        let mut labels = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = || {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{} {}: {line}", tr("Invalid line"), i + 1),
+                )
+            };
+            let (electrical, physical) = line.split_once(',').ok_or_else(invalid)?;
+            let index = match electrical.trim().parse::<usize>() {
+                Ok(n) if n > 0 && n <= cells => n - 1,
+                _ => return Err(invalid()),
+            };
+            if labels.len() <= index {
+                labels.resize(index + 1, None);
+            }
+            labels[index] = Some(physical.trim().to_string());
+        }
+        Ok(labels)
+    }
+
+    /// `cells` of the connected pack, 0 while not connected
+    pub fn settings(&mut self, ui: &mut Ui, cells: usize) {
+        ui.checkbox(&mut self.physical, tr("Show physical cell numbers"));
+        ui.horizontal(|ui| {
+            ui.label(tr("Mapping file"));
+            ui.text_edit_singleline(&mut self.path);
+            let load = ui
+                .add_enabled(cells > 0, Button::new(tr("Load")))
+                .on_disabled_hover_text(tr("Connect to the pack to check the cell numbers"));
+            if load.clicked() {
+                match Self::load(Path::new(&self.path), cells) {
+                    Ok(labels) => {
+                        self.labels = labels;
+                        self.error = None;
+                    }
+                    Err(e) => self.error = Some(e.to_string()),
+                }
+            }
+        });
+        let mapped = self.labels.iter().flatten().count();
+        ui.label(format!("{mapped} {}", tr("cells mapped")));
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+    }
+}
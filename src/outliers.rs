@@ -0,0 +1,149 @@
+use egui::{Grid, ScrollArea, Window};
+
+use crate::anomaly::DivergingCell;
+use crate::api::Data;
+use crate::i18n::tr;
+use crate::inventory::CellInventory;
+use crate::trend::{TrendConfig, WeakestCell};
+use crate::units;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Reason {
+    /// Below the alarm voltage
+    Undervoltage,
+    Diverging,
+    /// Projected to reach the alarm voltage soon
+    Trend,
+}
+
+impl Reason {
+    fn name(self) -> &'static str {
+        match self {
+            Reason::Undervoltage => tr("Below alarm voltage"),
+            Reason::Diverging => tr("Diverging"),
+            Reason::Trend => tr("Alarm projected"),
+        }
+    }
+}
+
+pub struct Outlier {
+    pub index: usize,
+    pub reason: Reason,
+    /// Unix time in ms of the first and the last time the cell was flagged
+    pub first: u128,
+    pub last: u128,
+    pub count: usize,
+    /// Lowest voltage in mV while flagged
+    pub min_voltage: u16,
+}
+
+/// Every cell flagged during the session stays listed until it is acknowledged, so short
+/// excursions nobody saw live still get reviewed
+#[derive(Default)]
+pub struct OutlierList {
+    pub outliers: Vec<Outlier>,
+    pub show: bool,
+}
+
+impl OutlierList {
+    pub fn update(
+        &mut self,
+        data: &Data,
+        trend: &TrendConfig,
+        diverging: &[DivergingCell],
+        weakest: Option<&WeakestCell>,
+        time: u128,
+    ) {
+        let voltage = &data.ucell.cell_voltage;
+        for (i, v) in voltage.iter().enumerate() {
+            if *v < trend.alarm_voltage {
+                self.flag(i, Reason::Undervoltage, *v, time);
+            }
+        }
+        for cell in diverging {
+            let v = voltage.get(cell.index).copied().unwrap_or(u16::MAX);
+            self.flag(cell.index, Reason::Diverging, v, time);
+        }
+        if let Some(weakest) = weakest.filter(|w| w.is_warning(trend)) {
+            self.flag(weakest.index, Reason::Trend, weakest.voltage, time);
+        }
+    }
+
+    fn flag(&mut self, index: usize, reason: Reason, voltage: u16, time: u128) {
+        let existing = self
+            .outliers
+            .iter_mut()
+            .find(|o| o.index == index && o.reason == reason);
+        match existing {
+            Some(outlier) => {
+                outlier.last = time;
+                outlier.count += 1;
+                outlier.min_voltage = outlier.min_voltage.min(voltage);
+            }
+            None => self.outliers.push(Outlier {
+                index,
+                reason,
+                first: time,
+                last: time,
+                count: 1,
+                min_voltage: voltage,
+            }),
+        }
+    }
+
+    pub fn window(&mut self, ctx: &egui::Context, inventory: &CellInventory, now: u128) {
+        let mut show = self.show;
+        Window::new(tr("Outliers"))
+            .id("outliers".into())
+            .open(&mut show)
+            .default_size([500.0, 300.0])
+            .show(ctx, |ui| {
+                if self.outliers.is_empty() {
+                    ui.label(tr("No cells were flagged"));
+                    return;
+                }
+                if ui.button(tr("Acknowledge all")).clicked() {
+                    self.outliers.clear();
+                    return;
+                }
+
+                let ago = |time: u128| {
+                    let seconds = now.saturating_sub(time) / 1000;
+                    format!("{}:{:02} {}", seconds / 60, seconds % 60, tr("ago"))
+                };
+                let mut acknowledged = None;
+                ScrollArea::vertical().show(ui, |ui| {
+                    Grid::new("outliers").striped(true).show(ui, |ui| {
+                        ui.strong(tr("Cell"));
+                        ui.strong(tr("Reason"));
+                        ui.strong(tr("First"));
+                        ui.strong(tr("Last"));
+                        ui.strong(tr("Count"));
+                        ui.strong(tr("Min"));
+                        ui.end_row();
+
+                        for (i, outlier) in self.outliers.iter().enumerate() {
+                            ui.label(inventory.label(outlier.index));
+                            ui.label(outlier.reason.name());
+                            ui.label(ago(outlier.first));
+                            ui.label(ago(outlier.last));
+                            ui.label(outlier.count.to_string());
+                            if outlier.min_voltage == u16::MAX {
+                                ui.label("-");
+                            } else {
+                                ui.label(units::voltage(outlier.min_voltage));
+                            }
+                            if ui.button(tr("Acknowledge")).clicked() {
+                                acknowledged = Some(i);
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+                if let Some(i) = acknowledged {
+                    self.outliers.remove(i);
+                }
+            });
+        self.show = show;
+    }
+}
@@ -0,0 +1,101 @@
+use egui::{Align2, Key, RichText, ScrollArea, TextEdit, Window};
+
+use crate::i18n::tr;
+use crate::keymap::{Action, Keymap};
+
+#[derive(Default)]
+pub struct CommandPalette {
+    pub open: bool,
+    query: String,
+    selected: usize,
+}
+
+impl CommandPalette {
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.query.clear();
+        self.selected = 0;
+    }
+
+    /// Shows the palette if it's open and returns the chosen action. `extra` are actions that
+    /// aren't part of the keymap with their labels, e.g. the heatmap profiles.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        keymap: &Keymap,
+        extra: Vec<(Action, String)>,
+    ) -> Option<Action> {
+        if !self.open {
+            return None;
+        }
+
+        let query = self.query.to_lowercase();
+        let matches: Vec<(Action, String)> = Action::ALL
+            .into_iter()
+            .filter(|a| *a != Action::CommandPalette)
+            .map(|a| (a, a.name().to_string()))
+            .chain(extra)
+            .filter(|(_, label)| fuzzy_match(&label.to_lowercase(), &query))
+            .collect();
+
+        let (up, down, enter, escape) = ctx.input(|i| {
+            (
+                i.key_pressed(Key::ArrowUp),
+                i.key_pressed(Key::ArrowDown),
+                i.key_pressed(Key::Enter),
+                i.key_pressed(Key::Escape),
+            )
+        });
+        if up {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        if down {
+            self.selected += 1;
+        }
+        self.selected = self.selected.min(matches.len().saturating_sub(1));
+
+        let mut chosen = None;
+        Window::new(tr("Command palette"))
+            .id("command_palette".into())
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_TOP, [0.0, 60.0])
+            .fixed_size([400.0, 300.0])
+            .show(ctx, |ui| {
+                let edit = ui.add(
+                    TextEdit::singleline(&mut self.query)
+                        .hint_text(tr("Search commands..."))
+                        .desired_width(f32::INFINITY),
+                );
+                edit.request_focus();
+                ui.separator();
+
+                ScrollArea::vertical().show(ui, |ui| {
+                    for (i, (action, label)) in matches.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            let selected = i == self.selected;
+                            if ui.selectable_label(selected, label).clicked() {
+                                chosen = Some(*action);
+                            }
+                            ui.label(RichText::new(keymap.format(ctx, *action)).weak());
+                        });
+                    }
+                });
+            });
+
+        if enter {
+            chosen = matches.get(self.selected).map(|(action, _)| *action);
+        }
+        if chosen.is_some() || escape {
+            self.open = false;
+        }
+        chosen
+    }
+}
+
+/// Whether all characters of the query appear in the text in the same order
+fn fuzzy_match(text: &str, query: &str) -> bool {
+    let mut chars = text.chars();
+    query.chars().all(|q| chars.any(|c| c == q))
+}
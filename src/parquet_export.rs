@@ -0,0 +1,106 @@
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use parquet::basic::Compression;
+use parquet::column::writer::ColumnWriter;
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+
+use crate::recording;
+
+/// Rows per row group, keeps the memory usage bounded for long recordings
+const ROW_GROUP_SIZE: usize = 10_000;
+
+/// Writes a recording as `<time>.parquet` next to it, with one typed column per value
+pub fn convert(path: &Path) -> io::Result<PathBuf> {
+    let text = recording::read(path)?;
+    let mut lines = text.lines();
+    let header: Vec<&str> = lines.next().unwrap_or_default().split(',').collect();
+    let rows: Vec<Vec<&str>> = lines.map(|l| l.split(',').collect()).collect();
+
+    let columns: Vec<String> = header.iter().map(|name| column_type(name)).collect();
+    let schema = format!("message recording {{ {} }}", columns.join(" "));
+    let schema = Arc::new(parse_message_type(&schema).map_err(io::Error::other)?);
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    let stem = name.split('.').next().unwrap_or_default();
+    let output = path.with_file_name(format!("{stem}.parquet"));
+    let file = File::create(&output)?;
+    let mut writer =
+        SerializedFileWriter::new(file, schema, Arc::new(properties)).map_err(io::Error::other)?;
+
+    for chunk in rows.chunks(ROW_GROUP_SIZE) {
+        let mut row_group = writer.next_row_group().map_err(io::Error::other)?;
+        let mut index = 0;
+        while let Some(mut column) = row_group.next_column().map_err(io::Error::other)? {
+            let cells = chunk.iter().map(|row| row.get(index).copied());
+            let result = match column.untyped() {
+                ColumnWriter::Int64ColumnWriter(w) => {
+                    let (values, levels) = parse::<i64>(cells);
+                    w.write_batch(&values, Some(&levels), None)
+                }
+                ColumnWriter::Int32ColumnWriter(w) => {
+                    let (values, levels) = parse::<i32>(cells);
+                    w.write_batch(&values, Some(&levels), None)
+                }
+                ColumnWriter::FloatColumnWriter(w) => {
+                    let (values, levels) = parse::<f32>(cells);
+                    w.write_batch(&values, Some(&levels), None)
+                }
+                ColumnWriter::DoubleColumnWriter(w) => {
+                    let (values, levels) = parse::<f64>(cells);
+                    w.write_batch(&values, Some(&levels), None)
+                }
+                _ => unreachable!("the schema only contains numeric columns"),
+            };
+            result.map_err(io::Error::other)?;
+            column.close().map_err(io::Error::other)?;
+            index += 1;
+        }
+        row_group.close().map_err(io::Error::other)?;
+    }
+    writer.close().map_err(io::Error::other)?;
+    Ok(output)
+}
+
+/// Schema of a recording column, every column but the time may be empty
+fn column_type(name: &str) -> String {
+    let kind = match name {
+        "time" | "bms_uptime" => "INT64",
+        "speed" | "latitude" | "longitude" => "DOUBLE",
+        name if name.starts_with("cell_") => "INT32",
+        _ => "FLOAT",
+    };
+    let annotation = match name {
+        "time" => " (TIMESTAMP(MILLIS,true))",
+        name if name.starts_with("cell_") => " (INTEGER(16,false))",
+        _ => "",
+    };
+    format!("OPTIONAL {kind} {name}{annotation};")
+}
+
+/// The present values and the definition level of every row
+fn parse<'a, T: FromStr>(cells: impl Iterator<Item = Option<&'a str>>) -> (Vec<T>, Vec<i16>) {
+    let mut values = Vec::new();
+    let mut levels = Vec::new();
+    for cell in cells {
+        match cell.and_then(|c| c.parse().ok()) {
+            Some(v) => {
+                values.push(v);
+                levels.push(1);
+            }
+            None => levels.push(0),
+        }
+    }
+    (values, levels)
+}
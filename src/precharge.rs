@@ -0,0 +1,168 @@
+use egui::{DragValue, Grid, Ui, Window};
+use egui_plot::{HLine, Legend, Line, Plot, PlotPoints};
+use serde::{Deserialize, Serialize};
+
+use crate::api::Data;
+use crate::i18n::tr;
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrechargeConfig {
+    pub enabled: bool,
+    /// Duration of a capture in s
+    pub duration: u64,
+    /// Fraction of the pack voltage in % the DC link has to reach for a healthy precharge
+    pub target: f32,
+    /// Maximum time in s to reach the target
+    pub max_time: f32,
+}
+
+impl Default for PrechargeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            duration: 5,
+            target: 95.0,
+            max_time: 3.0,
+        }
+    }
+}
+
+impl PrechargeConfig {
+    pub fn settings(&mut self, ui: &mut Ui) {
+        ui.checkbox(&mut self.enabled, tr("Capture precharge"));
+        Grid::new("precharge_config").show(ui, |ui| {
+            ui.label(tr("Capture duration"));
+            ui.add(
+                DragValue::new(&mut self.duration)
+                    .clamp_range(1..=60)
+                    .suffix(" s"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Precharge target"));
+            ui.add(
+                DragValue::new(&mut self.target)
+                    .clamp_range(50.0..=100.0)
+                    .suffix(" %"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Maximum precharge time"));
+            ui.add(
+                DragValue::new(&mut self.max_time)
+                    .clamp_range(0.1..=60.0)
+                    .speed(0.1)
+                    .suffix(" s"),
+            );
+            ui.end_row();
+        });
+    }
+}
+
+/// DC link voltage ramp after the precharge relay closed
+pub struct Capture {
+    /// Unix time in ms of the precharge relay closing
+    pub start: u128,
+    /// Pack voltage in V
+    pub pack_voltage: f32,
+    /// Time in s since the start and DC link voltage in V
+    pub points: Vec<(f32, f32)>,
+}
+
+impl Capture {
+    /// Time in s until the DC link reached `target` % of the pack voltage
+    pub fn time_to(&self, target: f32) -> Option<f32> {
+        let threshold = self.pack_voltage * target / 100.0;
+        self.points
+            .iter()
+            .find(|(_, v)| *v >= threshold)
+            .map(|(t, _)| *t)
+    }
+}
+
+#[derive(Default)]
+pub struct PrechargeMonitor {
+    precharge: Option<bool>,
+    active: Option<Capture>,
+    pub last: Option<Capture>,
+    pub show: bool,
+}
+
+impl PrechargeMonitor {
+    /// Whether a capture is running and data should be polled as fast as possible
+    pub fn is_capturing(&self) -> bool {
+        self.active.is_some()
+    }
+
+    pub fn update(&mut self, config: &PrechargeConfig, data: &Data, time: u128) {
+        let (Some(relays), Some(dc_link)) = (data.main.relays, data.main.dc_link_voltage) else {
+            return;
+        };
+
+        let started = self.precharge == Some(false) && relays.precharge;
+        self.precharge = Some(relays.precharge);
+        if config.enabled && started {
+            self.active = Some(Capture {
+                start: time,
+                pack_voltage: data.main.voltage,
+                points: Vec::new(),
+            });
+        }
+
+        let Some(capture) = &mut self.active else {
+            return;
+        };
+        let elapsed = time.saturating_sub(capture.start) as f32 / 1000.0;
+        capture.points.push((elapsed, dc_link));
+        if elapsed >= config.duration as f32 {
+            self.last = self.active.take();
+            self.show = true;
+        }
+    }
+
+    pub fn window(&mut self, ctx: &egui::Context, config: &PrechargeConfig) {
+        let last = &self.last;
+        Window::new(tr("Precharge"))
+            .id("precharge".into())
+            .open(&mut self.show)
+            .default_size([500.0, 350.0])
+            .show(ctx, |ui| {
+                let Some(capture) = last else {
+                    ui.label(tr("No precharge captured yet"));
+                    return;
+                };
+
+                match capture.time_to(config.target) {
+                    Some(t) if t <= config.max_time => {
+                        ui.label(format!("{}: {t:.2} s", tr("Time to target")));
+                    }
+                    Some(t) => {
+                        ui.colored_label(
+                            ui.visuals().error_fg_color,
+                            format!("{}: {t:.2} s", tr("Precharge too slow")),
+                        );
+                    }
+                    None => {
+                        ui.colored_label(
+                            ui.visuals().error_fg_color,
+                            tr("Precharge target not reached"),
+                        );
+                    }
+                }
+
+                let ramp: PlotPoints = capture
+                    .points
+                    .iter()
+                    .map(|(t, v)| [*t as f64, *v as f64])
+                    .collect();
+                let target = capture.pack_voltage * config.target / 100.0;
+                Plot::new("precharge_ramp")
+                    .legend(Legend::default())
+                    .show(ui, |plot| {
+                        plot.line(Line::new(ramp).name(tr("DC link voltage (V)")));
+                        plot.hline(HLine::new(target).name(tr("Precharge target")));
+                    });
+            });
+    }
+}
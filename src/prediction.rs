@@ -0,0 +1,129 @@
+use egui::{DragValue, Grid, RichText, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::history::{slope, History};
+use crate::i18n::tr;
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct PredictionConfig {
+    pub enabled: bool,
+    /// Usable pack energy in Wh at 100 % state of charge
+    pub pack_energy: f32,
+    /// Minimum cell voltage cutoff in mV
+    pub cutoff_voltage: u16,
+    /// Averaging window in s
+    pub window: u64,
+}
+
+impl Default for PredictionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            pack_energy: 7000.0,
+            cutoff_voltage: 3000,
+            window: 60,
+        }
+    }
+}
+
+impl PredictionConfig {
+    pub fn settings(&mut self, ui: &mut Ui) {
+        ui.checkbox(&mut self.enabled, tr("Show prediction"));
+        Grid::new("prediction_config").show(ui, |ui| {
+            ui.label(tr("Usable pack energy"));
+            ui.add(
+                DragValue::new(&mut self.pack_energy)
+                    .clamp_range(100.0..=100000.0)
+                    .suffix(" Wh"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Cutoff voltage"));
+            ui.add(
+                DragValue::new(&mut self.cutoff_voltage)
+                    .clamp_range(2000..=4000)
+                    .suffix(" mV"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Averaging window"));
+            ui.add(
+                DragValue::new(&mut self.window)
+                    .clamp_range(5..=1800)
+                    .suffix(" s"),
+            );
+            ui.end_row();
+        });
+    }
+}
+
+pub struct Prediction {
+    /// Average discharge power in W
+    pub power: f32,
+    /// Remaining usable energy in Wh
+    pub remaining_energy: f32,
+    /// Minutes until the usable energy is depleted at the average power
+    pub energy_minutes: Option<f32>,
+    /// Minutes until the weakest cell reaches the cutoff voltage at its current trend
+    pub cutoff_minutes: Option<f32>,
+}
+
+impl Prediction {
+    pub fn compute(config: &PredictionConfig, history: &History, soc: f32) -> Option<Self> {
+        let window = config.window as u128 * 1000;
+        let samples: Vec<_> = history.window(window).collect();
+        let last = samples.last()?;
+
+        let power = samples
+            .iter()
+            .map(|s| s.voltage * s.current / 1000.0)
+            .sum::<f32>()
+            / samples.len() as f32;
+        let remaining_energy = config.pack_energy * soc.clamp(0.0, 100.0) / 100.0;
+        let energy_minutes = (power > 0.0).then(|| remaining_energy / power * 60.0);
+
+        let start = samples[0].time;
+        let min_voltage_slope = slope(
+            samples
+                .iter()
+                .map(|s| ((s.time - start) as f64 / 1000.0, s.min_voltage as f64)),
+        );
+        let cutoff_minutes = match min_voltage_slope {
+            Some(slope) if slope < 0.0 => {
+                let headroom = last.min_voltage as f64 - config.cutoff_voltage as f64;
+                Some((headroom.max(0.0) / -slope / 60.0) as f32)
+            }
+            _ => None,
+        };
+
+        Some(Self {
+            power,
+            remaining_energy,
+            energy_minutes,
+            cutoff_minutes,
+        })
+    }
+
+    pub fn show(&self, ui: &mut Ui, font_size: f32) {
+        let minutes = |m: Option<f32>| match m {
+            Some(m) => format!("{m:.1} min"),
+            None => "-".into(),
+        };
+        ui.horizontal(|ui| {
+            let value = |ui: &mut Ui, name: &str, value: String| {
+                ui.label(RichText::new(name).size(font_size * 0.6));
+                ui.label(RichText::new(value).size(font_size).strong());
+                ui.add_space(font_size);
+            };
+            value(
+                ui,
+                tr("Remaining energy"),
+                format!("{:.0} Wh", self.remaining_energy),
+            );
+            value(ui, tr("Power"), format!("{:.1} kW", self.power / 1000.0));
+            value(ui, tr("Time at current pace"), minutes(self.energy_minutes));
+            value(ui, tr("Time to cutoff"), minutes(self.cutoff_minutes));
+        });
+    }
+}
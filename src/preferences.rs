@@ -0,0 +1,360 @@
+use egui::{TextEdit, Ui};
+
+use crate::i18n::tr;
+
+/// Categories of the settings window
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum Tab {
+    #[default]
+    Connection,
+    Display,
+    Alarms,
+    Logging,
+    Analysis,
+}
+
+impl Tab {
+    const ALL: [Tab; 5] = [
+        Tab::Connection,
+        Tab::Display,
+        Tab::Alarms,
+        Tab::Logging,
+        Tab::Analysis,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Tab::Connection => tr("Connection"),
+            Tab::Display => tr("Display"),
+            Tab::Alarms => tr("Alarms"),
+            Tab::Logging => tr("Logging"),
+            Tab::Analysis => tr("Analysis"),
+        }
+    }
+}
+
+/// A heading of the settings window, drawn by the app since most sections edit its fields
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    Polling,
+    BmsRequests,
+    IntegrityChecks,
+    LiveStreaming,
+    Mirror,
+    Gps,
+    Fleet,
+    Launch,
+    Language,
+    Appearance,
+    Window,
+    Layout,
+    Units,
+    FontSizes,
+    Heatmap,
+    CellNumbering,
+    TemperatureSensors,
+    CurrentDirection,
+    Smoothing,
+    FuelGauge,
+    KeyboardShortcuts,
+    Alarms,
+    CurrentLimits,
+    Insulation,
+    Anomalies,
+    Script,
+    EndOfCharge,
+    Precharge,
+    Recording,
+    History,
+    Laps,
+    DriverMessages,
+    TestProcedure,
+    Heartbeat,
+    VoltageSag,
+    StateOfCharge,
+    DerivedChannels,
+    Prediction,
+    Trend,
+    Charging,
+    Cooling,
+    StateOfHealth,
+    Balancing,
+}
+
+impl Section {
+    const ALL: [Section; 43] = [
+        Section::Polling,
+        Section::BmsRequests,
+        Section::IntegrityChecks,
+        Section::LiveStreaming,
+        Section::Mirror,
+        Section::Gps,
+        Section::Fleet,
+        Section::Launch,
+        Section::Language,
+        Section::Appearance,
+        Section::Window,
+        Section::Layout,
+        Section::Units,
+        Section::FontSizes,
+        Section::Heatmap,
+        Section::CellNumbering,
+        Section::TemperatureSensors,
+        Section::CurrentDirection,
+        Section::Smoothing,
+        Section::FuelGauge,
+        Section::KeyboardShortcuts,
+        Section::Alarms,
+        Section::CurrentLimits,
+        Section::Insulation,
+        Section::Anomalies,
+        Section::Script,
+        Section::EndOfCharge,
+        Section::Precharge,
+        Section::Recording,
+        Section::History,
+        Section::Laps,
+        Section::DriverMessages,
+        Section::TestProcedure,
+        Section::Heartbeat,
+        Section::VoltageSag,
+        Section::StateOfCharge,
+        Section::DerivedChannels,
+        Section::Prediction,
+        Section::Trend,
+        Section::Charging,
+        Section::Cooling,
+        Section::StateOfHealth,
+        Section::Balancing,
+    ];
+
+    /// The heading, also matched by the search
+    pub fn name(self) -> &'static str {
+        match self {
+            Section::Polling => tr("Polling"),
+            Section::BmsRequests => tr("BMS requests"),
+            Section::IntegrityChecks => tr("Integrity checks"),
+            Section::LiveStreaming => tr("Live streaming"),
+            Section::Mirror => tr("Mirror"),
+            Section::Gps => "GPS",
+            Section::Fleet => tr("Fleet"),
+            Section::Launch => tr("Launch"),
+            Section::Language => tr("Language"),
+            Section::Appearance => tr("Appearance"),
+            Section::Window => tr("Window"),
+            Section::Layout => tr("Layout"),
+            Section::Units => tr("Units"),
+            Section::FontSizes => tr("Font sizes"),
+            Section::Heatmap => tr("Heatmap"),
+            Section::CellNumbering => tr("Cell numbering"),
+            Section::TemperatureSensors => tr("Temperature sensors"),
+            Section::CurrentDirection => tr("Current direction"),
+            Section::Smoothing => tr("Smoothing"),
+            Section::FuelGauge => tr("Fuel gauge"),
+            Section::KeyboardShortcuts => tr("Keyboard shortcuts"),
+            Section::Alarms => tr("Alarms"),
+            Section::CurrentLimits => tr("Current limits"),
+            Section::Insulation => tr("Insulation monitoring"),
+            Section::Anomalies => tr("Anomaly detection"),
+            Section::Script => tr("Script"),
+            Section::EndOfCharge => tr("End of charge"),
+            Section::Precharge => tr("Precharge"),
+            Section::Recording => tr("Recording"),
+            Section::History => tr("History"),
+            Section::Laps => tr("Laps"),
+            Section::DriverMessages => tr("Driver messages"),
+            Section::TestProcedure => tr("Test procedure"),
+            Section::Heartbeat => tr("Watchdog heartbeat"),
+            Section::VoltageSag => tr("Voltage sag"),
+            Section::StateOfCharge => tr("State of charge"),
+            Section::DerivedChannels => tr("Derived channels"),
+            Section::Prediction => tr("Prediction"),
+            Section::Trend => tr("Weakest cell trend"),
+            Section::Charging => tr("Charging"),
+            Section::Cooling => tr("Cooling"),
+            Section::StateOfHealth => tr("State of health"),
+            Section::Balancing => tr("Balancing recommendation"),
+        }
+    }
+
+    fn tab(self) -> Tab {
+        match self {
+            Section::Polling
+            | Section::BmsRequests
+            | Section::IntegrityChecks
+            | Section::LiveStreaming
+            | Section::Mirror
+            | Section::Gps
+            | Section::Fleet
+            | Section::Launch => Tab::Connection,
+            Section::Language
+            | Section::Appearance
+            | Section::Window
+            | Section::Layout
+            | Section::Units
+            | Section::FontSizes
+            | Section::Heatmap
+            | Section::CellNumbering
+            | Section::TemperatureSensors
+            | Section::CurrentDirection
+            | Section::Smoothing
+            | Section::FuelGauge
+            | Section::KeyboardShortcuts => Tab::Display,
+            Section::Alarms
+            | Section::CurrentLimits
+            | Section::Insulation
+            | Section::Anomalies
+            | Section::Script
+            | Section::EndOfCharge
+            | Section::Precharge => Tab::Alarms,
+            Section::Recording
+            | Section::History
+            | Section::Laps
+            | Section::DriverMessages
+            | Section::TestProcedure
+            | Section::Heartbeat => Tab::Logging,
+            Section::VoltageSag
+            | Section::StateOfCharge
+            | Section::DerivedChannels
+            | Section::Prediction
+            | Section::Trend
+            | Section::Charging
+            | Section::Cooling
+            | Section::StateOfHealth
+            | Section::Balancing => Tab::Analysis,
+        }
+    }
+
+    /// Labels of the settings within the section, matched in English and the selected language
+    fn keywords(self) -> &'static [&'static str] {
+        match self {
+            Section::Polling => &["Poll rate"],
+            Section::BmsRequests => &[
+                "Firmware format",
+                "Pipeline requests",
+                "Proxy",
+                "Request headers",
+            ],
+            Section::IntegrityChecks => {
+                &["Quarantine inconsistent samples", "Pack voltage tolerance"]
+            }
+            Section::LiveStreaming => &["Push samples to a server", "Batch size", "Retries"],
+            Section::Mirror => &["Serve data to mirror clients", "Port"],
+            Section::Gps => &["Use GPS", "Serial port", "Baud rate"],
+            Section::Fleet => &["Poll interval", "Add pack"],
+            Section::Launch => &["Start minimized", "Start recording on launch"],
+            Section::Appearance => &["Theme", "Custom colors"],
+            Section::Window => &["Always on top", "Minimize instead of closing"],
+            Section::Layout => &[
+                "Stats panel",
+                "Temperatures",
+                "Stacks",
+                "Mirror horizontally",
+                "Mirror vertically",
+                "Temperature height",
+            ],
+            Section::Units => &[
+                "Cell voltage",
+                "Temperature",
+                "Voltage decimal places",
+                "Thousands separator",
+            ],
+            Section::Heatmap => &["Compensate voltage sag"],
+            Section::CurrentDirection => &["Discharge color", "Regen color", "Deadband"],
+            Section::Smoothing => &[
+                "Moving average window",
+                "Display interval",
+                "Combine samples by",
+            ],
+            Section::FuelGauge => &["Show fuel gauge in driver mode", "Empty at", "Full at"],
+            Section::Alarms => &["Overvoltage", "Undervoltage", "Overtemperature"],
+            Section::CurrentLimits => &["Peak current", "Continuous current", "Overload budget"],
+            Section::Insulation => &["Warning threshold"],
+            Section::Anomalies => &[
+                "Detect diverging cells",
+                "Minimum z-score",
+                "Highlight changed cells",
+            ],
+            Section::EndOfCharge => &[
+                "Detect the end of charge",
+                "End current",
+                "Notification URL",
+            ],
+            Section::Precharge => &["Capture precharge", "Precharge target"],
+            Section::Recording => &[
+                "Record data",
+                "Log directory",
+                "Maximum file size",
+                "Compress completed files",
+                "Convert completed files to Parquet",
+                "Export completed files to MoTeC CSV",
+                "Save a screenshot every",
+            ],
+            Section::History => &["Full rate", "Downsampled interval", "Memory limit"],
+            Section::Laps => &["Finish line radius", "Minimum lap time"],
+            Section::Heartbeat => &["Heartbeat file", "Toggle GPIO"],
+            Section::VoltageSag => &["Resistance window", "Default cell resistance"],
+            Section::Prediction => &["Show prediction", "Usable pack energy", "Cutoff voltage"],
+            Section::Trend => &["Alarm voltage", "Tracking window", "Warning horizon"],
+            Section::Charging => &["Charge current", "Full cell voltage", "Balancing threshold"],
+            Section::Cooling => &["Monitor cooling effectiveness"],
+            Section::StateOfHealth => &["Capacity fade margin", "Minimum cycle charge"],
+            Section::Balancing => &["Target delta", "Cell capacity", "Bleed current"],
+            Section::Language
+            | Section::FontSizes
+            | Section::CellNumbering
+            | Section::TemperatureSensors
+            | Section::KeyboardShortcuts
+            | Section::Script
+            | Section::DriverMessages
+            | Section::TestProcedure
+            | Section::StateOfCharge
+            | Section::DerivedChannels => &[],
+        }
+    }
+
+    fn matches(self, query: &str) -> bool {
+        let matches = |s: &str| s.to_lowercase().contains(query);
+        matches(self.name()) || self.keywords().iter().any(|k| matches(k) || matches(tr(k)))
+    }
+}
+
+/// State of the settings window
+#[derive(Default)]
+pub struct Preferences {
+    tab: Tab,
+    search: String,
+}
+
+impl Preferences {
+    /// Shows the search field and the tabs, returns the sections to draw below. A search looks
+    /// through every tab.
+    pub fn header(&mut self, ui: &mut Ui) -> Vec<Section> {
+        ui.add(
+            TextEdit::singleline(&mut self.search)
+                .hint_text(tr("Search settings..."))
+                .desired_width(f32::INFINITY),
+        );
+        let query = self.search.trim().to_lowercase();
+        ui.add_enabled_ui(query.is_empty(), |ui| {
+            ui.horizontal(|ui| {
+                for tab in Tab::ALL {
+                    ui.selectable_value(&mut self.tab, tab, tab.name());
+                }
+            });
+        });
+        ui.separator();
+
+        if query.is_empty() {
+            Section::ALL
+                .into_iter()
+                .filter(|s| s.tab() == self.tab)
+                .collect()
+        } else {
+            Section::ALL
+                .into_iter()
+                .filter(|s| s.matches(&query))
+                .collect()
+        }
+    }
+}
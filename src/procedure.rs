@@ -0,0 +1,474 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use egui::{
+    Color32, ComboBox, DragValue, Grid, ProgressBar, RichText, ScrollArea, TextEdit, Ui, Window,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::api::Data;
+use crate::i18n::tr;
+use crate::markers::{Marker, Markers};
+use crate::soc::OcvConfig;
+
+/// Time in ms into a hold step after which the loaded cell voltages are taken for the resistance
+const SETTLE_TIME: u128 = 2000;
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum StepKind {
+    /// Logs while the operator holds a load for the duration
+    Hold,
+    /// Logs while the pack rests for the duration
+    Wait,
+    /// Places a marker once the operator confirms
+    Mark,
+}
+
+impl StepKind {
+    const ALL: [StepKind; 3] = [StepKind::Hold, StepKind::Wait, StepKind::Mark];
+
+    fn name(self) -> &'static str {
+        match self {
+            StepKind::Hold => tr("Hold"),
+            StepKind::Wait => tr("Wait"),
+            StepKind::Mark => tr("Mark"),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Step {
+    pub kind: StepKind,
+    /// in s, unused by marks
+    pub duration: u64,
+    /// Shown to the operator before the step starts, e.g. `Set the load to 20 A`
+    pub prompt: String,
+}
+
+impl Step {
+    fn new(kind: StepKind, duration: u64, prompt: &str) -> Self {
+        Self {
+            kind,
+            duration,
+            prompt: prompt.to_string(),
+        }
+    }
+}
+
+/// Bench characterization profile stepped through by the operator
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProcedureConfig {
+    pub steps: Vec<Step>,
+}
+
+impl Default for ProcedureConfig {
+    fn default() -> Self {
+        Self {
+            steps: vec![
+                Step::new(
+                    StepKind::Wait,
+                    300,
+                    "Switch off the load and let the pack rest",
+                ),
+                Step::new(StepKind::Hold, 30, "Set the load to 20 A"),
+                Step::new(StepKind::Wait, 60, "Switch off the load"),
+                Step::new(StepKind::Hold, 1800, "Set the load to 40 A"),
+                Step::new(
+                    StepKind::Wait,
+                    600,
+                    "Switch off the load and let the pack rest",
+                ),
+                Step::new(StepKind::Mark, 0, "Test finished"),
+            ],
+        }
+    }
+}
+
+impl ProcedureConfig {
+    pub fn settings(&mut self, ui: &mut Ui) {
+        let mut remove = None;
+        Grid::new("procedure_steps").show(ui, |ui| {
+            for (i, step) in self.steps.iter_mut().enumerate() {
+                ui.label(format!("{}.", i + 1));
+                ComboBox::from_id_source(("procedure_step", i))
+                    .selected_text(step.kind.name())
+                    .show_ui(ui, |ui| {
+                        for kind in StepKind::ALL {
+                            ui.selectable_value(&mut step.kind, kind, kind.name());
+                        }
+                    });
+                ui.add_enabled(
+                    step.kind != StepKind::Mark,
+                    DragValue::new(&mut step.duration)
+                        .clamp_range(1..=36_000)
+                        .suffix(" s"),
+                );
+                ui.add(
+                    TextEdit::singleline(&mut step.prompt)
+                        .hint_text(tr("Prompt"))
+                        .desired_width(250.0),
+                );
+                if ui.button("🗑").clicked() {
+                    remove = Some(i);
+                }
+                ui.end_row();
+            }
+        });
+        if let Some(i) = remove {
+            self.steps.remove(i);
+        }
+        if ui.button(tr("Add step")).clicked() {
+            self.steps.push(Step::new(StepKind::Wait, 60, ""));
+        }
+    }
+}
+
+/// Voltages of the cells right before and a moment into a load step
+struct Pulse {
+    rest_current: f32,
+    rest_voltage: Vec<u16>,
+    load_current: f32,
+    load_voltage: Vec<u16>,
+}
+
+struct Run {
+    path: PathBuf,
+    file: BufWriter<File>,
+    step: usize,
+    /// Unix time in ms the current step was started by the operator
+    step_start: Option<u128>,
+    /// Time of the last sample, the charge is only integrated while a step is logging
+    last_time: Option<u128>,
+    header: bool,
+    /// Discharged charge in Ah
+    charge: f32,
+    /// Cell voltages at the end of the first rest and of the latest rest after a load
+    first_rest: Option<Vec<u16>>,
+    last_rest: Option<Vec<u16>>,
+    /// Last sample of the latest wait step
+    rest: Option<(f32, Vec<u16>)>,
+    pulses: Vec<Pulse>,
+    loaded: bool,
+}
+
+/// Result of a finished test
+pub struct Summary {
+    pub path: PathBuf,
+    /// Discharged charge in Ah
+    pub charge: f32,
+    /// Capacity of every cell in Ah
+    pub capacity: Vec<Option<f32>>,
+    /// Internal resistance of every cell in mΩ
+    pub resistance: Vec<Option<f32>>,
+}
+
+impl Summary {
+    fn save(&self) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(&self.path)?);
+        writeln!(file, "cell,capacity,resistance")?;
+        let value = |v: Option<f32>| v.map_or_else(String::new, |v| format!("{v:.3}"));
+        for (i, (capacity, resistance)) in self.capacity.iter().zip(&self.resistance).enumerate() {
+            writeln!(
+                file,
+                "{},{},{}",
+                i + 1,
+                value(*capacity),
+                value(*resistance)
+            )?;
+        }
+        file.flush()
+    }
+}
+
+/// Steps through the profile and logs every sample of a running test
+#[derive(Default)]
+pub struct TestProcedure {
+    run: Option<Run>,
+    pub summary: Option<Summary>,
+    pub show: bool,
+}
+
+impl TestProcedure {
+    /// The dashboard polls as fast as possible while a step is logging
+    pub fn is_logging(&self) -> bool {
+        self.run.as_ref().is_some_and(|r| r.step_start.is_some())
+    }
+
+    fn start(&mut self, directory: &Path, now: u128) -> io::Result<()> {
+        fs::create_dir_all(directory)?;
+        let path = directory.join(format!("test_{now}.csv"));
+        let file = BufWriter::new(File::create(&path)?);
+        self.summary = None;
+        self.run = Some(Run {
+            path,
+            file,
+            step: 0,
+            step_start: None,
+            last_time: None,
+            header: false,
+            charge: 0.0,
+            first_rest: None,
+            last_rest: None,
+            rest: None,
+            pulses: Vec::new(),
+            loaded: false,
+        });
+        Ok(())
+    }
+
+    /// Logs a new sample and advances to the next step once the current one has elapsed
+    pub fn update(
+        &mut self,
+        config: &ProcedureConfig,
+        ocv: &OcvConfig,
+        data: &Data,
+        time: u128,
+    ) -> io::Result<()> {
+        let Some(run) = &mut self.run else {
+            return Ok(());
+        };
+        let Some(step_start) = run.step_start else {
+            return Ok(());
+        };
+        let Some(step) = config.steps.get(run.step) else {
+            return self.finish(ocv);
+        };
+
+        let current = data.main.current;
+        let cells = &data.ucell.cell_voltage;
+        if !run.header {
+            run.header = true;
+            write!(run.file, "time,step,current,voltage")?;
+            for i in 0..cells.len() {
+                write!(run.file, ",cell_{}", i + 1)?;
+            }
+            writeln!(run.file)?;
+        }
+        write!(
+            run.file,
+            "{time},{},{current},{}",
+            run.step + 1,
+            data.main.voltage
+        )?;
+        for v in cells {
+            write!(run.file, ",{v}")?;
+        }
+        writeln!(run.file)?;
+
+        if let Some(last_time) = run.last_time {
+            let hours = (time - last_time) as f32 / 3_600_000.0;
+            run.charge += current.max(0.0) / 1000.0 * hours;
+        }
+        run.last_time = Some(time);
+
+        match step.kind {
+            StepKind::Wait => run.rest = Some((current, cells.clone())),
+            StepKind::Hold if time - step_start >= SETTLE_TIME => {
+                if let Some((rest_current, rest_voltage)) = run.rest.take() {
+                    run.pulses.push(Pulse {
+                        rest_current,
+                        rest_voltage,
+                        load_current: current,
+                        load_voltage: cells.clone(),
+                    });
+                }
+                run.loaded = true;
+            }
+            _ => {}
+        }
+
+        if time - step_start >= step.duration as u128 * 1000 {
+            if step.kind == StepKind::Wait {
+                match run.loaded {
+                    false => run.first_rest = Some(cells.clone()),
+                    true => run.last_rest = Some(cells.clone()),
+                }
+            }
+            run.step += 1;
+            run.step_start = None;
+            run.last_time = None;
+            if run.step >= config.steps.len() {
+                return self.finish(ocv);
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, ocv: &OcvConfig) -> io::Result<()> {
+        let Some(mut run) = self.run.take() else {
+            return Ok(());
+        };
+        run.file.flush()?;
+
+        // the charge divided by the drop in state of charge between the rests
+        let cells = run.pulses.first().map_or(0, |p| p.rest_voltage.len());
+        let cells = cells.max(run.first_rest.as_ref().map_or(0, Vec::len));
+        let capacity = match (&run.first_rest, &run.last_rest) {
+            (Some(start), Some(end)) if start.len() == end.len() => start
+                .iter()
+                .zip(end)
+                .map(|(start, end)| {
+                    let drop = ocv.soc(*start)? - ocv.soc(*end)?;
+                    (drop > 0.0).then(|| run.charge / drop * 100.0)
+                })
+                .collect(),
+            _ => vec![None; cells],
+        };
+
+        // voltage drop over the current step of every pulse, averaged
+        let resistance = (0..cells)
+            .map(|i| {
+                let values: Vec<f32> = run
+                    .pulses
+                    .iter()
+                    .filter_map(|p| {
+                        let step = (p.load_current - p.rest_current) / 1000.0;
+                        let drop = *p.rest_voltage.get(i)? as f32 - *p.load_voltage.get(i)? as f32;
+                        (step.abs() > 1.0).then_some(drop / step)
+                    })
+                    .collect();
+                (!values.is_empty()).then(|| values.iter().sum::<f32>() / values.len() as f32)
+            })
+            .collect();
+
+        let summary = Summary {
+            path: run.path.with_file_name(format!(
+                "{}_summary.csv",
+                run.path.file_stem().unwrap_or_default().to_string_lossy()
+            )),
+            charge: run.charge,
+            capacity,
+            resistance,
+        };
+        let result = summary.save();
+        self.summary = Some(summary);
+        result
+    }
+
+    /// Prompts the operator for every step, returns an error message if the log couldn't be
+    /// written
+    pub fn window(
+        &mut self,
+        ctx: &egui::Context,
+        config: &ProcedureConfig,
+        ocv: &OcvConfig,
+        markers: &mut Markers,
+        directory: &Path,
+        now: u128,
+    ) -> Option<String> {
+        let mut error = None;
+        let mut show = self.show;
+        Window::new(tr("Test procedure"))
+            .id("test_procedure".into())
+            .open(&mut show)
+            .default_size([400.0, 300.0])
+            .show(ctx, |ui| match &mut self.run {
+                None => {
+                    let start = ui.add_enabled(
+                        !config.steps.is_empty(),
+                        egui::Button::new(tr("Start test")),
+                    );
+                    if start.clicked() {
+                        if let Err(e) = self.start(directory, now) {
+                            error = Some(format!("{}: {e}", tr("Error starting test")));
+                        }
+                    }
+                    if let Some(summary) = &self.summary {
+                        summary_view(ui, summary);
+                    }
+                }
+                Some(run) => {
+                    let Some(step) = config.steps.get(run.step) else {
+                        return;
+                    };
+                    ui.label(format!(
+                        "{} {}/{}: {}",
+                        tr("Step"),
+                        run.step + 1,
+                        config.steps.len(),
+                        step.kind.name()
+                    ));
+                    ui.label(RichText::new(&step.prompt).strong().size(18.0));
+                    match run.step_start {
+                        None => {
+                            let text = match step.kind {
+                                StepKind::Mark => tr("Confirm"),
+                                _ => tr("Start step"),
+                            };
+                            if ui.button(text).clicked() {
+                                if step.kind == StepKind::Mark {
+                                    let marker = Marker {
+                                        time: now,
+                                        text: step.prompt.clone(),
+                                    };
+                                    if let Err(e) = markers.insert(directory, marker) {
+                                        error = Some(format!("{}: {e}", tr("Error saving marker")));
+                                    }
+                                    run.step += 1;
+                                } else {
+                                    run.step_start = Some(now);
+                                }
+                            }
+                        }
+                        Some(start) => {
+                            let elapsed = (now - start) as f32 / 1000.0;
+                            let progress = elapsed / step.duration.max(1) as f32;
+                            let remaining = (step.duration as f32 - elapsed).max(0.0);
+                            ui.add(ProgressBar::new(progress).text(format!("{remaining:.0} s")));
+                        }
+                    }
+                    ui.separator();
+                    if ui
+                        .button(RichText::new(tr("Abort test")).color(Color32::RED))
+                        .clicked()
+                    {
+                        self.run = None;
+                    }
+                }
+            });
+        self.show = show;
+
+        // a mark as the last step finishes the test without another sample
+        let finished = self
+            .run
+            .as_ref()
+            .is_some_and(|r| r.step >= config.steps.len());
+        if finished {
+            if let Err(e) = self.finish(ocv) {
+                error = Some(format!("{}: {e}", tr("Error saving test")));
+            }
+        }
+        error
+    }
+}
+
+fn summary_view(ui: &mut Ui, summary: &Summary) {
+    ui.separator();
+    ui.label(format!(
+        "{}: {:.2} Ah",
+        tr("Discharged charge"),
+        summary.charge
+    ));
+    ui.label(format!("{} {}", tr("Saved to"), summary.path.display()));
+    let value = |v: Option<f32>, decimals: usize| {
+        v.map_or_else(|| "-".to_string(), |v| format!("{v:.decimals$}"))
+    };
+    ScrollArea::vertical().show(ui, |ui| {
+        Grid::new("test_summary").striped(true).show(ui, |ui| {
+            ui.strong(tr("Cell"));
+            ui.strong(tr("Capacity (Ah)"));
+            ui.strong(tr("Resistance (mΩ)"));
+            ui.end_row();
+            for (i, (capacity, resistance)) in
+                summary.capacity.iter().zip(&summary.resistance).enumerate()
+            {
+                ui.label((i + 1).to_string());
+                ui.label(value(*capacity, 2));
+                ui.label(value(*resistance, 2));
+                ui.end_row();
+            }
+        });
+    });
+}
@@ -0,0 +1,202 @@
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use egui::{DragValue, Grid, Ui};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::api::Data;
+use crate::i18n::tr;
+
+/// Samples kept while the server is unreachable, older ones are dropped
+const MAX_QUEUED: usize = 10_000;
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct PushConfig {
+    pub enabled: bool,
+    /// Every batch is POSTed as a JSON array to this URL
+    pub url: String,
+    /// Number of samples sent in one request
+    pub batch_size: usize,
+    /// A batch is sent after this many ms even if it is not full
+    pub max_delay: u64,
+    /// Attempts per batch before it is put back into the queue
+    pub retries: u32,
+}
+
+impl Default for PushConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: String::new(),
+            batch_size: 10,
+            max_delay: 1000,
+            retries: 3,
+        }
+    }
+}
+
+impl PushConfig {
+    pub fn settings(&mut self, ui: &mut Ui, pusher: &Pusher) {
+        ui.checkbox(&mut self.enabled, tr("Push samples to a server"));
+        Grid::new("push_config").show(ui, |ui| {
+            ui.label("URL");
+            ui.text_edit_singleline(&mut self.url);
+            ui.end_row();
+
+            ui.label(tr("Batch size"));
+            ui.add(DragValue::new(&mut self.batch_size).clamp_range(1..=1000));
+            ui.end_row();
+
+            ui.label(tr("Maximum delay"));
+            ui.add(
+                DragValue::new(&mut self.max_delay)
+                    .clamp_range(100..=60000)
+                    .suffix(" ms"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Retries"));
+            ui.add(DragValue::new(&mut self.retries).clamp_range(0..=10));
+            ui.end_row();
+        });
+        if let Some(error) = pusher.error() {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+    }
+}
+
+/// Sends the samples from a background thread so a slow server never blocks the UI
+#[derive(Default)]
+pub struct Pusher {
+    /// The settings the thread was started with
+    sender: Option<(String, Sender<Value>)>,
+    error: Arc<Mutex<Option<String>>>,
+}
+
+impl Pusher {
+    pub fn push(&mut self, config: &PushConfig, data: &Data, time: u128) {
+        if !config.enabled || config.url.is_empty() {
+            self.sender = None;
+            return;
+        }
+
+        let key = format!(
+            "{} {} {} {}",
+            config.url, config.batch_size, config.max_delay, config.retries
+        );
+        if self.sender.as_ref().is_none_or(|(k, _)| *k != key) {
+            let (sender, receiver) = mpsc::channel();
+            let worker = Worker {
+                url: config.url.clone(),
+                batch_size: config.batch_size.max(1),
+                max_delay: Duration::from_millis(config.max_delay),
+                retries: config.retries,
+                error: self.error.clone(),
+            };
+            thread::spawn(move || worker.run(receiver));
+            self.sender = Some((key, sender));
+        }
+
+        let (_, sender) = self.sender.as_ref().unwrap();
+        if sender.send(sample(data, time)).is_err() {
+            self.sender = None;
+        }
+    }
+
+    /// The error of the last failed request, cleared when a request succeeds
+    pub fn error(&self) -> Option<String> {
+        self.error.lock().unwrap().clone()
+    }
+}
+
+struct Worker {
+    url: String,
+    batch_size: usize,
+    max_delay: Duration,
+    retries: u32,
+    error: Arc<Mutex<Option<String>>>,
+}
+
+impl Worker {
+    /// Runs until the sender is dropped
+    fn run(self, receiver: Receiver<Value>) {
+        let mut queue = Vec::new();
+        let mut batch_start = Instant::now();
+        loop {
+            let timeout = self.max_delay.saturating_sub(batch_start.elapsed());
+            match receiver.recv_timeout(timeout) {
+                Ok(sample) => {
+                    if queue.is_empty() {
+                        batch_start = Instant::now();
+                    }
+                    queue.push(sample);
+                    if queue.len() < self.batch_size {
+                        continue;
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+            if queue.is_empty() {
+                batch_start = Instant::now();
+                continue;
+            }
+
+            let count = queue.len().min(self.batch_size);
+            let body = Value::Array(queue[..count].to_vec()).to_string();
+            match self.send(&body) {
+                Ok(()) => {
+                    queue.drain(..count);
+                    *self.error.lock().unwrap() = None;
+                }
+                Err(e) => {
+                    // keep the samples for the next attempt
+                    *self.error.lock().unwrap() = Some(e);
+                    let excess = queue.len().saturating_sub(MAX_QUEUED);
+                    queue.drain(..excess);
+                }
+            }
+            batch_start = Instant::now();
+        }
+    }
+
+    fn send(&self, body: &str) -> Result<(), String> {
+        let mut error = String::new();
+        for attempt in 0..=self.retries {
+            if attempt > 0 {
+                thread::sleep(Duration::from_millis(200 << attempt.min(5)));
+            }
+            let result = ureq::post(&self.url)
+                .set("Content-Type", "application/json")
+                .timeout(Duration::from_secs(5))
+                .send_string(body);
+            match result {
+                Ok(_) => return Ok(()),
+                Err(e) => error = e.to_string(),
+            }
+        }
+        Err(error)
+    }
+}
+
+fn sample(data: &Data, time: u128) -> Value {
+    let (main, ucell, tcell) = (&data.main, &data.ucell, &data.tcell);
+    json!({
+        "time": time as u64,
+        "voltage": main.voltage,
+        "current": main.current,
+        "state_of_charge": main.state_of_charge,
+        "min_cell_voltage": ucell.overall.min_voltage,
+        "avg_cell_voltage": ucell.overall.avg_voltage,
+        "max_cell_voltage": ucell.overall.max_voltage,
+        "min_temp": tcell.overall.min_temp,
+        "avg_temp": tcell.overall.avg_temp,
+        "max_temp": tcell.overall.max_temp,
+        "cell_voltage": ucell.cell_voltage,
+        "temp": tcell.temp,
+    })
+}
@@ -0,0 +1,269 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+
+use egui::{DragValue, Grid, Ui};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+
+use crate::api::Data;
+use crate::gps::Fix;
+use crate::i18n::tr;
+use crate::motec_export;
+use crate::parquet_export;
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogConfig {
+    pub enabled: bool,
+    pub directory: String,
+    /// Start a new file after this many MB, 0 disables the limit
+    pub max_size: u64,
+    /// Start a new file after this many minutes, 0 disables the limit
+    pub max_duration: u64,
+    /// gzip completed files
+    pub compress: bool,
+    /// Convert completed files to Parquet
+    pub parquet: bool,
+    /// Export completed files in the CSV layout of MoTeC i2
+    pub motec: bool,
+    /// Offset in h of the ECU logs' time zone to UTC, for the MoTeC log date and time
+    pub utc_offset: f32,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            directory: "logs".into(),
+            max_size: 100,
+            max_duration: 60,
+            compress: true,
+            parquet: false,
+            motec: false,
+            utc_offset: 0.0,
+        }
+    }
+}
+
+impl LogConfig {
+    pub fn directory(&self) -> &Path {
+        Path::new(&self.directory)
+    }
+
+    pub fn settings(&mut self, ui: &mut Ui) {
+        ui.checkbox(&mut self.enabled, tr("Record data"));
+        ui.horizontal(|ui| {
+            ui.label(tr("Log directory"));
+            ui.text_edit_singleline(&mut self.directory);
+        });
+        Grid::new("log_rotation").show(ui, |ui| {
+            ui.label(tr("Maximum file size"));
+            ui.add(
+                DragValue::new(&mut self.max_size)
+                    .clamp_range(0..=10000)
+                    .suffix(" MB"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Maximum file duration"));
+            ui.add(
+                DragValue::new(&mut self.max_duration)
+                    .clamp_range(0..=1440)
+                    .suffix(" min"),
+            );
+            ui.end_row();
+        });
+        ui.checkbox(&mut self.compress, tr("Compress completed files"));
+        ui.checkbox(&mut self.parquet, tr("Convert completed files to Parquet"));
+        ui.checkbox(&mut self.motec, tr("Export completed files to MoTeC CSV"));
+        ui.horizontal(|ui| {
+            ui.label(tr("UTC offset of the ECU logs"));
+            ui.add(
+                DragValue::new(&mut self.utc_offset)
+                    .clamp_range(-12.0..=14.0)
+                    .speed(0.25)
+                    .suffix(" h"),
+            );
+        });
+    }
+}
+
+/// Writes every received sample as a line of a csv file
+pub struct Recorder {
+    pub path: PathBuf,
+    file: BufWriter<File>,
+    /// Unix time in ms of the first sample
+    start: u128,
+    /// Bytes written so far
+    size: u64,
+    /// Columns of the derived channels
+    pub derived: Vec<String>,
+}
+
+impl Recorder {
+    pub fn start(
+        directory: &Path,
+        data: &Data,
+        derived: Vec<String>,
+        time: u128,
+    ) -> io::Result<Self> {
+        fs::create_dir_all(directory)?;
+        let path = directory.join(format!("{time}.csv"));
+        let mut file = BufWriter::new(File::create(&path)?);
+
+        write!(
+            file,
+            "time,voltage,current,state_of_charge,speed,latitude,longitude,bms_uptime"
+        )?;
+        for i in 0..data.ucell.cell_voltage.len() {
+            write!(file, ",cell_{}", i + 1)?;
+        }
+        for i in 0..data.tcell.temp.len() {
+            write!(file, ",temp_{}", i + 1)?;
+        }
+        for column in &derived {
+            write!(file, ",{column}")?;
+        }
+        writeln!(file)?;
+
+        Ok(Self {
+            path,
+            file,
+            start: time,
+            size: 0,
+            derived,
+        })
+    }
+
+    /// Whether the file reached the size or duration limit and a new one should be started
+    pub fn is_full(&self, config: &LogConfig, time: u128) -> bool {
+        let too_large = config.max_size > 0 && self.size >= config.max_size * 1_000_000;
+        let too_long = config.max_duration > 0
+            && time.saturating_sub(self.start) >= config.max_duration as u128 * 60_000;
+        too_large || too_long
+    }
+
+    /// The GPS columns, the BMS uptime and invalid derived channels are left empty if unknown
+    pub fn write(
+        &mut self,
+        data: &Data,
+        fix: Option<Fix>,
+        derived: &[Option<f64>],
+        time: u128,
+    ) -> io::Result<()> {
+        let main = &data.main;
+        let mut line = format!(
+            "{time},{},{},{}",
+            main.voltage, main.current, main.state_of_charge
+        );
+        match fix {
+            Some(f) => line.push_str(&format!(",{},{},{}", f.speed, f.latitude, f.longitude)),
+            None => line.push_str(",,,"),
+        }
+        // aligns the sample with the BMS's own error log
+        match main.uptime {
+            Some(uptime) => line.push_str(&format!(",{uptime}")),
+            None => line.push(','),
+        }
+        for v in &data.ucell.cell_voltage {
+            line.push_str(&format!(",{v}"));
+        }
+        for t in &data.tcell.temp {
+            line.push_str(&format!(",{t}"));
+        }
+        for value in derived {
+            match value {
+                Some(v) => line.push_str(&format!(",{v}")),
+                None => line.push(','),
+            }
+        }
+        writeln!(self.file, "{line}")?;
+        self.size += line.len() as u64 + 1;
+        self.file.flush()
+    }
+}
+
+/// Converts, exports and compresses a completed recording in the background
+pub fn finish(path: PathBuf, config: &LogConfig) -> thread::JoinHandle<io::Result<()>> {
+    let (parquet, compress) = (config.parquet, config.compress);
+    let motec = config.motec.then_some(config.utc_offset);
+    thread::spawn(move || {
+        // every step runs even if another one failed, a broken conversion mustn't leave the raw
+        // recording uncompressed
+        let mut errors = Vec::new();
+        if parquet {
+            if let Err(e) = parquet_export::convert(&path) {
+                errors.push(format!("Parquet: {e}"));
+            }
+        }
+        if let Some(utc_offset) = motec {
+            if let Err(e) = motec_export::convert(&path, utc_offset) {
+                errors.push(format!("MoTeC: {e}"));
+            }
+        }
+        if compress {
+            if let Err(e) = self::compress(&path) {
+                errors.push(format!("{}: {e}", tr("Compression")));
+            }
+        }
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(io::Error::other(errors.join(", "))),
+        }
+    })
+}
+
+/// Replaces a recording with a gzipped copy
+fn compress(path: &Path) -> io::Result<PathBuf> {
+    let mut name = path.to_path_buf().into_os_string();
+    name.push(".gz");
+    let compressed = PathBuf::from(name);
+
+    let mut input = File::open(path)?;
+    let output = File::create(&compressed)?;
+    let mut encoder = GzEncoder::new(BufWriter::new(output), Compression::default());
+    io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?.flush()?;
+    fs::remove_file(path)?;
+    Ok(compressed)
+}
+
+/// Reads a recording, decompressing it if necessary
+pub fn read(path: &Path) -> io::Result<String> {
+    let mut text = String::new();
+    if path.extension().is_some_and(|e| e == "gz") {
+        GzDecoder::new(File::open(path)?).read_to_string(&mut text)?;
+    } else {
+        File::open(path)?.read_to_string(&mut text)?;
+    }
+    Ok(text)
+}
+
+/// The recordings in `directory` in chronological order
+pub fn recordings(directory: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return Vec::new();
+    };
+    let mut recordings: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| is_recording(p))
+        .collect();
+    recordings.sort();
+    recordings
+}
+
+/// Recordings are named after their start time, `<time>.csv` or `<time>.csv.gz`
+pub fn is_recording(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
+    let stem = name
+        .strip_suffix(".csv.gz")
+        .or_else(|| name.strip_suffix(".csv"));
+    stem.is_some_and(|s| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit()))
+}
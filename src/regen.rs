@@ -0,0 +1,121 @@
+use egui::{Color32, DragValue, Grid, RichText, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::api::Data;
+use crate::i18n::tr;
+
+/// Colors of the current and power for both directions, a negative current is regen
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct RegenConfig {
+    pub discharge_color: Color32,
+    pub regen_color: Color32,
+    /// Currents within this many A of zero are shown in the normal text color
+    pub deadband: f32,
+}
+
+impl Default for RegenConfig {
+    fn default() -> Self {
+        Self {
+            discharge_color: Color32::from_rgb(0xff, 0x98, 0x00),
+            regen_color: Color32::from_rgb(0x4c, 0xaf, 0x50),
+            deadband: 1.0,
+        }
+    }
+}
+
+impl RegenConfig {
+    /// Color of a current in mA, `None` within the deadband
+    pub fn color(&self, current: f32) -> Option<Color32> {
+        let current = current / 1000.0;
+        if current > self.deadband {
+            Some(self.discharge_color)
+        } else if current < -self.deadband {
+            Some(self.regen_color)
+        } else {
+            None
+        }
+    }
+
+    /// The text colored by the direction of the current in mA
+    pub fn text(&self, text: impl Into<String>, current: f32) -> RichText {
+        let text = RichText::new(text);
+        match self.color(current) {
+            Some(color) => text.color(color),
+            None => text,
+        }
+    }
+
+    pub fn settings(&mut self, ui: &mut Ui) {
+        Grid::new("regen_config").show(ui, |ui| {
+            ui.label(tr("Discharge color"));
+            ui.color_edit_button_srgba(&mut self.discharge_color);
+            ui.end_row();
+
+            ui.label(tr("Regen color"));
+            ui.color_edit_button_srgba(&mut self.regen_color);
+            ui.end_row();
+
+            ui.label(tr("Deadband"));
+            ui.add(
+                DragValue::new(&mut self.deadband)
+                    .clamp_range(0.0..=50.0)
+                    .speed(0.1)
+                    .suffix(" A"),
+            );
+            ui.end_row();
+        });
+    }
+}
+
+/// Highest current in A and power in kW of the session in both directions, as positive values
+#[derive(Default)]
+pub struct CurrentPeaks {
+    pub discharge_current: f32,
+    pub regen_current: f32,
+    pub discharge_power: f32,
+    pub regen_power: f32,
+}
+
+impl CurrentPeaks {
+    pub fn update(&mut self, data: &Data) {
+        let current = data.main.current / 1000.0;
+        let power = power(data);
+        self.discharge_current = self.discharge_current.max(current);
+        self.regen_current = self.regen_current.max(-current);
+        self.discharge_power = self.discharge_power.max(power);
+        self.regen_power = self.regen_power.max(-power);
+    }
+
+    /// Rows for the side panel grid, with the value and the unit in separate columns
+    pub fn show(&self, ui: &mut Ui, config: &RegenConfig) {
+        let row = |ui: &mut Ui, name: &str, value: f32, unit: &str, color: Color32| {
+            ui.label(name);
+            ui.label(RichText::new(format!("{value:.1}")).color(color));
+            ui.label(unit);
+            ui.end_row();
+        };
+        let (discharge, regen) = (config.discharge_color, config.regen_color);
+        row(
+            ui,
+            tr("Peak discharge current"),
+            self.discharge_current,
+            "A",
+            discharge,
+        );
+        row(ui, tr("Peak regen current"), self.regen_current, "A", regen);
+        row(
+            ui,
+            tr("Peak discharge power"),
+            self.discharge_power,
+            "kW",
+            discharge,
+        );
+        row(ui, tr("Peak regen power"), self.regen_power, "kW", regen);
+    }
+}
+
+/// Power of the pack in kW, negative while charging or recuperating
+pub fn power(data: &Data) -> f32 {
+    data.main.voltage * data.main.current / 1_000_000.0
+}
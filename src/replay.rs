@@ -0,0 +1,451 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use egui::{
+    Align2, Color32, ComboBox, FontId, Pos2, Rect, ScrollArea, Sense, Shape, Stroke, Ui, Vec2,
+    Window,
+};
+
+use crate::api::{Data, Main, Tcell, Ucell};
+use crate::i18n::tr;
+use crate::markers::Marker;
+use crate::motec_export;
+use crate::recording;
+
+const SPEEDS: [f32; 6] = [0.5, 1.0, 2.0, 5.0, 10.0, 30.0];
+
+/// One line of a recording
+struct Row {
+    /// Unix time in ms
+    time: u128,
+    main: Main,
+    cell_voltage: Vec<u16>,
+    temp: Vec<f32>,
+    // in mV, kept for drawing the timeline
+    min_voltage: u16,
+    delta_voltage: u16,
+}
+
+/// Plays back a recording written by the `Recorder`
+pub struct Replay {
+    pub path: PathBuf,
+    rows: Vec<Row>,
+    markers: Vec<Marker>,
+    pub position: usize,
+    pub playing: bool,
+    pub speed: f32,
+    /// Recording time in ms of the playhead
+    playhead: f64,
+    /// Wall clock time in ms of the last tick
+    last_tick: Option<u128>,
+    /// Set when the position was changed by the user instead of by playing
+    seeked: bool,
+    /// Times of the bookmarks, stored next to the recording
+    bookmarks: Vec<u128>,
+    /// Start and end time of the section that is played in a loop
+    loop_a: Option<u128>,
+    loop_b: Option<u128>,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let text = recording::read(path)?;
+        let mut lines = text.lines();
+        let header: Vec<&str> = lines.next().unwrap_or_default().split(',').collect();
+        let column = |name: &str| header.iter().position(|h| *h == name);
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, tr("Not a recording"));
+
+        let time_column = column("time").ok_or_else(invalid)?;
+        let voltage_column = column("voltage").ok_or_else(invalid)?;
+        let current_column = column("current").ok_or_else(invalid)?;
+        let soc_column = column("state_of_charge").ok_or_else(invalid)?;
+        // missing in older recordings
+        let uptime_column = column("bms_uptime");
+        let cell_columns: Vec<usize> = (0..header.len())
+            .filter(|i| header[*i].starts_with("cell_"))
+            .collect();
+        let temp_columns: Vec<usize> = (0..header.len())
+            .filter(|i| header[*i].starts_with("temp_"))
+            .collect();
+        if cell_columns.is_empty() {
+            return Err(invalid());
+        }
+
+        let rows: Vec<Row> = lines
+            .filter_map(|line| {
+                let values: Vec<&str> = line.split(',').collect();
+                let cell_voltage: Vec<u16> = cell_columns
+                    .iter()
+                    .map(|i| value(&values, *i))
+                    .collect::<Option<_>>()?;
+                let temp: Vec<f32> = temp_columns
+                    .iter()
+                    .map(|i| value(&values, *i))
+                    .collect::<Option<_>>()?;
+                let min_voltage = *cell_voltage.iter().min()?;
+                let max_voltage = *cell_voltage.iter().max()?;
+                Some(Row {
+                    time: value(&values, time_column)?,
+                    main: Main {
+                        voltage: value(&values, voltage_column)?,
+                        current: value(&values, current_column)?,
+                        state_of_charge: value(&values, soc_column)?,
+                        uptime: uptime_column.and_then(|i| value(&values, i)),
+                        ..Default::default()
+                    },
+                    cell_voltage,
+                    temp,
+                    min_voltage,
+                    delta_voltage: max_voltage - min_voltage,
+                })
+            })
+            .collect();
+        if rows.is_empty() {
+            return Err(invalid());
+        }
+
+        let markers = path.parent().map(Marker::load).unwrap_or_default();
+        let bookmarks = fs::read_to_string(bookmarks_path(path))
+            .map(|text| text.lines().filter_map(|l| l.parse().ok()).collect())
+            .unwrap_or_default();
+        Ok(Self {
+            path: path.to_path_buf(),
+            playhead: rows[0].time as f64,
+            rows,
+            markers,
+            position: 0,
+            playing: true,
+            speed: 1.0,
+            last_tick: None,
+            seeked: true,
+            bookmarks,
+            loop_a: None,
+            loop_b: None,
+        })
+    }
+
+    pub fn time(&self, index: usize) -> u128 {
+        self.rows[index].time
+    }
+
+    /// Reconstructs the data of a row like it would have been received live
+    pub fn data(&self, index: usize, safe: bool, dead_sensors: &[usize]) -> Data {
+        let row = &self.rows[index];
+        let ucell = Ucell::new(row.cell_voltage.clone(), safe);
+        let tcell = Tcell::new(row.temp.clone(), safe, dead_sensors);
+        let mut main = row.main;
+        main.temp_avg = tcell.overall.avg_temp;
+        main.temp_min = tcell.overall.min_temp;
+        main.temp_max = tcell.overall.max_temp;
+        Data {
+            main,
+            ucell,
+            tcell,
+            status: None,
+        }
+    }
+
+    pub fn seek(&mut self, index: usize) {
+        self.position = index.min(self.rows.len() - 1);
+        self.playhead = self.rows[self.position].time as f64;
+        self.seeked = true;
+    }
+
+    fn seek_time(&mut self, time: u128) {
+        let index = self.rows.partition_point(|r| r.time < time);
+        self.seek(index);
+    }
+
+    fn save_bookmarks(&self) -> io::Result<()> {
+        let text: String = self.bookmarks.iter().map(|b| format!("{b}\n")).collect();
+        fs::write(bookmarks_path(&self.path), text)
+    }
+
+    /// Returns whether the position was changed by the user since the last call
+    pub fn take_seeked(&mut self) -> bool {
+        std::mem::take(&mut self.seeked)
+    }
+
+    /// Advances the playhead by the elapsed wall clock time, returns whether the position changed
+    pub fn tick(&mut self, now: u128) -> bool {
+        let elapsed = self.last_tick.map_or(0, |t| now.saturating_sub(t));
+        self.last_tick = Some(now);
+        if !self.playing {
+            return false;
+        }
+
+        self.playhead += elapsed as f64 * self.speed as f64;
+        if let (Some(a), Some(b)) = (self.loop_a, self.loop_b) {
+            if self.playhead >= b as f64 {
+                self.seek_time(a);
+                return true;
+            }
+        }
+        let previous = self.position;
+        while self.position + 1 < self.rows.len()
+            && self.rows[self.position + 1].time as f64 <= self.playhead
+        {
+            self.position += 1;
+        }
+        if self.position + 1 == self.rows.len() {
+            self.playing = false;
+        }
+        self.position != previous
+    }
+
+    /// Playback controls and a timeline with the cell delta, markers and alarms, returns whether
+    /// the replay should be closed and a status message
+    pub fn timeline(
+        &mut self,
+        ui: &mut Ui,
+        alarm_voltage: u16,
+        utc_offset: f32,
+    ) -> (bool, Option<String>) {
+        let mut close = false;
+        let mut status = None;
+        let start = self.rows[0].time;
+        let end = self.rows[self.rows.len() - 1].time;
+        let format = |t: u128| {
+            let s = (t - start) / 1000;
+            format!("{}:{:02}", s / 60, s % 60)
+        };
+
+        ui.horizontal(|ui| {
+            let play = if self.playing { "⏸" } else { "▶" };
+            if ui.button(play).clicked() {
+                if !self.playing && self.position + 1 == self.rows.len() {
+                    self.seek(0);
+                }
+                self.playing = !self.playing;
+            }
+            ComboBox::from_id_source("replay_speed")
+                .width(60.0)
+                .selected_text(format!("{}×", self.speed))
+                .show_ui(ui, |ui| {
+                    for speed in SPEEDS {
+                        ui.selectable_value(&mut self.speed, speed, format!("{speed}×"));
+                    }
+                });
+            ui.label(format!(
+                "{} / {}",
+                format(self.rows[self.position].time),
+                format(end)
+            ));
+            ui.separator();
+            let current = self.rows[self.position].time;
+            if ui.button(tr("Bookmark")).clicked() {
+                self.bookmarks.push(current);
+                self.bookmarks.sort();
+                if let Err(e) = self.save_bookmarks() {
+                    status = Some(format!("{}: {e}", tr("Error saving bookmarks")));
+                }
+            }
+            let mut jump = None;
+            ComboBox::from_id_source("replay_bookmarks")
+                .selected_text(tr("Bookmarks"))
+                .show_ui(ui, |ui| {
+                    for bookmark in &self.bookmarks {
+                        if ui.selectable_label(false, format(*bookmark)).clicked() {
+                            jump = Some(*bookmark);
+                        }
+                    }
+                });
+            if let Some(bookmark) = jump {
+                self.seek_time(bookmark);
+            }
+
+            ui.separator();
+            if ui.selectable_label(self.loop_a.is_some(), "A").clicked() {
+                self.loop_a = Some(current);
+                self.loop_b = self.loop_b.filter(|b| *b > current);
+            }
+            if ui.selectable_label(self.loop_b.is_some(), "B").clicked() {
+                self.loop_b = Some(current);
+                self.loop_a = self.loop_a.filter(|a| *a < current);
+            }
+            if let (Some(a), Some(b)) = (self.loop_a, self.loop_b) {
+                ui.label(format!("{} – {}", format(a), format(b)));
+            }
+            if (self.loop_a.is_some() || self.loop_b.is_some())
+                && ui.button(tr("Clear loop")).clicked()
+            {
+                self.loop_a = None;
+                self.loop_b = None;
+            }
+
+            ui.separator();
+            ui.label(self.path.display().to_string());
+            if ui.button(tr("Export MoTeC CSV")).clicked() {
+                status = Some(match motec_export::convert(&self.path, utc_offset) {
+                    Ok(path) => format!("{} {}", tr("Exported to"), path.display()),
+                    Err(e) => format!("{}: {e}", tr("Error exporting recording")),
+                });
+            }
+            if ui.button(tr("Stop replay")).clicked() {
+                close = true;
+            }
+        });
+
+        let (rect, response) = ui.allocate_exact_size(
+            Vec2::new(ui.available_width(), 40.0),
+            Sense::click_and_drag(),
+        );
+        let duration = (end - start).max(1) as f32;
+        let x = |t: u128| rect.left() + (t - start) as f32 / duration * rect.width();
+
+        if let Some(pos) = response.interact_pointer_pos() {
+            let t = start + ((pos.x - rect.left()) / rect.width() * duration).max(0.0) as u128;
+            let index = self.rows.partition_point(|r| r.time < t);
+            self.seek(index);
+        }
+
+        let painter = ui.painter_at(rect);
+        let visuals = ui.visuals();
+        painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+        if let (Some(a), Some(b)) = (self.loop_a, self.loop_b) {
+            let section = Rect::from_x_y_ranges(x(a)..=x(b), rect.y_range());
+            painter.rect_filled(section, 0.0, visuals.selection.bg_fill.gamma_multiply(0.4));
+        }
+        for bookmark in &self.bookmarks {
+            let x = x(*bookmark);
+            painter.line_segment(
+                [Pos2::new(x, rect.top()), Pos2::new(x, rect.top() + 8.0)],
+                Stroke::new(2.0, visuals.selection.stroke.color),
+            );
+        }
+
+        // sparkline of the cell delta, alarms as red ticks below it
+        let max_delta = self
+            .rows
+            .iter()
+            .map(|r| r.delta_voltage)
+            .max()
+            .unwrap_or(1)
+            .max(1);
+        let step = (self.rows.len() / rect.width().max(1.0) as usize).max(1);
+        let points: Vec<Pos2> = self
+            .rows
+            .iter()
+            .step_by(step)
+            .map(|r| {
+                let y = rect.bottom() - r.delta_voltage as f32 / max_delta as f32 * rect.height();
+                Pos2::new(x(r.time), y)
+            })
+            .collect();
+        painter.add(Shape::line(
+            points,
+            Stroke::new(1.0, visuals.weak_text_color()),
+        ));
+        for row in self.rows.iter().step_by(step) {
+            if row.min_voltage < alarm_voltage {
+                let x = x(row.time);
+                painter.line_segment(
+                    [
+                        Pos2::new(x, rect.bottom() - 6.0),
+                        Pos2::new(x, rect.bottom()),
+                    ],
+                    Stroke::new(1.0, Color32::RED),
+                );
+            }
+        }
+
+        let marker_color = Color32::from_rgb(0xe0, 0xa0, 0x00);
+        for marker in &self.markers {
+            if marker.time < start || marker.time > end {
+                continue;
+            }
+            let x = x(marker.time);
+            painter.line_segment(
+                [Pos2::new(x, rect.top()), Pos2::new(x, rect.bottom())],
+                Stroke::new(1.5, marker_color),
+            );
+            let marker_rect = Rect::from_center_size(
+                Pos2::new(x, rect.center().y),
+                Vec2::new(8.0, rect.height()),
+            );
+            if !marker.text.is_empty() && ui.rect_contains_pointer(marker_rect) {
+                painter.text(
+                    Pos2::new(x + 4.0, rect.top()),
+                    Align2::LEFT_TOP,
+                    &marker.text,
+                    FontId::proportional(12.0),
+                    marker_color,
+                );
+            }
+        }
+
+        let playhead = x(self.rows[self.position].time);
+        painter.line_segment(
+            [
+                Pos2::new(playhead, rect.top()),
+                Pos2::new(playhead, rect.bottom()),
+            ],
+            Stroke::new(2.0, visuals.strong_text_color()),
+        );
+
+        (close, status)
+    }
+}
+
+/// Lists the recordings in the log directory and its sessions
+#[derive(Default)]
+pub struct ReplayPicker {
+    pub show: bool,
+    files: Vec<PathBuf>,
+}
+
+impl ReplayPicker {
+    pub fn open(&mut self, log_directory: &Path) {
+        let mut files = recording::recordings(log_directory);
+        if let Ok(entries) = fs::read_dir(log_directory) {
+            for entry in entries.flatten() {
+                if entry.path().is_dir() {
+                    files.extend(recording::recordings(&entry.path()));
+                }
+            }
+        }
+        files.sort();
+        files.reverse();
+        self.files = files;
+        self.show = true;
+    }
+
+    /// Returns the picked recording
+    pub fn window(&mut self, ctx: &egui::Context) -> Option<PathBuf> {
+        let mut picked = None;
+        Window::new(tr("Replay"))
+            .id("replay".into())
+            .open(&mut self.show)
+            .show(ctx, |ui| {
+                if self.files.is_empty() {
+                    ui.label(tr("No recordings found"));
+                }
+                ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    for file in &self.files {
+                        if ui.button(file.display().to_string()).clicked() {
+                            picked = Some(file.clone());
+                        }
+                    }
+                });
+            });
+        if picked.is_some() {
+            self.show = false;
+        }
+        picked
+    }
+}
+
+fn value<T: FromStr>(values: &[&str], index: usize) -> Option<T> {
+    values.get(index)?.parse().ok()
+}
+
+/// Shared by the plain and the compressed file of a recording
+fn bookmarks_path(path: &Path) -> PathBuf {
+    let path = match path.extension().is_some_and(|e| e == "gz") {
+        true => path.with_extension(""),
+        false => path.to_path_buf(),
+    };
+    path.with_extension("bookmarks")
+}
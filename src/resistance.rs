@@ -0,0 +1,83 @@
+use egui::{DragValue, Grid, Ui};
+use egui_plot::{Legend, Line, Plot, PlotPoints, Points};
+
+use crate::history::{slope, History};
+use crate::i18n::tr;
+
+/// Scatter plots of the voltage over the current, the negative slope of the fitted line is the
+/// effective internal resistance
+pub fn resistance_view(ui: &mut Ui, history: &History, window: &mut u64) {
+    let samples: Vec<_> = history.window(*window as u128 * 1000).collect();
+    let pack: Vec<[f64; 2]> = samples
+        .iter()
+        .map(|s| [s.current as f64 / 1000.0, s.voltage as f64])
+        .collect();
+    let min_cell: Vec<[f64; 2]> = samples
+        .iter()
+        .map(|s| [s.current as f64 / 1000.0, s.min_voltage as f64])
+        .collect();
+
+    // in V/A and mV/A
+    let pack_slope = slope(pack.iter().map(|p| (p[0], p[1])));
+    let cell_slope = slope(min_cell.iter().map(|p| (p[0], p[1])));
+
+    Grid::new("resistance_stats").show(ui, |ui| {
+        ui.label(tr("Window"));
+        ui.add(DragValue::new(window).clamp_range(10..=3600).suffix(" s"));
+        ui.end_row();
+
+        ui.label(tr("Pack resistance"));
+        ui.strong(match pack_slope {
+            Some(s) => format!("{:.1} mΩ", -s * 1000.0),
+            None => "-".into(),
+        });
+        ui.end_row();
+
+        ui.label(tr("Weakest cell resistance"));
+        ui.strong(match cell_slope {
+            Some(s) => format!("{:.2} mΩ", -s),
+            None => "-".into(),
+        });
+        ui.end_row();
+    });
+
+    ui.columns(2, |columns| {
+        scatter(
+            &mut columns[0],
+            "pack_scatter",
+            tr("Pack voltage (V)"),
+            &pack,
+            pack_slope,
+        );
+        scatter(
+            &mut columns[1],
+            "cell_scatter",
+            tr("Min cell voltage (mV)"),
+            &min_cell,
+            cell_slope,
+        );
+    });
+}
+
+fn scatter(ui: &mut Ui, id: &str, name: &str, points: &[[f64; 2]], slope: Option<f64>) {
+    Plot::new(id)
+        .legend(Legend::default())
+        .x_axis_label("A")
+        .show(ui, |plot| {
+            plot.points(Points::new(points.to_vec()).radius(2.0).name(name));
+
+            // least squares line through the mean
+            let Some(slope) = slope else {
+                return;
+            };
+            let n = points.len() as f64;
+            let mean_x = points.iter().map(|p| p[0]).sum::<f64>() / n;
+            let mean_y = points.iter().map(|p| p[1]).sum::<f64>() / n;
+            let (min_x, max_x) = points.iter().fold((f64::MAX, f64::MIN), |(min, max), p| {
+                (min.min(p[0]), max.max(p[0]))
+            });
+            let line = |x: f64| [x, mean_y + slope * (x - mean_x)];
+            let fit: PlotPoints = vec![line(min_x), line(max_x)].into();
+            plot.line(Line::new(fit).name(tr("Fit")));
+        });
+}
@@ -0,0 +1,251 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::{Data, Tcell, TempStats, Ucell, VoltageStats};
+use crate::history::{History, Sample};
+use crate::i18n::tr;
+
+/// Samples the statistics of the side panel are aggregated over
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum StatsWindow {
+    /// Only the latest sample
+    #[default]
+    Sample,
+    HalfMinute,
+    FiveMinutes,
+    Session,
+}
+
+impl StatsWindow {
+    pub const ALL: [StatsWindow; 4] = [
+        StatsWindow::Sample,
+        StatsWindow::HalfMinute,
+        StatsWindow::FiveMinutes,
+        StatsWindow::Session,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            StatsWindow::Sample => tr("Latest sample"),
+            StatsWindow::HalfMinute => tr("Last 30 s"),
+            StatsWindow::FiveMinutes => tr("Last 5 min"),
+            StatsWindow::Session => tr("Whole session"),
+        }
+    }
+
+    /// Length of the window in ms, `None` for the latest sample only
+    fn duration(self) -> Option<u128> {
+        match self {
+            StatsWindow::Sample => None,
+            StatsWindow::HalfMinute => Some(30_000),
+            StatsWindow::FiveMinutes => Some(300_000),
+            StatsWindow::Session => Some(u128::MAX),
+        }
+    }
+}
+
+/// Cell statistics aggregated over a window of the history, updated with every new sample
+#[derive(Default)]
+pub struct RollingStats {
+    window: Option<StatsWindow>,
+    /// Time of the newest sample included in the aggregates
+    newest: Option<u128>,
+    /// Overall, left and right side
+    voltage: [Aggregate; 3],
+    temp: [Aggregate; 3],
+    data: Option<Data>,
+}
+
+impl RollingStats {
+    pub fn update(
+        &mut self,
+        window: StatsWindow,
+        history: &History,
+        data: &Data,
+        dead_sensors: &[usize],
+    ) {
+        let (Some(duration), Some(last)) = (window.duration(), history.last()) else {
+            *self = Self::default();
+            return;
+        };
+        let last = last.time;
+        // the history starts over with a new connection
+        if self.window != Some(window) || self.newest.is_some_and(|n| n > last) {
+            *self = Self {
+                window: Some(window),
+                ..Default::default()
+            };
+        }
+        if self.newest == Some(last) {
+            return;
+        }
+
+        let newest = self.newest;
+        let new: Vec<_> = history
+            .window(duration)
+            .rev()
+            .take_while(|s| newest.is_none_or(|n| s.time > n))
+            .collect();
+        for sample in new.into_iter().rev() {
+            self.push(sample, dead_sensors);
+        }
+        self.newest = Some(last);
+
+        if let Some(start) = history.window(duration).next().map(|s| s.time) {
+            for aggregate in self.voltage.iter_mut().chain(&mut self.temp) {
+                aggregate.evict(start);
+            }
+        }
+
+        let mut aggregated = data.clone();
+        let ucell = &mut aggregated.ucell;
+        for (stats, aggregate) in [&mut ucell.overall, &mut ucell.left, &mut ucell.right]
+            .into_iter()
+            .zip(&self.voltage)
+        {
+            if let Some([avg, min, max, delta]) = aggregate.get() {
+                *stats = VoltageStats {
+                    avg_voltage: avg as u16,
+                    min_voltage: min as u16,
+                    max_voltage: max as u16,
+                    delta_voltage: delta as u16,
+                };
+            }
+        }
+        let tcell = &mut aggregated.tcell;
+        for (stats, aggregate) in [&mut tcell.overall, &mut tcell.left, &mut tcell.right]
+            .into_iter()
+            .zip(&self.temp)
+        {
+            if let Some([avg_temp, min_temp, max_temp, delta_temp]) = aggregate.get() {
+                *stats = TempStats {
+                    avg_temp,
+                    min_temp,
+                    max_temp,
+                    delta_temp,
+                };
+            }
+        }
+        self.data = Some(aggregated);
+    }
+
+    /// The latest sample with the cell statistics replaced by the lowest minimum, the average, the
+    /// highest maximum and the largest delta within the window, `None` for the latest sample only
+    pub fn data(&self) -> Option<&Data> {
+        self.data.as_ref()
+    }
+
+    /// Adds the statistics of the sample, sides without cells or live sensors are skipped so they
+    /// don't pull the minimum down to 0
+    fn push(&mut self, sample: &Sample, dead_sensors: &[usize]) {
+        let time = sample.time;
+        // safe mode was already applied when the sample was received
+        let cells = sample.cell_voltage.len();
+        if cells > 0 {
+            let ucell = Ucell::new(sample.cell_voltage.clone(), false);
+            let sides = [
+                (&ucell.overall, true),
+                (&ucell.left, cells > 72),
+                (&ucell.right, true),
+            ];
+            for ((stats, present), aggregate) in sides.into_iter().zip(&mut self.voltage) {
+                if present {
+                    aggregate.push(
+                        time,
+                        [
+                            stats.avg_voltage as f32,
+                            stats.min_voltage as f32,
+                            stats.max_voltage as f32,
+                            stats.delta_voltage as f32,
+                        ],
+                    );
+                }
+            }
+        }
+
+        let sensors = sample.temp.len();
+        let alive =
+            |range: std::ops::Range<usize>| range.into_iter().any(|i| !dead_sensors.contains(&i));
+        let tcell = Tcell::new(sample.temp.clone(), false, dead_sensors);
+        let sides = [
+            (&tcell.overall, alive(0..sensors)),
+            (&tcell.left, alive(8..sensors)),
+            (&tcell.right, alive(0..sensors.min(8))),
+        ];
+        for ((stats, present), aggregate) in sides.into_iter().zip(&mut self.temp) {
+            if present {
+                aggregate.push(
+                    time,
+                    [
+                        stats.avg_temp,
+                        stats.min_temp,
+                        stats.max_temp,
+                        stats.delta_temp,
+                    ],
+                );
+            }
+        }
+    }
+}
+
+/// Running average, minimum, maximum and largest delta of one side over the window. The extremes
+/// are kept in monotonic queues, so adding and dropping a sample takes amortized constant time.
+#[derive(Default)]
+struct Aggregate {
+    /// Time and average of every sample in the window
+    averages: VecDeque<(u128, f32)>,
+    sum: f64,
+    /// Candidates for the lowest minimum, negated
+    min: VecDeque<(u128, f32)>,
+    max: VecDeque<(u128, f32)>,
+    delta: VecDeque<(u128, f32)>,
+}
+
+impl Aggregate {
+    fn push(&mut self, time: u128, [avg, min, max, delta]: [f32; 4]) {
+        self.averages.push_back((time, avg));
+        self.sum += avg as f64;
+        push_max(&mut self.min, time, -min);
+        push_max(&mut self.max, time, max);
+        push_max(&mut self.delta, time, delta);
+    }
+
+    /// Drops the samples older than `start`
+    fn evict(&mut self, start: u128) {
+        while let Some(&(time, avg)) = self.averages.front() {
+            if time >= start {
+                break;
+            }
+            self.averages.pop_front();
+            self.sum -= avg as f64;
+        }
+        for queue in [&mut self.min, &mut self.max, &mut self.delta] {
+            while queue.front().is_some_and(|(time, _)| *time < start) {
+                queue.pop_front();
+            }
+        }
+    }
+
+    /// Average, minimum, maximum and delta, `None` if no sample in the window had this side
+    fn get(&self) -> Option<[f32; 4]> {
+        let len = self.averages.len();
+        if len == 0 {
+            return None;
+        }
+        Some([
+            (self.sum / len as f64) as f32,
+            -self.min.front()?.1,
+            self.max.front()?.1,
+            self.delta.front()?.1,
+        ])
+    }
+}
+
+/// Appends the value, dropping the older ones that can no longer be the maximum of the window
+fn push_max(queue: &mut VecDeque<(u128, f32)>, time: u128, value: f32) {
+    while queue.back().is_some_and(|(_, v)| *v <= value) {
+        queue.pop_back();
+    }
+    queue.push_back((time, value));
+}
@@ -0,0 +1,129 @@
+use egui::{DragValue, Grid, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::api::{Data, Ucell};
+use crate::history::{slope, History};
+use crate::i18n::tr;
+
+/// The resistances are fitted again after this many ms
+const FIT_INTERVAL: u128 = 5000;
+/// Spread of the current in A needed within the window for a meaningful fit
+const MIN_CURRENT_SPREAD: f64 = 20.0;
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct SagConfig {
+    /// Window in s the resistance of every cell is fitted over
+    pub window: u64,
+    /// Resistance in mΩ of the cells that couldn't be fitted yet
+    pub default_resistance: f32,
+}
+
+impl Default for SagConfig {
+    fn default() -> Self {
+        Self {
+            window: 300,
+            default_resistance: 1.0,
+        }
+    }
+}
+
+impl SagConfig {
+    pub fn settings(&mut self, ui: &mut Ui) {
+        Grid::new("sag_config").show(ui, |ui| {
+            ui.label(tr("Resistance window"));
+            ui.add(
+                DragValue::new(&mut self.window)
+                    .clamp_range(30..=3600)
+                    .suffix(" s"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Default cell resistance"));
+            ui.add(
+                DragValue::new(&mut self.default_resistance)
+                    .clamp_range(0.0..=20.0)
+                    .speed(0.05)
+                    .suffix(" mΩ"),
+            );
+            ui.end_row();
+        });
+    }
+}
+
+/// Internal resistance of every cell, fitted like the weakest cell on the resistance view
+#[derive(Default)]
+pub struct SagEstimator {
+    /// in mΩ, `None` until the current varied enough during the window
+    pub resistance: Vec<Option<f32>>,
+    last_fit: u128,
+}
+
+impl SagEstimator {
+    pub fn update(&mut self, config: &SagConfig, history: &History, time: u128) {
+        // also refits right away after seeking back in a replay
+        if time.abs_diff(self.last_fit) < FIT_INTERVAL {
+            return;
+        }
+        self.last_fit = time;
+
+        let samples: Vec<_> = history.window(config.window as u128 * 1000).collect();
+        let Some(last) = samples.last() else {
+            return;
+        };
+        let cells = last.cell_voltage.len();
+        self.resistance.resize(cells, None);
+        let (min, max) = samples.iter().fold((f64::MAX, f64::MIN), |(min, max), s| {
+            let current = s.current as f64 / 1000.0;
+            (min.min(current), max.max(current))
+        });
+        // keep the last fit while the car is standing or cruising
+        if max - min < MIN_CURRENT_SPREAD {
+            return;
+        }
+
+        for (i, resistance) in self.resistance.iter_mut().enumerate() {
+            // in mV/A, the voltage drops with the discharge current
+            let fit = slope(samples.iter().filter_map(|s| {
+                let voltage = *s.cell_voltage.get(i)?;
+                Some((s.current as f64 / 1000.0, voltage as f64))
+            }));
+            *resistance = fit.map(|s| (-s).max(0.0) as f32);
+        }
+    }
+
+    /// The sample with every cell voltage raised by its drop over the internal resistance, an
+    /// estimate of the open circuit voltage that doesn't depend on the load
+    pub fn compensate(&self, config: &SagConfig, data: &Data) -> Data {
+        let current = data.main.current / 1000.0;
+        let voltage = data
+            .ucell
+            .cell_voltage
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                let resistance = self
+                    .resistance
+                    .get(i)
+                    .copied()
+                    .flatten()
+                    .unwrap_or(config.default_resistance);
+                (*v as f32 + current * resistance)
+                    .round()
+                    .clamp(0.0, u16::MAX as f32) as u16
+            })
+            .collect();
+        let mut compensated = data.clone();
+        if !data.ucell.cell_voltage.is_empty() {
+            compensated.ucell = Ucell {
+                num_slaves: data.ucell.num_slaves,
+                num_cells: data.ucell.num_cells,
+                num_cells_per_slave: data.ucell.num_cells_per_slave,
+                num_temp_sensors: data.ucell.num_temp_sensors,
+                num_safe_resistors: data.ucell.num_safe_resistors,
+                ..Ucell::new(voltage, false)
+            };
+        }
+        compensated
+    }
+}
@@ -0,0 +1,267 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use egui::{Grid, Ui, Window};
+use rhai::{Array, Dynamic, Engine, Map, Scope, AST};
+use serde::{Deserialize, Serialize};
+
+use crate::api::{Data, TempStats, VoltageStats};
+use crate::i18n::tr;
+
+/// Upper bound of operations per sample, so an endless loop can't freeze the dashboard
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScriptConfig {
+    pub enabled: bool,
+    /// Rhai script run on every sample, reloaded when the file changes
+    pub path: String,
+}
+
+impl ScriptConfig {
+    pub fn settings(&mut self, ui: &mut Ui, script: &Script) {
+        ui.checkbox(&mut self.enabled, tr("Run script on every sample"));
+        ui.horizontal(|ui| {
+            ui.label(tr("Script file"));
+            ui.text_edit_singleline(&mut self.path);
+        });
+        if let Some(error) = &script.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+        ui.collapsing(tr("Script API"), |ui| {
+            ui.label(tr(
+                "The script runs on every sample with `data` (e.g. data.voltage, data.cells, \
+                 data.left.avg) and `state`, a map kept between samples. It can call \
+                 alarm(text), alarm(text, value), value(name, value, unit), label(text) and print.",
+            ));
+        });
+    }
+}
+
+/// What the script produced for the last sample
+#[derive(Default)]
+pub struct Output {
+    /// Text and value of every raised alarm
+    pub alarms: Vec<(String, f32)>,
+    /// Name, value and unit shown in the side panel
+    pub values: Vec<(String, f64, String)>,
+    /// Lines of the script panel
+    pub labels: Vec<String>,
+}
+
+pub struct Script {
+    engine: Engine,
+    ast: Option<AST>,
+    /// Path and modification time of the compiled file
+    loaded: Option<(PathBuf, Option<SystemTime>)>,
+    /// Kept between samples so scripts can count, filter or latch
+    state: Dynamic,
+    shared: Arc<Mutex<Output>>,
+    pub output: Output,
+    pub error: Option<String>,
+    pub show: bool,
+}
+
+impl Default for Script {
+    fn default() -> Self {
+        let shared = Arc::new(Mutex::new(Output::default()));
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+
+        let out = shared.clone();
+        engine.register_fn("alarm", move |text: &str| {
+            out.lock()
+                .unwrap()
+                .alarms
+                .push((text.to_string(), f32::NAN));
+        });
+        let out = shared.clone();
+        engine.register_fn("alarm", move |text: &str, value: f64| {
+            out.lock()
+                .unwrap()
+                .alarms
+                .push((text.to_string(), value as f32));
+        });
+        let out = shared.clone();
+        engine.register_fn("alarm", move |text: &str, value: i64| {
+            out.lock()
+                .unwrap()
+                .alarms
+                .push((text.to_string(), value as f32));
+        });
+        let out = shared.clone();
+        engine.register_fn("value", move |name: &str, value: f64, unit: &str| {
+            let entry = (name.to_string(), value, unit.to_string());
+            out.lock().unwrap().values.push(entry);
+        });
+        let out = shared.clone();
+        engine.register_fn("value", move |name: &str, value: i64, unit: &str| {
+            let entry = (name.to_string(), value as f64, unit.to_string());
+            out.lock().unwrap().values.push(entry);
+        });
+        let out = shared.clone();
+        engine.register_fn("label", move |text: &str| {
+            out.lock().unwrap().labels.push(text.to_string());
+        });
+        let out = shared.clone();
+        engine.on_print(move |text| out.lock().unwrap().labels.push(text.to_string()));
+
+        Self {
+            engine,
+            ast: None,
+            loaded: None,
+            state: Dynamic::from_map(Map::new()),
+            shared,
+            output: Output::default(),
+            error: None,
+            show: false,
+        }
+    }
+}
+
+impl Script {
+    /// Runs the script on a new sample, the results are in `output`
+    pub fn run(&mut self, config: &ScriptConfig, data: &Data, time: u128) {
+        if !config.enabled {
+            self.output = Output::default();
+            return;
+        }
+        self.reload(config);
+        let Some(ast) = &self.ast else {
+            self.output = Output::default();
+            return;
+        };
+
+        let mut scope = Scope::new();
+        scope.push_constant("data", data_map(data, time));
+        scope.push("state", std::mem::take(&mut self.state));
+        let result = self.engine.run_ast_with_scope(&mut scope, ast);
+        self.state = scope
+            .remove::<Dynamic>("state")
+            .unwrap_or_else(|| Dynamic::from_map(Map::new()));
+        self.output = std::mem::take(&mut self.shared.lock().unwrap());
+        self.error = result.err().map(|e| format!("{}: {e}", tr("Script error")));
+    }
+
+    /// Compiles the script again if the path or the file changed
+    fn reload(&mut self, config: &ScriptConfig) {
+        let path = PathBuf::from(&config.path);
+        let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+        if self.loaded.as_ref() == Some(&(path.clone(), modified)) {
+            return;
+        }
+        self.loaded = Some((path.clone(), modified));
+        self.state = Dynamic::from_map(Map::new());
+
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) => {
+                self.ast = None;
+                self.error = Some(format!("{}: {e}", tr("Error loading script")));
+                return;
+            }
+        };
+        match self.engine.compile(text) {
+            Ok(ast) => {
+                self.ast = Some(ast);
+                self.error = None;
+            }
+            Err(e) => {
+                self.ast = None;
+                self.error = Some(format!("{}: {e}", tr("Script error")));
+            }
+        }
+    }
+
+    /// Shows the labels and values of the script
+    pub fn window(&mut self, ctx: &egui::Context) {
+        let mut show = self.show;
+        Window::new(tr("Script"))
+            .id("script".into())
+            .open(&mut show)
+            .default_size([300.0, 200.0])
+            .show(ctx, |ui| {
+                if let Some(error) = &self.error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+                for label in &self.output.labels {
+                    ui.label(label);
+                }
+                Grid::new("script_values").show(ui, |ui| {
+                    for (name, value, unit) in &self.output.values {
+                        ui.label(name);
+                        ui.label(format!("{value:.2}"));
+                        ui.label(unit);
+                        ui.end_row();
+                    }
+                });
+            });
+        self.show = show;
+    }
+}
+
+fn data_map(data: &Data, time: u128) -> Dynamic {
+    let voltage = |stats: &VoltageStats| {
+        let mut map = Map::new();
+        map.insert("min".into(), (stats.min_voltage as f64).into());
+        map.insert("avg".into(), (stats.avg_voltage as f64).into());
+        map.insert("max".into(), (stats.max_voltage as f64).into());
+        map.insert("delta".into(), (stats.delta_voltage as f64).into());
+        map
+    };
+    let temp = |map: &mut Map, prefix: &str, stats: &TempStats| {
+        map.insert(
+            format!("{prefix}min").into(),
+            (stats.min_temp as f64).into(),
+        );
+        map.insert(
+            format!("{prefix}avg").into(),
+            (stats.avg_temp as f64).into(),
+        );
+        map.insert(
+            format!("{prefix}max").into(),
+            (stats.max_temp as f64).into(),
+        );
+        map.insert(
+            format!("{prefix}delta").into(),
+            (stats.delta_temp as f64).into(),
+        );
+    };
+    let (ucell, tcell) = (&data.ucell, &data.tcell);
+
+    let mut map = Map::new();
+    map.insert("time".into(), (time as i64).into());
+    map.insert("voltage".into(), (data.main.voltage as f64).into());
+    map.insert("current".into(), (data.main.current as f64).into());
+    map.insert("soc".into(), (data.main.state_of_charge as f64).into());
+    map.insert("temp_master".into(), (data.main.temp_master as f64).into());
+    let dc_link = data
+        .main
+        .dc_link_voltage
+        .map_or(Dynamic::UNIT, |v| (v as f64).into());
+    map.insert("dc_link".into(), dc_link);
+
+    map.insert("cell".into(), voltage(&ucell.overall).into());
+    let mut left = voltage(&ucell.left);
+    temp(&mut left, "temp_", &tcell.left);
+    map.insert("left".into(), left.into());
+    let mut right = voltage(&ucell.right);
+    temp(&mut right, "temp_", &tcell.right);
+    map.insert("right".into(), right.into());
+    let mut overall_temp = Map::new();
+    temp(&mut overall_temp, "", &tcell.overall);
+    map.insert("temp".into(), overall_temp.into());
+
+    let cells: Array = ucell
+        .cell_voltage
+        .iter()
+        .map(|v| (*v as f64).into())
+        .collect();
+    map.insert("cells".into(), cells.into());
+    let temps: Array = tcell.temp.iter().map(|t| (*t as f64).into()).collect();
+    map.insert("temps".into(), temps.into());
+    map.into()
+}
@@ -0,0 +1,90 @@
+use std::io::{self, BufRead, BufReader};
+use std::time::{Duration, Instant};
+
+use crate::api::{self, Data, DataSource, PollOptions};
+use crate::stream::{Stream, Values};
+
+/// Reads the streaming output of the BMS master's debug UART in the background
+pub struct SerialSource {
+    stream: Stream,
+}
+
+impl SerialSource {
+    pub fn open(port: &str, baud_rate: u32) -> io::Result<Self> {
+        let serial = serialport::new(port, baud_rate)
+            .timeout(Duration::from_millis(200))
+            .open()?;
+        let mut reader = BufReader::new(serial);
+        let mut line = String::new();
+        let stream = Stream::spawn(move |values| {
+            // a timeout may leave a partial line in the buffer, which is completed by the next read
+            match reader.read_line(&mut line) {
+                Ok(_) => (),
+                Err(e) if e.kind() == io::ErrorKind::TimedOut => return Ok(()),
+                Err(e) => return Err(e),
+            }
+            if line.ends_with('\n') {
+                let mut values = values.lock().unwrap();
+                decode(&mut values, line.trim());
+                values.received = Some(Instant::now());
+                line.clear();
+            }
+            Ok(())
+        });
+
+        Ok(Self { stream })
+    }
+}
+
+impl DataSource for SerialSource {
+    fn poll(&mut self, options: &PollOptions) -> Result<Data, api::Error> {
+        self.stream.data(options.safe, &options.dead_sensors)
+    }
+
+    fn is_alive(&self) -> bool {
+        self.stream.is_alive()
+    }
+}
+
+/// Decodes one line of the debug output, unknown or malformed lines are ignored
+fn decode(values: &mut Values, line: &str) {
+    let mut fields = line.split(',');
+    let kind = fields.next().unwrap_or_default();
+    let Ok(numbers) = fields.map(str::parse::<i32>).collect::<Result<Vec<_>, _>>() else {
+        return;
+    };
+
+    match (kind, numbers.as_slice()) {
+        // voltage in mV, current in mA, state of charge in 0.1 %,
+        // average, minimum, maximum and master temperature in 0.1 °C
+        ("MAIN", [voltage, current, soc, avg, min, max, master]) => {
+            let main = &mut values.main;
+            main.voltage = *voltage as f32 / 1000.0;
+            main.current = *current as f32;
+            main.state_of_charge = *soc as f32 / 10.0;
+            main.temp_avg = *avg as f32 / 10.0;
+            main.temp_min = *min as f32 / 10.0;
+            main.temp_max = *max as f32 / 10.0;
+            main.temp_master = *master as f32 / 10.0;
+        }
+        ("CONF", [slaves, cells, cells_per_slave, temp_sensors, ..]) => {
+            values.num_slaves = *slaves as usize;
+            values.num_cells = *cells as usize;
+            values.num_cells_per_slave = *cells_per_slave as usize;
+            values.num_temp_sensors = *temp_sensors as usize;
+        }
+        // index of the first cell followed by the voltages in mV
+        ("UCELL", [first, voltage @ ..]) => {
+            for (i, v) in voltage.iter().enumerate() {
+                values.cell_voltage.insert(*first as usize + i, *v as u16);
+            }
+        }
+        // index of the first sensor followed by the temperatures in 0.1 °C
+        ("TCELL", [first, temp @ ..]) => {
+            for (i, t) in temp.iter().enumerate() {
+                values.temp.insert(*first as usize + i, *t as f32 / 10.0);
+            }
+        }
+        _ => (),
+    }
+}
@@ -0,0 +1,194 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use egui::{Align2, Grid, ScrollArea, TextEdit, Ui, Window};
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::tr;
+
+const SESSION_FILE: &str = "session.ron";
+
+/// Groups the recordings, reports and screenshots of one test or race
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    pub name: String,
+    pub car: String,
+    pub driver: String,
+    pub notes: String,
+    /// Unix time in ms, also used as identifier
+    pub start: u128,
+    pub end: Option<u128>,
+}
+
+impl Session {
+    pub fn directory(&self, log_directory: &Path) -> PathBuf {
+        log_directory.join(format!("session-{}", self.start))
+    }
+
+    pub fn save(&self, log_directory: &Path) -> io::Result<()> {
+        let directory = self.directory(log_directory);
+        fs::create_dir_all(&directory)?;
+        let text =
+            ron::ser::to_string_pretty(self, Default::default()).map_err(io::Error::other)?;
+        fs::write(directory.join(SESSION_FILE), text)
+    }
+
+    /// The session stored in `directory`, if it is a session directory
+    pub fn load(directory: &Path) -> Option<Session> {
+        let text = fs::read_to_string(directory.join(SESSION_FILE)).ok()?;
+        ron::from_str(&text).ok()
+    }
+
+    /// All sessions in the log directory, the most recent first
+    pub fn list(log_directory: &Path) -> Vec<Session> {
+        let Ok(entries) = fs::read_dir(log_directory) else {
+            return Vec::new();
+        };
+        let mut sessions: Vec<Session> = entries
+            .filter_map(|e| fs::read_to_string(e.ok()?.path().join(SESSION_FILE)).ok())
+            .filter_map(|text| ron::from_str(&text).ok())
+            .collect();
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.start));
+        sessions
+    }
+
+    fn fields(&mut self, ui: &mut Ui) {
+        Grid::new("session_fields").show(ui, |ui| {
+            ui.label(tr("Name"));
+            ui.text_edit_singleline(&mut self.name);
+            ui.end_row();
+
+            ui.label(tr("Car"));
+            ui.text_edit_singleline(&mut self.car);
+            ui.end_row();
+
+            ui.label(tr("Driver"));
+            ui.text_edit_singleline(&mut self.driver);
+            ui.end_row();
+
+            ui.label(tr("Notes"));
+            ui.add(TextEdit::multiline(&mut self.notes).desired_rows(3));
+            ui.end_row();
+        });
+    }
+}
+
+#[derive(Default)]
+pub struct SessionManager {
+    pub current: Option<Session>,
+    pub show: bool,
+    draft: Session,
+    sessions: Vec<Session>,
+}
+
+impl SessionManager {
+    /// Shows the session picker
+    pub fn open(&mut self, log_directory: &Path) {
+        self.sessions = Session::list(log_directory);
+        self.show = true;
+    }
+
+    /// The directory recordings and screenshots are written to
+    pub fn directory(&self, log_directory: &Path) -> PathBuf {
+        match &self.current {
+            Some(session) => session.directory(log_directory),
+            None => log_directory.to_path_buf(),
+        }
+    }
+
+    /// Returns a status message if something was changed
+    pub fn window(
+        &mut self,
+        ctx: &egui::Context,
+        log_directory: &Path,
+        now: u128,
+    ) -> Option<String> {
+        if !self.show {
+            return None;
+        }
+
+        let mut result = None;
+        let mut ended = false;
+        let mut show = true;
+        Window::new(tr("Session"))
+            .id("session".into())
+            .collapsible(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .open(&mut show)
+            .show(ctx, |ui| {
+                if let Some(session) = &mut self.current {
+                    ui.heading(&session.name);
+                    session.fields(ui);
+                    ui.horizontal(|ui| {
+                        if ui.button(tr("Save")).clicked() {
+                            result = Some(session.save(log_directory));
+                        }
+                        if ui.button(tr("End session")).clicked() {
+                            session.end = Some(now);
+                            result = Some(session.save(log_directory));
+                            ended = true;
+                        }
+                    });
+                    return;
+                }
+
+                ui.heading(tr("New session"));
+                self.draft.fields(ui);
+                ui.horizontal(|ui| {
+                    if ui.button(tr("Start session")).clicked() {
+                        let session = Session {
+                            start: now,
+                            end: None,
+                            ..self.draft.clone()
+                        };
+                        result = Some(session.save(log_directory));
+                        self.current = Some(session);
+                        self.draft.notes.clear();
+                        self.show = false;
+                    }
+                    if ui.button(tr("Continue without session")).clicked() {
+                        self.show = false;
+                    }
+                });
+
+                if self.sessions.is_empty() {
+                    return;
+                }
+                ui.separator();
+                ui.heading(tr("Previous sessions"));
+                ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                    let mut resume = None;
+                    Grid::new("sessions").striped(true).show(ui, |ui| {
+                        for (i, session) in self.sessions.iter().enumerate() {
+                            ui.label(&session.name);
+                            ui.label(&session.car);
+                            ui.label(&session.driver);
+                            if ui.button(tr("Resume")).clicked() {
+                                resume = Some(i);
+                            }
+                            ui.end_row();
+                        }
+                    });
+                    if let Some(i) = resume {
+                        let mut session = self.sessions.remove(i);
+                        session.end = None;
+                        result = Some(session.save(log_directory));
+                        self.current = Some(session);
+                        self.show = false;
+                    }
+                });
+            });
+        self.show &= show;
+        if ended {
+            self.current = None;
+            self.sessions = Session::list(log_directory);
+        }
+
+        match result? {
+            Ok(()) => Some(tr("Session saved").to_string()),
+            Err(e) => Some(format!("{}: {e}", tr("Error saving session"))),
+        }
+    }
+}
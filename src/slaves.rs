@@ -0,0 +1,96 @@
+use egui::{Color32, Grid, RichText, ScrollArea, Ui};
+
+use crate::api::Data;
+use crate::history::History;
+use crate::i18n::tr;
+use crate::units;
+
+/// Number of consecutive identical samples after which a slave is considered stale
+const STALE_SAMPLES: usize = 10;
+
+enum Health {
+    Ok,
+    Invalid(usize),
+    Stale,
+}
+
+pub fn slave_view(ui: &mut Ui, data: &Data, history: &History) {
+    let ucell = &data.ucell;
+    let num_slaves = ucell.num_slaves.max(1);
+    let cells_per_slave = match ucell.num_cells_per_slave {
+        0 => ucell.cell_voltage.len() / num_slaves,
+        n => n,
+    };
+    let temps_per_slave = data.tcell.temp.len() / num_slaves;
+
+    ScrollArea::vertical().show(ui, |ui| {
+        Grid::new("slaves").striped(true).show(ui, |ui| {
+            ui.strong(tr("Slave"));
+            ui.strong(tr("Min"));
+            ui.strong(tr("Avg"));
+            ui.strong(tr("Max"));
+            ui.strong(tr("Delta"));
+            ui.strong(tr("Temperatures"));
+            ui.strong(tr("Communication"));
+            ui.end_row();
+
+            for slave in 0..num_slaves {
+                let cells = slave * cells_per_slave..(slave + 1) * cells_per_slave;
+                let Some(voltage) = ucell.cell_voltage.get(cells.clone()) else {
+                    break;
+                };
+                let min = voltage.iter().copied().min().unwrap_or(0);
+                let max = voltage.iter().copied().max().unwrap_or(0);
+                let avg =
+                    voltage.iter().map(|v| *v as u32).sum::<u32>() / voltage.len().max(1) as u32;
+
+                let temps = slave * temps_per_slave..(slave + 1) * temps_per_slave;
+                let temps = data.tcell.temp.get(temps).unwrap_or_default();
+                let temps = temps
+                    .iter()
+                    .map(|t| units::temp_value(*t))
+                    .collect::<Vec<_>>()
+                    .join(" / ");
+
+                ui.label((slave + 1).to_string());
+                ui.label(units::voltage(min));
+                ui.label(units::voltage(avg));
+                ui.label(units::voltage(max));
+                ui.label(units::voltage(max - min));
+                ui.label(format!("{temps} {}", units::temp_unit()));
+                match health(voltage, history, cells) {
+                    Health::Ok => ui.label(RichText::new(tr("OK")).color(Color32::GREEN)),
+                    Health::Invalid(n) => ui.label(
+                        RichText::new(format!("{n} {}", tr("invalid values"))).color(Color32::RED),
+                    ),
+                    Health::Stale => ui.label(
+                        RichText::new(tr("No updates")).color(Color32::from_rgb(0xff, 0x8c, 0x00)),
+                    ),
+                };
+                ui.end_row();
+            }
+        });
+    });
+}
+
+fn health(voltage: &[u16], history: &History, cells: std::ops::Range<usize>) -> Health {
+    let invalid = voltage
+        .iter()
+        .filter(|v| !(1000..=5000).contains(*v))
+        .count();
+    if invalid > 0 {
+        return Health::Invalid(invalid);
+    }
+
+    let mut recent = history
+        .samples()
+        .rev()
+        .take(STALE_SAMPLES)
+        .filter_map(|s| s.cell_voltage.get(cells.clone()));
+    let stale = recent.clone().count() == STALE_SAMPLES && recent.all(|v| v == voltage);
+    if stale {
+        Health::Stale
+    } else {
+        Health::Ok
+    }
+}
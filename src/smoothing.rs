@@ -0,0 +1,277 @@
+use std::collections::VecDeque;
+
+use egui::{ComboBox, DragValue, Grid, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::api::{Data, Tcell, Ucell};
+use crate::app::View;
+use crate::i18n::tr;
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct SmoothingConfig {
+    /// Number of samples averaged
+    pub window: usize,
+    pub pack: bool,
+    pub slaves: bool,
+    pub driver: bool,
+    /// Interval in ms the displayed sample is updated at, the samples received in between are
+    /// combined so short spikes aren't lost between two repaints. 0 shows every sample.
+    pub frame_interval: u64,
+    pub frame_aggregate: FrameAggregate,
+}
+
+impl Default for SmoothingConfig {
+    fn default() -> Self {
+        Self {
+            window: 5,
+            pack: false,
+            slaves: false,
+            driver: false,
+            frame_interval: 0,
+            frame_aggregate: FrameAggregate::Mean,
+        }
+    }
+}
+
+/// How the cell values received during one displayed frame are combined
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FrameAggregate {
+    Mean,
+    Min,
+    Max,
+}
+
+impl FrameAggregate {
+    const ALL: [FrameAggregate; 3] = [
+        FrameAggregate::Mean,
+        FrameAggregate::Min,
+        FrameAggregate::Max,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            FrameAggregate::Mean => tr("Mean"),
+            FrameAggregate::Min => tr("Minimum"),
+            FrameAggregate::Max => tr("Maximum"),
+        }
+    }
+
+    fn combine<T: Into<f64>>(self, values: impl Iterator<Item = T>) -> f64 {
+        let (mut sum, mut n, mut extreme) = (0.0, 0.0, None::<f64>);
+        for v in values {
+            let v = v.into();
+            sum += v;
+            n += 1.0;
+            extreme = Some(match (self, extreme) {
+                (_, None) => v,
+                (FrameAggregate::Min, Some(e)) => e.min(v),
+                (_, Some(e)) => e.max(v),
+            });
+        }
+        match self {
+            FrameAggregate::Mean => sum / f64::max(n, 1.0),
+            FrameAggregate::Min | FrameAggregate::Max => extreme.unwrap_or_default(),
+        }
+    }
+}
+
+impl SmoothingConfig {
+    /// Whether the smoothed values are displayed in `view`
+    pub fn applies(&self, view: View, driver_mode: bool) -> bool {
+        if driver_mode {
+            return self.driver;
+        }
+        match view {
+            View::Pack => self.pack,
+            View::Slaves => self.slaves,
+            View::Charging
+            | View::Info
+            | View::Resistance
+            | View::Comparison
+            | View::StackBands => false,
+        }
+    }
+
+    pub fn settings(&mut self, ui: &mut Ui) {
+        Grid::new("smoothing_config").show(ui, |ui| {
+            ui.label(tr("Moving average window"));
+            ui.add(
+                DragValue::new(&mut self.window)
+                    .clamp_range(1..=100)
+                    .suffix(tr(" samples")),
+            );
+            ui.end_row();
+
+            ui.label(tr("Smooth pack view"));
+            ui.checkbox(&mut self.pack, "");
+            ui.end_row();
+
+            ui.label(tr("Smooth slave view"));
+            ui.checkbox(&mut self.slaves, "");
+            ui.end_row();
+
+            ui.label(tr("Smooth driver mode"));
+            ui.checkbox(&mut self.driver, "");
+            ui.end_row();
+
+            ui.label(tr("Display interval"));
+            ui.add(
+                DragValue::new(&mut self.frame_interval)
+                    .clamp_range(0..=5000)
+                    .speed(10)
+                    .suffix(" ms"),
+            )
+            .on_hover_text(tr("0 shows every sample"));
+            ui.end_row();
+
+            ui.label(tr("Combine samples by"));
+            ComboBox::from_id_source("frame_aggregate")
+                .selected_text(self.frame_aggregate.name())
+                .show_ui(ui, |ui| {
+                    for aggregate in FrameAggregate::ALL {
+                        ui.selectable_value(&mut self.frame_aggregate, aggregate, aggregate.name());
+                    }
+                });
+            ui.end_row();
+        });
+    }
+}
+
+/// Moving average of the cell voltages and temperatures, only used for displaying
+#[derive(Default)]
+pub struct Smoother {
+    voltages: VecDeque<Vec<u16>>,
+    temps: VecDeque<Vec<f32>>,
+    pub data: Option<Data>,
+}
+
+impl Smoother {
+    pub fn push(&mut self, config: &SmoothingConfig, data: &Data, dead_sensors: &[usize]) {
+        let ucell = &data.ucell;
+        let tcell = &data.tcell;
+        // the number of cells or sensors changes when switching between packs
+        if self
+            .voltages
+            .back()
+            .is_some_and(|v| v.len() != ucell.cell_voltage.len())
+            || self
+                .temps
+                .back()
+                .is_some_and(|t| t.len() != tcell.temp.len())
+        {
+            self.clear();
+        }
+        self.voltages.push_back(ucell.cell_voltage.clone());
+        self.temps.push_back(tcell.temp.clone());
+        while self.voltages.len() > config.window.max(1) {
+            self.voltages.pop_front();
+            self.temps.pop_front();
+        }
+
+        let n = self.voltages.len();
+        if ucell.cell_voltage.is_empty() {
+            self.data = None;
+            return;
+        }
+        let voltage = (0..ucell.cell_voltage.len())
+            .map(|i| {
+                let sum = self.voltages.iter().map(|v| v[i] as usize).sum::<usize>();
+                (sum / n) as u16
+            })
+            .collect();
+        let temp = (0..tcell.temp.len())
+            .map(|i| self.temps.iter().map(|t| t[i]).sum::<f32>() / n as f32)
+            .collect();
+
+        // the samples were already filtered in safe mode
+        let smoothed_ucell = Ucell {
+            num_slaves: ucell.num_slaves,
+            num_cells: ucell.num_cells,
+            num_cells_per_slave: ucell.num_cells_per_slave,
+            num_temp_sensors: ucell.num_temp_sensors,
+            num_safe_resistors: ucell.num_safe_resistors,
+            ..Ucell::new(voltage, false)
+        };
+        self.data = Some(Data {
+            main: data.main,
+            ucell: smoothed_ucell,
+            tcell: Tcell::new(temp, false, dead_sensors),
+            status: data.status,
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.voltages.clear();
+        self.temps.clear();
+        self.data = None;
+    }
+}
+
+/// Samples received since the last displayed frame
+#[derive(Default)]
+pub struct FrameBuffer {
+    samples: Vec<Data>,
+    last_frame: u128,
+}
+
+impl FrameBuffer {
+    pub fn push(&mut self, data: Data) {
+        self.samples.push(data);
+    }
+
+    /// Combines the buffered samples into the one to display once the frame interval has passed
+    pub fn take(
+        &mut self,
+        config: &SmoothingConfig,
+        dead_sensors: &[usize],
+        time: u128,
+    ) -> Option<Data> {
+        if self.samples.is_empty() || time < self.last_frame + config.frame_interval as u128 {
+            return None;
+        }
+        self.last_frame = time;
+        let samples = std::mem::take(&mut self.samples);
+        let latest = samples.last()?;
+        let (ucell, tcell) = (&latest.ucell, &latest.tcell);
+        // the number of cells or sensors changes when switching between packs
+        let samples: Vec<&Data> = samples
+            .iter()
+            .filter(|s| {
+                s.ucell.cell_voltage.len() == ucell.cell_voltage.len()
+                    && s.tcell.temp.len() == tcell.temp.len()
+            })
+            .collect();
+        if samples.len() == 1 || ucell.cell_voltage.is_empty() {
+            return Some(latest.clone());
+        }
+
+        let aggregate = config.frame_aggregate;
+        let voltage = (0..ucell.cell_voltage.len())
+            .map(|i| aggregate.combine(samples.iter().map(|s| s.ucell.cell_voltage[i])) as u16)
+            .collect();
+        let temp = (0..tcell.temp.len())
+            .map(|i| aggregate.combine(samples.iter().map(|s| s.tcell.temp[i])) as f32)
+            .collect();
+
+        // the samples were already filtered in safe mode
+        let combined_ucell = Ucell {
+            num_slaves: ucell.num_slaves,
+            num_cells: ucell.num_cells,
+            num_cells_per_slave: ucell.num_cells_per_slave,
+            num_temp_sensors: ucell.num_temp_sensors,
+            num_safe_resistors: ucell.num_safe_resistors,
+            ..Ucell::new(voltage, false)
+        };
+        Some(Data {
+            main: latest.main,
+            ucell: combined_ucell,
+            tcell: Tcell::new(temp, false, dead_sensors),
+            status: latest.status,
+        })
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+}
@@ -0,0 +1,151 @@
+use egui::{DragValue, Grid, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::api::Data;
+use crate::i18n::tr;
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct OcvConfig {
+    pub enabled: bool,
+    /// Maximum absolute current in mA for the pack to be considered at rest
+    pub rest_current: f32,
+    /// Time in s the pack has to be at rest before the cell voltage is used
+    pub rest_time: u64,
+    /// Open-circuit voltage in mV to state of charge in %, sorted by voltage
+    pub table: Vec<(u16, f32)>,
+}
+
+impl Default for OcvConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            rest_current: 2000.0,
+            rest_time: 300,
+            table: vec![
+                (3000, 0.0),
+                (3300, 2.0),
+                (3450, 5.0),
+                (3550, 10.0),
+                (3620, 20.0),
+                (3680, 30.0),
+                (3730, 40.0),
+                (3790, 50.0),
+                (3860, 60.0),
+                (3940, 70.0),
+                (4020, 80.0),
+                (4090, 90.0),
+                (4180, 100.0),
+            ],
+        }
+    }
+}
+
+impl OcvConfig {
+    /// Linearly interpolates the state of charge for a cell voltage
+    pub fn soc(&self, voltage: u16) -> Option<f32> {
+        let (first, last) = (self.table.first()?, self.table.last()?);
+        if voltage <= first.0 {
+            return Some(first.1);
+        }
+        if voltage >= last.0 {
+            return Some(last.1);
+        }
+
+        let upper = self.table.iter().position(|(v, _)| *v >= voltage)?;
+        let (v0, soc0) = self.table[upper - 1];
+        let (v1, soc1) = self.table[upper];
+        let t = (voltage as f32 - v0 as f32) / (v1 as f32 - v0 as f32);
+        Some(soc0 + t * (soc1 - soc0))
+    }
+
+    pub fn settings(&mut self, ui: &mut Ui) {
+        ui.checkbox(
+            &mut self.enabled,
+            tr("Estimate SoC from open-circuit voltage"),
+        );
+        Grid::new("ocv_config").show(ui, |ui| {
+            ui.label(tr("Rest current"));
+            ui.add(
+                DragValue::new(&mut self.rest_current)
+                    .clamp_range(0.0..=50000.0)
+                    .suffix(" mA"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Rest time"));
+            ui.add(
+                DragValue::new(&mut self.rest_time)
+                    .clamp_range(0..=3600)
+                    .suffix(" s"),
+            );
+            ui.end_row();
+        });
+
+        ui.collapsing(tr("OCV table"), |ui| {
+            let mut remove = None;
+            Grid::new("ocv_table").show(ui, |ui| {
+                ui.label(tr("Voltage"));
+                ui.label(tr("State of charge"));
+                ui.end_row();
+
+                for (i, (voltage, soc)) in self.table.iter_mut().enumerate() {
+                    ui.add(
+                        DragValue::new(voltage)
+                            .clamp_range(2000..=5000)
+                            .suffix(" mV"),
+                    );
+                    ui.add(DragValue::new(soc).clamp_range(0.0..=100.0).suffix(" %"));
+                    if ui.button("x").clicked() {
+                        remove = Some(i);
+                    }
+                    ui.end_row();
+                }
+            });
+            if let Some(i) = remove {
+                self.table.remove(i);
+            }
+            ui.horizontal(|ui| {
+                if ui.button(tr("Add point")).clicked() {
+                    let last = self.table.last().copied().unwrap_or((3000, 0.0));
+                    self.table.push(last);
+                }
+                if ui.button(tr("Reset table")).clicked() {
+                    self.table = OcvConfig::default().table;
+                }
+            });
+            if !ui.ctx().is_using_pointer() {
+                self.table.sort_by_key(|(v, _)| *v);
+            }
+        });
+    }
+}
+
+#[derive(Default)]
+pub struct OcvEstimator {
+    rest_since: Option<u128>,
+    pub rested: bool,
+    /// Last estimate taken while the pack was at rest
+    pub estimate: Option<f32>,
+}
+
+impl OcvEstimator {
+    pub fn update(&mut self, config: &OcvConfig, data: &Data, now: u128) {
+        if !config.enabled {
+            *self = Self::default();
+            return;
+        }
+
+        if data.main.current.abs() > config.rest_current {
+            self.rest_since = None;
+            self.rested = false;
+            return;
+        }
+
+        let rest_since = *self.rest_since.get_or_insert(now);
+        self.rested = now.saturating_sub(rest_since) >= config.rest_time as u128 * 1000;
+        if self.rested {
+            self.estimate = config.soc(data.ucell.overall.min_voltage);
+        }
+    }
+}
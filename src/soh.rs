@@ -0,0 +1,385 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+use egui::{DragValue, Grid, Ui, Window};
+use egui_plot::{Bar, BarChart, Legend, Line, Plot, PlotPoints};
+use serde::{Deserialize, Serialize};
+
+use crate::api::Data;
+use crate::i18n::tr;
+use crate::inventory::CellInventory;
+use crate::soc::OcvConfig;
+
+const CYCLES_FILE: &str = "cycles.csv";
+const CHARGES_FILE: &str = "charges.csv";
+/// Number of cycles averaged at the start and the end of the capacity history for the fade
+const FADE_CYCLES: usize = 3;
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct SohConfig {
+    /// Minimum current in mA for the pack to be considered discharging
+    pub discharge_current: f32,
+    /// Time in s without discharge after which a cycle is finished
+    pub end_time: u64,
+    /// Minimum charge in Ah for a discharge to count as a cycle
+    pub min_charge: f32,
+    /// Minimum charging current in mA for the pack to be considered charging
+    pub charge_current: f32,
+    /// Cells whose capacity faded this many percentage points more than the pack median are
+    /// flagged
+    pub fade_margin: f32,
+}
+
+impl Default for SohConfig {
+    fn default() -> Self {
+        Self {
+            discharge_current: 5000.0,
+            end_time: 60,
+            min_charge: 1.0,
+            charge_current: 2000.0,
+            fade_margin: 5.0,
+        }
+    }
+}
+
+impl SohConfig {
+    pub fn settings(&mut self, ui: &mut Ui) {
+        Grid::new("soh_config").show(ui, |ui| {
+            ui.label(tr("Discharge current"));
+            ui.add(
+                DragValue::new(&mut self.discharge_current)
+                    .clamp_range(0.0..=100000.0)
+                    .suffix(" mA"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Cycle end time"));
+            ui.add(
+                DragValue::new(&mut self.end_time)
+                    .clamp_range(5..=3600)
+                    .suffix(" s"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Minimum cycle charge"));
+            ui.add(
+                DragValue::new(&mut self.min_charge)
+                    .clamp_range(0.1..=100.0)
+                    .speed(0.1)
+                    .suffix(" Ah"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Charging current"));
+            ui.add(
+                DragValue::new(&mut self.charge_current)
+                    .clamp_range(0.0..=100000.0)
+                    .suffix(" mA"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Capacity fade margin"));
+            ui.add(
+                DragValue::new(&mut self.fade_margin)
+                    .clamp_range(0.5..=50.0)
+                    .speed(0.1)
+                    .suffix(" %"),
+            );
+            ui.end_row();
+        });
+    }
+}
+
+/// Metrics of one completed discharge or charge
+pub struct Cycle {
+    /// Unix time in ms of the end of the discharge or charge
+    pub time: u128,
+    /// Discharged or charged charge in Ah
+    pub charge: f32,
+    /// Difference between the highest and lowest cell at the end in mV
+    pub imbalance: u16,
+    /// Estimated capacity of every cell in Ah
+    pub capacity: Vec<f32>,
+}
+
+impl Cycle {
+    fn to_csv(&self) -> String {
+        let mut line = format!("{},{},{}", self.time, self.charge, self.imbalance);
+        for c in &self.capacity {
+            line.push_str(&format!(",{c}"));
+        }
+        line
+    }
+
+    fn from_csv(line: &str) -> Option<Self> {
+        let mut values = line.split(',');
+        Some(Self {
+            time: values.next()?.parse().ok()?,
+            charge: values.next()?.parse().ok()?,
+            imbalance: values.next()?.parse().ok()?,
+            capacity: values.map(|v| v.parse().ok()).collect::<Option<_>>()?,
+        })
+    }
+}
+
+struct Session {
+    charging: bool,
+    last_time: u128,
+    /// Last time the current exceeded the threshold of the session's direction
+    last_active: u128,
+    charge: f32,
+    start_voltage: Vec<u16>,
+}
+
+#[derive(Default)]
+pub struct SohTracker {
+    session: Option<Session>,
+    /// Completed discharges
+    pub cycles: Vec<Cycle>,
+    /// Completed charges
+    pub charges: Vec<Cycle>,
+    pub show: bool,
+}
+
+impl SohTracker {
+    /// Loads the previously recorded cycles and charges from the log directory
+    pub fn load(directory: &Path) -> Self {
+        let load = |file: &str| {
+            fs::read_to_string(directory.join(file))
+                .map(|text| text.lines().filter_map(Cycle::from_csv).collect())
+                .unwrap_or_default()
+        };
+        Self {
+            cycles: load(CYCLES_FILE),
+            charges: load(CHARGES_FILE),
+            ..Default::default()
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        config: &SohConfig,
+        ocv: &OcvConfig,
+        directory: &Path,
+        data: &Data,
+        time: u128,
+    ) -> io::Result<()> {
+        let current = data.main.current;
+        let discharging = current > config.discharge_current;
+        let charging = current < -config.charge_current;
+
+        let Some(session) = &mut self.session else {
+            if discharging || charging {
+                self.session = Some(Session {
+                    charging,
+                    last_time: time,
+                    last_active: time,
+                    charge: 0.0,
+                    start_voltage: data.ucell.cell_voltage.clone(),
+                });
+            }
+            return Ok(());
+        };
+
+        let hours = time.saturating_sub(session.last_time) as f32 / 3_600_000.0;
+        let flow = if session.charging { -current } else { current };
+        session.charge += flow.max(0.0) / 1000.0 * hours;
+        session.last_time = time;
+        if (session.charging && charging) || (!session.charging && discharging) {
+            session.last_active = time;
+            return Ok(());
+        }
+        if time.saturating_sub(session.last_active) < config.end_time as u128 * 1000 {
+            return Ok(());
+        }
+
+        let session = self.session.take().unwrap();
+        let end_voltage = &data.ucell.cell_voltage;
+        if session.charge < config.min_charge || end_voltage.len() != session.start_voltage.len() {
+            return Ok(());
+        }
+
+        // the charge divided by the change in state of charge of every cell
+        let capacity = session
+            .start_voltage
+            .iter()
+            .zip(end_voltage)
+            .map(|(start, end)| {
+                let drop = ocv.soc(*start).unwrap_or(0.0) - ocv.soc(*end).unwrap_or(0.0);
+                let change = if session.charging { -drop } else { drop };
+                if change > 0.0 {
+                    session.charge / change * 100.0
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        let cycle = Cycle {
+            time,
+            charge: session.charge,
+            imbalance: data.ucell.overall.delta_voltage,
+            capacity,
+        };
+
+        let (file, cycles) = match session.charging {
+            true => (CHARGES_FILE, &mut self.charges),
+            false => (CYCLES_FILE, &mut self.cycles),
+        };
+        fs::create_dir_all(directory)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(directory.join(file))?;
+        writeln!(file, "{}", cycle.to_csv())?;
+        cycles.push(cycle);
+        Ok(())
+    }
+
+    /// Discharges and charges with the same number of cells as the latest, ordered by time
+    fn capacity_history(&self) -> Vec<&Cycle> {
+        let mut history: Vec<&Cycle> = self.cycles.iter().chain(&self.charges).collect();
+        history.sort_by_key(|c| c.time);
+        if let Some(cells) = history.last().map(|c| c.capacity.len()) {
+            history.retain(|c| c.capacity.len() == cells);
+        }
+        history
+    }
+
+    /// Cells whose capacity fade in % exceeds the median fade of the pack by more than the margin,
+    /// with their fade
+    pub fn fading_cells(&self, config: &SohConfig) -> Vec<(usize, f32)> {
+        let fades = fades(&self.capacity_history());
+        let mut sorted: Vec<f32> = fades.iter().flatten().copied().collect();
+        if sorted.is_empty() {
+            return Vec::new();
+        }
+        sorted.sort_by(f32::total_cmp);
+        let median = sorted[sorted.len() / 2];
+        fades
+            .iter()
+            .enumerate()
+            .filter_map(|(i, fade)| Some((i, (*fade)?)))
+            .filter(|(_, fade)| fade - median > config.fade_margin)
+            .collect()
+    }
+
+    pub fn window(&mut self, ctx: &egui::Context, config: &SohConfig, inventory: &CellInventory) {
+        if !self.show {
+            return;
+        }
+        let cycles = &self.cycles;
+        let history = self.capacity_history();
+        let fading = self.fading_cells(config);
+        let mut show = self.show;
+        Window::new(tr("State of health"))
+            .id("state_of_health".into())
+            .open(&mut show)
+            .default_size([600.0, 500.0])
+            .show(ctx, |ui| {
+                if history.is_empty() {
+                    ui.label(tr(
+                        "No cycles recorded yet, enable recording to track cycles",
+                    ));
+                    return;
+                }
+
+                ui.label(tr("Per cycle"));
+                let imbalance: PlotPoints = cycles
+                    .iter()
+                    .enumerate()
+                    .map(|(i, c)| [i as f64 + 1.0, c.imbalance as f64])
+                    .collect();
+                let min_capacity: PlotPoints = cycles
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, c)| {
+                        let min = c.capacity.iter().copied().filter(|c| *c > 0.0);
+                        let min = min.min_by(f32::total_cmp)?;
+                        Some([i as f64 + 1.0, min as f64])
+                    })
+                    .collect();
+                Plot::new("soh_trend")
+                    .height(ui.available_height() / 3.0)
+                    .legend(Legend::default())
+                    .show(ui, |plot| {
+                        plot.line(Line::new(imbalance).name(tr("Imbalance (mV)")));
+                        plot.line(Line::new(min_capacity).name(tr("Lowest cell capacity (Ah)")));
+                    });
+
+                ui.label(tr("Cell capacity over discharges and charges"));
+                for (cell, fade) in &fading {
+                    let text = format!(
+                        "{} {}: {} {fade:.1} %",
+                        tr("Cell"),
+                        inventory.label(*cell),
+                        tr("capacity faded by")
+                    );
+                    ui.colored_label(ui.visuals().warn_fg_color, text);
+                }
+                let median: PlotPoints = history
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, c)| Some([i as f64 + 1.0, median(&c.capacity)? as f64]))
+                    .collect();
+                Plot::new("soh_fade")
+                    .height(ui.available_height() / 2.0)
+                    .legend(Legend::default())
+                    .show(ui, |plot| {
+                        plot.line(Line::new(median).name(tr("Median cell capacity (Ah)")));
+                        for (cell, _) in &fading {
+                            let points: PlotPoints = history
+                                .iter()
+                                .enumerate()
+                                .filter(|(_, c)| c.capacity[*cell] > 0.0)
+                                .map(|(i, c)| [i as f64 + 1.0, c.capacity[*cell] as f64])
+                                .collect();
+                            let name = format!("{} {}", tr("Cell"), inventory.label(*cell));
+                            plot.line(Line::new(points).name(name));
+                        }
+                    });
+
+                ui.label(tr("Cell capacity of the last cycle"));
+                let last = history.last().unwrap();
+                let bars = last
+                    .capacity
+                    .iter()
+                    .enumerate()
+                    .map(|(i, c)| Bar::new(i as f64 + 1.0, *c as f64))
+                    .collect();
+                Plot::new("soh_cells").show(ui, |plot| {
+                    plot.bar_chart(BarChart::new(bars).name(tr("Capacity (Ah)")));
+                });
+            });
+        self.show = show;
+    }
+}
+
+/// Median of the estimated capacities, cells without an estimate are skipped
+fn median(capacity: &[f32]) -> Option<f32> {
+    let mut values: Vec<f32> = capacity.iter().copied().filter(|c| *c > 0.0).collect();
+    values.sort_by(f32::total_cmp);
+    values.get(values.len() / 2).copied()
+}
+
+/// Capacity fade of every cell in % between the first and the last cycles of the history
+fn fades(history: &[&Cycle]) -> Vec<Option<f32>> {
+    let cells = history.last().map_or(0, |c| c.capacity.len());
+    (0..cells)
+        .map(|i| {
+            let values: Vec<f32> = history
+                .iter()
+                .map(|c| c.capacity[i])
+                .filter(|c| *c > 0.0)
+                .collect();
+            if values.len() < 2 * FADE_CYCLES {
+                return None;
+            }
+            let mean = |v: &[f32]| v.iter().sum::<f32>() / v.len() as f32;
+            let first = mean(&values[..FADE_CYCLES]);
+            let last = mean(&values[values.len() - FADE_CYCLES..]);
+            Some((first - last) / first * 100.0)
+        })
+        .collect()
+}
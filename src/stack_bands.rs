@@ -0,0 +1,78 @@
+use egui::{Color32, DragValue, Grid, Ui, Vec2};
+use egui_plot::{Line, Plot, PlotPoints};
+
+use crate::history::{History, Sample};
+use crate::i18n::tr;
+use crate::markers::Markers;
+use crate::units;
+
+/// One mini plot per stack with the band between its lowest and highest cell and the average, on
+/// shared axes so a stack whose spread diverges from the others stands out
+pub fn stack_bands_view(
+    ui: &mut Ui,
+    history: &History,
+    markers: &Markers,
+    stacks: usize,
+    window: &mut u64,
+) {
+    ui.horizontal(|ui| {
+        ui.label(tr("Window"));
+        ui.add(DragValue::new(window).clamp_range(30..=3600).suffix(" s"));
+    });
+
+    let samples: Vec<&Sample> = history.window(*window as u128 * 1000).collect();
+    let start = samples.first().map_or(0, |s| s.time);
+    let minutes = |s: &Sample| (s.time - start) as f64 / 60_000.0;
+    let marker_x = |t: u128| (t >= start).then(|| (t - start) as f64 / 60_000.0);
+    let cells = samples.first().map_or(0, |s| s.cell_voltage.len()) / stacks.max(1);
+    let pack = |value: fn(&Sample) -> u16| -> PlotPoints {
+        samples
+            .iter()
+            .map(|s| [minutes(s), units::voltage_scale(value(s) as f64)])
+            .collect()
+    };
+    let stack = |stack: usize, value: fn(&[u16]) -> f64| -> PlotPoints {
+        samples
+            .iter()
+            .filter_map(|s| {
+                let voltages = s.cell_voltage.get(stack * cells..(stack + 1) * cells)?;
+                (!voltages.is_empty()).then(|| [minutes(s), units::voltage_scale(value(voltages))])
+            })
+            .collect()
+    };
+
+    let columns = stacks.div_ceil(2).max(1);
+    let spacing = ui.spacing().item_spacing;
+    let size = (ui.available_size() - spacing * Vec2::new(columns as f32, 2.0))
+        / Vec2::new(columns as f32, 2.0);
+    let band_color = ui.visuals().hyperlink_color;
+    Grid::new("stack_bands").show(ui, |ui| {
+        for i in 0..stacks {
+            ui.vertical(|ui| {
+                ui.label(format!("{} {}", tr("Stack"), i + 1));
+                Plot::new(("stack_band", i))
+                    .width(size.x)
+                    .height((size.y - 20.0).max(60.0))
+                    .link_axis("stack_bands", true, true)
+                    .link_cursor("stack_bands", true, false)
+                    .y_axis_label(units::voltage_unit())
+                    .show(ui, |plot| {
+                        // the pack extremes as reference for the spread of the stack
+                        plot.line(Line::new(pack(|s| s.min_voltage)).color(Color32::GRAY));
+                        plot.line(Line::new(pack(|s| s.max_voltage)).color(Color32::GRAY));
+                        let min = |v: &[u16]| v.iter().copied().min().unwrap_or_default() as f64;
+                        let max = |v: &[u16]| v.iter().copied().max().unwrap_or_default() as f64;
+                        let avg =
+                            |v: &[u16]| v.iter().map(|c| *c as f64).sum::<f64>() / v.len() as f64;
+                        plot.line(Line::new(stack(i, min)).color(band_color));
+                        plot.line(Line::new(stack(i, max)).color(band_color));
+                        plot.line(Line::new(stack(i, avg)).color(band_color).width(2.0));
+                        markers.plot(plot, marker_x);
+                    });
+            });
+            if (i + 1).is_multiple_of(columns) {
+                ui.end_row();
+            }
+        }
+    });
+}
@@ -0,0 +1,119 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::api::{self, Data, Main, Tcell, Ucell};
+
+/// Values older than this are outdated, the BMS broadcasts several times per second, so the bus
+/// or the UART went silent
+const MAX_AGE: Duration = Duration::from_secs(3);
+
+/// Last received value of every signal of a streaming source
+#[derive(Default)]
+pub struct Values {
+    pub main: Main,
+    pub num_slaves: usize,
+    pub num_cells: usize,
+    pub num_cells_per_slave: usize,
+    pub num_temp_sensors: usize,
+    pub cell_voltage: BTreeMap<usize, u16>,
+    pub temp: BTreeMap<usize, f32>,
+    /// Time the last signal was received
+    pub received: Option<Instant>,
+}
+
+impl Values {
+    pub fn data(&self, safe: bool, dead_sensors: &[usize]) -> Option<Data> {
+        // wait until every cell has been received at least once
+        if self.num_cells == 0
+            || self.cell_voltage.len() < self.num_cells
+            || self.temp.len() < self.num_temp_sensors
+        {
+            return None;
+        }
+
+        let voltage = self.cell_voltage.values().take(self.num_cells).copied();
+        let mut ucell = Ucell::new(voltage.collect(), safe);
+        ucell.num_slaves = self.num_slaves;
+        ucell.num_cells = self.num_cells;
+        ucell.num_cells_per_slave = self.num_cells_per_slave;
+        ucell.num_temp_sensors = self.num_temp_sensors;
+
+        let temp = self.temp.values().take(self.num_temp_sensors).copied();
+        let tcell = Tcell::new(temp.collect(), safe, dead_sensors);
+
+        Some(Data {
+            main: self.main,
+            ucell,
+            tcell,
+            status: None,
+        })
+    }
+}
+
+/// Reads values in a background thread until dropped
+pub struct Stream {
+    values: Arc<Mutex<Values>>,
+    error: Arc<Mutex<Option<io::Error>>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Stream {
+    /// `read` is called repeatedly and has to return regularly, so the thread notices when the
+    /// stream is dropped
+    pub fn spawn(mut read: impl FnMut(&Mutex<Values>) -> io::Result<()> + Send + 'static) -> Self {
+        let values = Arc::new(Mutex::new(Values::default()));
+        let error = Arc::new(Mutex::new(None));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let (thread_values, thread_error, thread_stop) =
+            (values.clone(), error.clone(), stop.clone());
+        thread::spawn(move || {
+            while !thread_stop.load(Ordering::Relaxed) {
+                if let Err(e) = read(&thread_values) {
+                    *thread_error.lock().unwrap() = Some(e);
+                    break;
+                }
+            }
+        });
+
+        Self {
+            values,
+            error,
+            stop,
+        }
+    }
+
+    /// Whether the thread is still reading, a stream that failed has to be opened again
+    pub fn is_alive(&self) -> bool {
+        self.error.lock().unwrap().is_none()
+    }
+
+    /// Snapshot of the most recently received values
+    pub fn data(&self, safe: bool, dead_sensors: &[usize]) -> Result<Data, api::Error> {
+        // the values stay frozen after the thread failed, so the error is reported until the
+        // stream is dropped
+        if let Some(e) = &*self.error.lock().unwrap() {
+            return Err(api::Error::Fetch(anyhow::anyhow!("{e}")));
+        }
+        let values = self.values.lock().unwrap();
+        if values.received.is_some_and(|t| t.elapsed() > MAX_AGE) {
+            return Err(api::Error::Fetch(anyhow::anyhow!(
+                "No data received for {} s",
+                MAX_AGE.as_secs()
+            )));
+        }
+        values
+            .data(safe, dead_sensors)
+            .ok_or_else(|| api::Error::Fetch(anyhow::anyhow!("Waiting for data")))
+    }
+}
+
+impl Drop for Stream {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
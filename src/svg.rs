@@ -0,0 +1,82 @@
+use std::fmt::Write;
+
+use egui::{Align2, Color32, Rect};
+
+/// Minimal SVG writer for the exported diagrams, every shape uses the user units of the page
+pub struct Svg {
+    width: f32,
+    height: f32,
+    body: String,
+}
+
+impl Svg {
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            width,
+            height,
+            body: String::new(),
+        }
+    }
+
+    pub fn rect(&mut self, rect: Rect, fill: Option<Color32>, stroke: Option<(f32, Color32)>) {
+        let fill = fill.map_or("none".into(), color);
+        let _ = write!(
+            self.body,
+            r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{fill}""#,
+            rect.left(),
+            rect.top(),
+            rect.width(),
+            rect.height()
+        );
+        if let Some((width, stroke)) = stroke {
+            let _ = write!(
+                self.body,
+                r#" stroke="{}" stroke-width="{width:.1}""#,
+                color(stroke)
+            );
+        }
+        self.body.push_str("/>\n");
+    }
+
+    pub fn text(&mut self, rect: Rect, align: Align2, size: f32, fill: Color32, text: &str) {
+        let pos = align.pos_in_rect(&rect);
+        let anchor = match align.x() {
+            egui::Align::Min => "start",
+            egui::Align::Center => "middle",
+            egui::Align::Max => "end",
+        };
+        let baseline = match align.y() {
+            egui::Align::Min => "hanging",
+            egui::Align::Center => "central",
+            egui::Align::Max => "alphabetic",
+        };
+        let _ = writeln!(
+            self.body,
+            r#"<text x="{:.1}" y="{:.1}" font-size="{size:.1}" fill="{}" text-anchor="{anchor}" dominant-baseline="{baseline}">{}</text>"#,
+            pos.x,
+            pos.y,
+            color(fill),
+            escape(text)
+        );
+    }
+
+    pub fn finish(self) -> String {
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+             viewBox=\"0 0 {w} {h}\" font-family=\"monospace\">\n{}</svg>\n",
+            self.body,
+            w = self.width,
+            h = self.height
+        )
+    }
+}
+
+fn color(color: Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
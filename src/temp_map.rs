@@ -0,0 +1,77 @@
+use egui::{DragValue, Grid, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::tr;
+
+/// Maps the temperature sensors to the cells they are mounted on
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct TempMapping {
+    /// Draw the sensor temperatures as borders around the voltage cells
+    pub enabled: bool,
+    /// First and last cell index of every sensor, 0-based and inclusive
+    pub groups: Vec<(usize, usize)>,
+    /// Known-dead sensors that are excluded from the statistics
+    pub dead: Vec<usize>,
+}
+
+impl Default for TempMapping {
+    fn default() -> Self {
+        // every stack has two sensors, one for each column of 9 cells
+        let groups = (0..16).map(|i| (i * 9, i * 9 + 8)).collect();
+        Self {
+            enabled: false,
+            groups,
+            dead: Vec::new(),
+        }
+    }
+}
+
+impl TempMapping {
+    pub fn sensor(&self, cell_index: usize) -> Option<usize> {
+        self.groups
+            .iter()
+            .position(|(first, last)| (*first..=*last).contains(&cell_index))
+    }
+
+    pub fn is_dead(&self, sensor: usize) -> bool {
+        self.dead.contains(&sensor)
+    }
+
+    pub fn settings(&mut self, ui: &mut Ui) {
+        ui.checkbox(&mut self.enabled, tr("Show temperatures as cell borders"));
+        ui.collapsing(tr("Sensor mapping"), |ui| {
+            Grid::new("temp_mapping").show(ui, |ui| {
+                ui.label(tr("Sensor"));
+                ui.label(tr("First cell"));
+                ui.label(tr("Last cell"));
+                ui.label(tr("Dead"));
+                ui.end_row();
+
+                for (i, (first, last)) in self.groups.iter_mut().enumerate() {
+                    ui.label((i + 1).to_string());
+                    // displayed 1-based like the cell labels
+                    let mut first_label = *first + 1;
+                    let mut last_label = *last + 1;
+                    ui.add(DragValue::new(&mut first_label).clamp_range(1..=999));
+                    ui.add(DragValue::new(&mut last_label).clamp_range(first_label..=999));
+                    *first = first_label - 1;
+                    *last = last_label.max(first_label) - 1;
+
+                    let mut dead = self.dead.contains(&i);
+                    if ui.checkbox(&mut dead, "").changed() {
+                        if dead {
+                            self.dead.push(i);
+                        } else {
+                            self.dead.retain(|d| *d != i);
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+            if ui.button(tr("Reset mapping")).clicked() {
+                self.groups = TempMapping::default().groups;
+            }
+        });
+    }
+}
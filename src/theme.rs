@@ -0,0 +1,129 @@
+use egui::{Color32, ComboBox, Ui, Visuals};
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::tr;
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThemeMode {
+    System,
+    Dark,
+    Light,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Appearance {
+    pub mode: ThemeMode,
+    pub custom_colors: bool,
+    pub dark: Palette,
+    pub light: Palette,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct Palette {
+    pub background: Color32,
+    pub panel: Color32,
+    pub accent: Color32,
+}
+
+impl Default for Appearance {
+    fn default() -> Self {
+        Self {
+            mode: ThemeMode::System,
+            custom_colors: false,
+            dark: Palette::DARK,
+            light: Palette::LIGHT,
+        }
+    }
+}
+
+impl Palette {
+    const DARK: Self = Self {
+        background: Color32::from_gray(27),
+        panel: Color32::from_gray(0x20),
+        accent: Color32::from_rgb(0, 92, 128),
+    };
+    const LIGHT: Self = Self {
+        background: Color32::from_gray(248),
+        panel: Color32::from_gray(0xf0),
+        accent: Color32::from_rgb(144, 209, 255),
+    };
+}
+
+impl Appearance {
+    /// Applies the selected theme, falling back to the system theme in [`ThemeMode::System`]
+    pub fn apply(&self, ctx: &egui::Context, system_theme: Option<eframe::Theme>) {
+        let dark_mode = match self.mode {
+            ThemeMode::System => match system_theme {
+                Some(theme) => theme == eframe::Theme::Dark,
+                None => ctx.style().visuals.dark_mode,
+            },
+            ThemeMode::Dark => true,
+            ThemeMode::Light => false,
+        };
+
+        let mut visuals = if dark_mode {
+            Visuals::dark()
+        } else {
+            Visuals::light()
+        };
+        if self.custom_colors {
+            let palette = self.palette(dark_mode);
+            visuals.panel_fill = palette.background;
+            visuals.selection.bg_fill = palette.accent;
+            visuals.hyperlink_color = palette.accent;
+        }
+
+        if ctx.style().visuals != visuals {
+            ctx.set_visuals(visuals);
+        }
+    }
+
+    pub fn palette(&self, dark_mode: bool) -> Palette {
+        match (self.custom_colors, dark_mode) {
+            (true, true) => self.dark,
+            (true, false) => self.light,
+            (false, true) => Palette::DARK,
+            (false, false) => Palette::LIGHT,
+        }
+    }
+
+    pub fn settings(&mut self, ui: &mut Ui) {
+        ComboBox::from_label(tr("Theme"))
+            .selected_text(match self.mode {
+                ThemeMode::System => tr("System"),
+                ThemeMode::Dark => tr("Dark"),
+                ThemeMode::Light => tr("Light"),
+            })
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut self.mode, ThemeMode::System, tr("System"));
+                ui.selectable_value(&mut self.mode, ThemeMode::Dark, tr("Dark"));
+                ui.selectable_value(&mut self.mode, ThemeMode::Light, tr("Light"));
+            });
+
+        ui.checkbox(&mut self.custom_colors, tr("Custom colors"));
+        ui.add_enabled_ui(self.custom_colors, |ui| {
+            let palette = if ui.style().visuals.dark_mode {
+                &mut self.dark
+            } else {
+                &mut self.light
+            };
+            ui.horizontal(|ui| {
+                ui.color_edit_button_srgba(&mut palette.background);
+                ui.label(tr("Background"));
+            });
+            ui.horizontal(|ui| {
+                ui.color_edit_button_srgba(&mut palette.panel);
+                ui.label(tr("Panel"));
+            });
+            ui.horizontal(|ui| {
+                ui.color_edit_button_srgba(&mut palette.accent);
+                ui.label(tr("Accent"));
+            });
+            if ui.button(tr("Reset colors")).clicked() {
+                self.dark = Palette::DARK;
+                self.light = Palette::LIGHT;
+            }
+        });
+    }
+}
@@ -0,0 +1,54 @@
+use egui::{DragValue, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::tr;
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimelapseConfig {
+    pub enabled: bool,
+    /// Time between two screenshots in s
+    pub interval: u64,
+}
+
+impl Default for TimelapseConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval: 30,
+        }
+    }
+}
+
+impl TimelapseConfig {
+    pub fn settings(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.enabled, tr("Save a screenshot every"));
+            ui.add(
+                DragValue::new(&mut self.interval)
+                    .clamp_range(1..=3600)
+                    .suffix(" s"),
+            );
+        });
+    }
+}
+
+/// Requests screenshots periodically, they are saved to the timelapse folder of the session
+#[derive(Default)]
+pub struct Timelapse {
+    last: u128,
+    /// The next screenshot belongs to the timelapse
+    pub pending: bool,
+}
+
+impl Timelapse {
+    /// Whether a screenshot should be taken now
+    pub fn due(&mut self, config: &TimelapseConfig, now: u128) -> bool {
+        if !config.enabled || self.pending || now < self.last + config.interval as u128 * 1000 {
+            return false;
+        }
+        self.last = now;
+        self.pending = true;
+        true
+    }
+}
@@ -0,0 +1,114 @@
+use egui::{DragValue, Grid, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::history::{slope, History};
+use crate::i18n::tr;
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrendConfig {
+    pub enabled: bool,
+    /// Cell voltage alarm threshold in mV
+    pub alarm_voltage: u16,
+    /// Window in s over which the weakest cell is tracked
+    pub window: u64,
+    /// Warn if the alarm threshold is projected to be crossed within this many s
+    pub horizon: u64,
+}
+
+impl Default for TrendConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            alarm_voltage: 3200,
+            window: 300,
+            horizon: 120,
+        }
+    }
+}
+
+impl TrendConfig {
+    pub fn settings(&mut self, ui: &mut Ui) {
+        ui.checkbox(&mut self.enabled, tr("Warn about the weakest cell trend"));
+        Grid::new("trend_config").show(ui, |ui| {
+            ui.label(tr("Alarm voltage"));
+            ui.add(
+                DragValue::new(&mut self.alarm_voltage)
+                    .clamp_range(2000..=4000)
+                    .suffix(" mV"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Tracking window"));
+            ui.add(
+                DragValue::new(&mut self.window)
+                    .clamp_range(10..=3600)
+                    .suffix(" s"),
+            );
+            ui.end_row();
+
+            ui.label(tr("Warning horizon"));
+            ui.add(
+                DragValue::new(&mut self.horizon)
+                    .clamp_range(10..=3600)
+                    .suffix(" s"),
+            );
+            ui.end_row();
+        });
+    }
+}
+
+pub struct WeakestCell {
+    pub index: usize,
+    // in mV
+    pub voltage: u16,
+    // in mV/s
+    pub slope: f64,
+    /// Projected time in s until the alarm voltage is crossed
+    pub time_to_alarm: Option<f64>,
+}
+
+impl WeakestCell {
+    /// Finds the cell that was the minimum most often during the window and extrapolates its
+    /// voltage trend
+    pub fn compute(config: &TrendConfig, history: &History) -> Option<Self> {
+        let samples: Vec<_> = history.window(config.window as u128 * 1000).collect();
+        let last = samples.last()?;
+
+        let mut counts = vec![0usize; last.cell_voltage.len()];
+        for s in &samples {
+            let min = s.cell_voltage.iter().enumerate().min_by_key(|(_, v)| **v);
+            if let Some((i, _)) = min {
+                if let Some(c) = counts.get_mut(i) {
+                    *c += 1;
+                }
+            }
+        }
+        let index = counts
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, c)| **c)
+            .map(|(i, _)| i)?;
+        let voltage = *last.cell_voltage.get(index)?;
+
+        let start = samples[0].time;
+        let slope = slope(samples.iter().filter_map(|s| {
+            let v = *s.cell_voltage.get(index)?;
+            Some(((s.time - start) as f64 / 1000.0, v as f64))
+        }))?;
+        let time_to_alarm =
+            (slope < 0.0).then(|| (voltage as f64 - config.alarm_voltage as f64).max(0.0) / -slope);
+
+        Some(Self {
+            index,
+            voltage,
+            slope,
+            time_to_alarm,
+        })
+    }
+
+    pub fn is_warning(&self, config: &TrendConfig) -> bool {
+        self.time_to_alarm
+            .is_some_and(|t| t <= config.horizon as f64)
+    }
+}
@@ -0,0 +1,234 @@
+use std::sync::RwLock;
+
+use egui::{ComboBox, DragValue, Grid, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::tr;
+
+static CURRENT: RwLock<Units> = RwLock::new(Units::DEFAULT);
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VoltageUnit {
+    Millivolt,
+    Volt,
+}
+
+impl VoltageUnit {
+    fn name(self) -> &'static str {
+        match self {
+            VoltageUnit::Millivolt => "mV",
+            VoltageUnit::Volt => "V",
+        }
+    }
+
+    fn default_decimals(self) -> usize {
+        match self {
+            VoltageUnit::Millivolt => 0,
+            VoltageUnit::Volt => 3,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TempUnit {
+    Celsius,
+    Fahrenheit,
+}
+
+impl TempUnit {
+    fn name(self) -> &'static str {
+        match self {
+            TempUnit::Celsius => "°C",
+            TempUnit::Fahrenheit => "°F",
+        }
+    }
+}
+
+/// How cell voltages and temperatures are displayed, the values are always stored in mV and °C
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Units {
+    pub voltage: VoltageUnit,
+    pub voltage_decimals: usize,
+    pub temp: TempUnit,
+    pub temp_decimals: usize,
+    /// Group the digits of large values, e.g. `4 160 mV`
+    pub thousands_separator: bool,
+}
+
+impl Default for Units {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl Units {
+    const DEFAULT: Units = Units {
+        voltage: VoltageUnit::Millivolt,
+        voltage_decimals: 0,
+        temp: TempUnit::Celsius,
+        temp_decimals: 1,
+        thousands_separator: false,
+    };
+
+    pub fn current() -> Self {
+        *CURRENT.read().unwrap()
+    }
+
+    pub fn set_current(self) {
+        *CURRENT.write().unwrap() = self;
+    }
+
+    pub fn settings(&mut self, ui: &mut Ui) {
+        Grid::new("units").show(ui, |ui| {
+            ui.label(tr("Cell voltage"));
+            let before = self.voltage;
+            ComboBox::from_id_source("voltage_unit")
+                .selected_text(self.voltage.name())
+                .show_ui(ui, |ui| {
+                    for unit in [VoltageUnit::Millivolt, VoltageUnit::Volt] {
+                        ui.selectable_value(&mut self.voltage, unit, unit.name());
+                    }
+                });
+            if self.voltage != before {
+                self.voltage_decimals = self.voltage.default_decimals();
+            }
+            ui.end_row();
+
+            ui.label(tr("Voltage decimal places"));
+            ui.add(DragValue::new(&mut self.voltage_decimals).clamp_range(0..=4));
+            ui.end_row();
+
+            ui.label(tr("Temperature"));
+            ComboBox::from_id_source("temp_unit")
+                .selected_text(self.temp.name())
+                .show_ui(ui, |ui| {
+                    for unit in [TempUnit::Celsius, TempUnit::Fahrenheit] {
+                        ui.selectable_value(&mut self.temp, unit, unit.name());
+                    }
+                });
+            ui.end_row();
+
+            ui.label(tr("Temperature decimal places"));
+            ui.add(DragValue::new(&mut self.temp_decimals).clamp_range(0..=3));
+            ui.end_row();
+
+            ui.label(tr("Thousands separator"));
+            ui.checkbox(&mut self.thousands_separator, "");
+            ui.end_row();
+        });
+    }
+}
+
+pub fn voltage_unit() -> &'static str {
+    Units::current().voltage.name()
+}
+
+/// Converts a cell voltage or voltage difference in mV to the display unit
+pub fn voltage_scale(mv: f64) -> f64 {
+    match Units::current().voltage {
+        VoltageUnit::Millivolt => mv,
+        VoltageUnit::Volt => mv / 1000.0,
+    }
+}
+
+/// A cell voltage in mV formatted in the display unit, without the unit
+pub fn voltage_value(mv: impl Into<f64>) -> String {
+    let units = Units::current();
+    format_number(voltage_scale(mv.into()), units.voltage_decimals, &units)
+}
+
+/// A cell voltage in mV formatted in the display unit, e.g. `4.160 V`
+pub fn voltage(mv: impl Into<f64>) -> String {
+    format!("{} {}", voltage_value(mv), voltage_unit())
+}
+
+/// Like `voltage`, but always with a sign
+pub fn voltage_diff(mv: impl Into<f64>) -> String {
+    let mv = mv.into();
+    let sign = if mv >= 0.0 { "+" } else { "" };
+    format!("{sign}{}", voltage(mv))
+}
+
+/// A voltage change in mV/min formatted in the display unit with one additional decimal place
+pub fn voltage_trend(mv_per_min: f64) -> String {
+    let decimals = Units::current().voltage_decimals + 1;
+    let value = voltage_scale(mv_per_min);
+    format!("{value:+.decimals$} {}/min", voltage_unit())
+}
+
+pub fn temp_unit() -> &'static str {
+    Units::current().temp.name()
+}
+
+/// Converts a temperature in °C to the display unit
+pub fn temp_scale(celsius: f64) -> f64 {
+    match Units::current().temp {
+        TempUnit::Celsius => celsius,
+        TempUnit::Fahrenheit => celsius * 1.8 + 32.0,
+    }
+}
+
+/// Converts a temperature difference in °C to the display unit
+pub fn temp_delta_scale(celsius: f64) -> f64 {
+    match Units::current().temp {
+        TempUnit::Celsius => celsius,
+        TempUnit::Fahrenheit => celsius * 1.8,
+    }
+}
+
+/// A temperature in °C formatted in the display unit, without the unit
+pub fn temp_value(celsius: impl Into<f64>) -> String {
+    let units = Units::current();
+    format_number(temp_scale(celsius.into()), units.temp_decimals, &units)
+}
+
+/// A temperature in °C formatted in the display unit, e.g. `31.5 °C`
+pub fn temp(celsius: impl Into<f64>) -> String {
+    format!("{} {}", temp_value(celsius), temp_unit())
+}
+
+/// A temperature difference in °C formatted in the display unit
+pub fn temp_delta(celsius: impl Into<f64>) -> String {
+    let units = Units::current();
+    let value = format_number(
+        temp_delta_scale(celsius.into()),
+        units.temp_decimals,
+        &units,
+    );
+    format!("{value} {}", units.temp.name())
+}
+
+/// Like `temp_delta`, but always with a sign
+pub fn temp_diff(celsius: impl Into<f64>) -> String {
+    let celsius = celsius.into();
+    let sign = if celsius >= 0.0 { "+" } else { "" };
+    format!("{sign}{}", temp_delta(celsius))
+}
+
+fn format_number(value: f64, decimals: usize, units: &Units) -> String {
+    let text = format!("{value:.decimals$}");
+    if !units.thousands_separator {
+        return text;
+    }
+
+    let (sign, digits) = match text.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", text.as_str()),
+    };
+    let (int, fraction) = match digits.split_once('.') {
+        Some((int, fraction)) => (int, Some(fraction)),
+        None => (digits, None),
+    };
+    let mut grouped = String::new();
+    for (i, c) in int.chars().enumerate() {
+        if i > 0 && (int.len() - i).is_multiple_of(3) {
+            grouped.push(' ');
+        }
+        grouped.push(c);
+    }
+    match fraction {
+        Some(fraction) => format!("{sign}{grouped}.{fraction}"),
+        None => format!("{sign}{grouped}"),
+    }
+}
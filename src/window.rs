@@ -0,0 +1,52 @@
+use egui::{ViewportCommand, WindowLevel};
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::tr;
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowOptions {
+    pub always_on_top: bool,
+    /// Closing the window only minimizes it, polling and recording continue in the background and
+    /// a new alarm requests the user's attention
+    pub minimize_on_close: bool,
+    /// The window level that was last sent to the viewport
+    #[serde(skip)]
+    applied_on_top: Option<bool>,
+    /// Set by the quit action to close the window even if `minimize_on_close` is enabled
+    #[serde(skip)]
+    pub quit: bool,
+}
+
+impl WindowOptions {
+    pub fn apply(&mut self, ctx: &egui::Context) {
+        if self.applied_on_top != Some(self.always_on_top) {
+            let level = match self.always_on_top {
+                true => WindowLevel::AlwaysOnTop,
+                false => WindowLevel::Normal,
+            };
+            ctx.send_viewport_cmd(ViewportCommand::WindowLevel(level));
+            self.applied_on_top = Some(self.always_on_top);
+        }
+
+        let close_requested = ctx.input(|i| i.viewport().close_requested());
+        if close_requested && self.minimize_on_close && !self.quit {
+            ctx.send_viewport_cmd(ViewportCommand::CancelClose);
+            ctx.send_viewport_cmd(ViewportCommand::Minimized(true));
+        }
+    }
+
+    pub fn quit(&mut self, ctx: &egui::Context) {
+        self.quit = true;
+        ctx.send_viewport_cmd(ViewportCommand::Close);
+    }
+
+    pub fn settings(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.always_on_top, tr("Always on top"));
+        ui.checkbox(
+            &mut self.minimize_on_close,
+            tr("Minimize instead of closing"),
+        )
+        .on_hover_text(tr("Polling and recording continue in the background"));
+    }
+}